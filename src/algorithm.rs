@@ -0,0 +1,602 @@
+//! Choosing how to search, with `--algorithm`
+//!
+//! Breadth-first search is the default, and always finds a shortest solution.
+//! The rest search the same space of both players' positions differently:
+//!
+//! - `dfs` dives down one line of moves at a time. Quick to find *a* solution,
+//!   but rarely the shortest.
+//! - `astar` searches the most promising positions first, going by how far
+//!   each player is from the exit on their own board. Still finds a shortest
+//!   solution, usually looking at far fewer positions.
+//! - `ida` is `astar` a depth limit at a time, using next to no memory.
+//! - `beam` keeps only the `--beam-width` most promising positions at each
+//!   depth. Fast on big boards, but can miss solutions.
+//!
+//! Every search tries the moves from a position in `--direction-order`, which
+//! picks between solutions that are just as short.
+
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::time::Instant;
+
+use crate::cli::Matches;
+use crate::interrupt;
+#[cfg(feature = "profile")]
+use crate::profile;
+use crate::rng::Rng;
+use crate::trace;
+use crate::verify::{self, Step};
+use crate::{apply, Board, Dir, Error, Player, PlayerState, Result, Stats};
+
+/// Every direction, in the order they're tried unless `--direction-order`
+/// says otherwise
+const DIRS: [Dir; 4] = [Dir::Up, Dir::Down, Dir::Right, Dir::Left];
+
+/// How many positions to look at between checks of the deadline
+const CHECK_EVERY: usize = 1024;
+
+/// A way of searching for a solution
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Algorithm {
+    Bfs,
+    Dfs,
+    Astar,
+    Ida,
+    Beam,
+}
+
+/// Every algorithm, in the order of their `ALGORITHM` numbers
+pub const ALL: [Algorithm; 5] = [
+    Algorithm::Bfs,
+    Algorithm::Dfs,
+    Algorithm::Astar,
+    Algorithm::Ida,
+    Algorithm::Beam,
+];
+
+/// The algorithm from `--algorithm`, as its place in [`ALL`]
+static ALGORITHM: AtomicU8 = AtomicU8::new(0);
+static BEAM_WIDTH: AtomicUsize = AtomicUsize::new(1000);
+/// The order from `--direction-order`, as two bits per place in [`DIRS`]
+static ORDER: AtomicU8 = AtomicU8::new(0b11_10_01_00);
+
+impl Algorithm {
+    /// Parse an algorithm name
+    pub fn parse(name: &str) -> Result<Self> {
+        ALL.into_iter()
+            .find(|a| a.name() == name)
+            .ok_or(Error::UnknownAlgorithm)
+    }
+
+    /// The algorithm's name on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Bfs => "bfs",
+            Algorithm::Dfs => "dfs",
+            Algorithm::Astar => "astar",
+            Algorithm::Ida => "ida",
+            Algorithm::Beam => "beam",
+        }
+    }
+}
+
+/// Parse a direction order like `UDRL`, with each direction once
+fn parse_order(text: &str) -> Result<[Dir; 4]> {
+    let usage =
+        || Error::Usage("--direction-order must have each of U, D, R and L once".to_string());
+    let dirs = Dir::parse_moves(text).map_err(|_| usage())?;
+    match <[Dir; 4]>::try_from(dirs) {
+        Ok(order) if DIRS.iter().all(|d| order.contains(d)) => Ok(order),
+        _ => Err(usage()),
+    }
+}
+
+/// The order the searches try moves in
+pub fn order() -> [Dir; 4] {
+    let packed = ORDER.load(Ordering::Relaxed);
+    std::array::from_fn(|i| DIRS[(packed >> (i * 2)) as usize & 3])
+}
+
+/// Pick the algorithm with `--algorithm`, and set `--beam-width` and
+/// `--direction-order`
+pub fn configure(matches: &Matches) -> Result<()> {
+    if let Some(name) = matches.value("algorithm") {
+        let algorithm = Algorithm::parse(name)?;
+        let i = ALL.iter().position(|a| *a == algorithm).unwrap();
+        ALGORITHM.store(i as u8, Ordering::Relaxed);
+    }
+
+    if let Some(value) = matches.value("beam-width") {
+        match value.parse() {
+            Ok(width) if width > 0 => BEAM_WIDTH.store(width, Ordering::Relaxed),
+            _ => return Err(Error::InvalidLimit),
+        }
+    }
+
+    if let Some(text) = matches.value("direction-order") {
+        let packed = parse_order(text)?
+            .iter()
+            .enumerate()
+            .map(|(i, dir)| (DIRS.iter().position(|d| d == dir).unwrap() as u8) << (i * 2))
+            .sum();
+        ORDER.store(packed, Ordering::Relaxed);
+    }
+
+    Ok(())
+}
+
+/// The algorithm searches use unless told otherwise
+pub fn configured() -> Algorithm {
+    ALL[ALGORITHM.load(Ordering::Relaxed) as usize]
+}
+
+/// Both players' positions
+type State = (Player, Player);
+
+/// How many moves each cell of a board is from the exit, for a player with
+/// the board to themselves. Cells missing can't reach the exit at all.
+pub fn distances(board: &Board) -> HashMap<Player, usize> {
+    #[cfg(feature = "profile")]
+    let _span = profile::span("distances");
+    let mut leads_to: HashMap<Option<Player>, Vec<Player>> = HashMap::new();
+
+    for (y, row) in board.tiles.iter().enumerate() {
+        for x in 0..row.len() {
+            let from = Player {
+                x: x as isize,
+                y: y as isize,
+            };
+            for dir in DIRS {
+                match apply(dir, board, from) {
+                    PlayerState::Success => leads_to.entry(None).or_default().push(from),
+                    PlayerState::Just(to) => leads_to.entry(Some(to)).or_default().push(from),
+                    PlayerState::Dead => {}
+                }
+            }
+        }
+    }
+
+    // Work backwards from the exit, written as `None`
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::from([(None, 0)]);
+    while let Some((cell, distance)) = queue.pop_front() {
+        for &from in leads_to.get(&cell).into_iter().flatten() {
+            if let Entry::Vacant(entry) = distances.entry(from) {
+                entry.insert(distance + 1);
+                queue.push_back((Some(from), distance + 1));
+            }
+        }
+    }
+
+    distances
+}
+
+/// Every cell a player with the board to themselves can stop on, starting
+/// from `start`
+pub fn reachable(board: &Board, start: Player) -> HashSet<Player> {
+    let mut seen = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(from) = queue.pop_front() {
+        for dir in DIRS {
+            if let PlayerState::Just(to) = apply(dir, board, from) {
+                if seen.insert(to) {
+                    queue.push_back(to);
+                }
+            }
+        }
+    }
+
+    seen
+}
+
+/// Which of the states is nearest to solved, by how far the player further
+/// from their exit has to go
+pub fn closest((b1, b2): (&Board, &Board), states: &[State]) -> Option<usize> {
+    let distances = [distances(b1), distances(b2)];
+
+    (0..states.len()).min_by_key(|&i| {
+        let (p1, p2) = states[i];
+        match (distances[0].get(&p1), distances[1].get(&p2)) {
+            (Some(d1), Some(d2)) => *d1.max(d2),
+            _ => usize::MAX,
+        }
+    })
+}
+
+/// A search over both boards
+struct Search<'a> {
+    b1: &'a Board,
+    b2: &'a Board,
+    /// How far each board's cells are from its exit
+    distances: [HashMap<Player, usize>; 2],
+    stats: &'a mut Stats,
+    deadline: Option<Instant>,
+    progress: &'a mut dyn FnMut(&Stats),
+}
+
+impl Search<'_> {
+    /// The fewest moves the state could possibly be solved in, or `None` if a
+    /// player can't reach the exit from where they are
+    fn estimate(&self, (p1, p2): State) -> Option<usize> {
+        let d1 = self.distances[0].get(&p1)?;
+        let d2 = self.distances[1].get(&p2)?;
+        Some(*d1.max(d2))
+    }
+
+    /// Count the state as looked at, giving up if the time's up
+    fn expand(&mut self, (p1, p2): State) -> Result<()> {
+        #[cfg(feature = "profile")]
+        let _span = profile::span("expand");
+        self.stats.paths += 1;
+        *self.stats.visits[0].entry(p1).or_default() += 1;
+        *self.stats.visits[1].entry(p2).or_default() += 1;
+
+        if self.stats.paths.is_multiple_of(CHECK_EVERY) {
+            (self.progress)(self.stats);
+            if interrupt::interrupted() {
+                return Err(Error::Interrupted);
+            }
+            if self.deadline.is_some_and(|d| Instant::now() >= d) {
+                return Err(Error::Timeout);
+            }
+        }
+        Ok(())
+    }
+
+    /// Note the line being followed when the search has to stop short
+    fn stop(&mut self, partial: Vec<Dir>, error: Error) -> Error {
+        self.stats.partial = partial;
+        error
+    }
+
+    /// Where each move from the state leads, `None` being out the exit, leaving
+    /// out moves that lose or can't lead to the exit
+    fn next(&self, (p1, p2): State) -> Vec<(Dir, Option<State>)> {
+        #[cfg(feature = "profile")]
+        let _span = profile::span("next");
+        order()
+            .into_iter()
+            .filter_map(|dir| {
+                let step = verify::step(dir, (self.b1, p1), (self.b2, p2));
+                let (outcome, to) = match step {
+                    Step::Solved => (trace::Outcome::Solved, (p1, p2)),
+                    Step::Moved(n1, n2) => (trace::Outcome::Moved, (n1, n2)),
+                    Step::Died(_) => (trace::Outcome::Fell, (p1, p2)),
+                    Step::ExitedAlone(_) => (trace::Outcome::ExitedAlone, (p1, p2)),
+                };
+                trace::record((p1, p2), dir, outcome, to);
+
+                match step {
+                    Step::Solved => Some((dir, None)),
+                    Step::Moved(n1, n2) => self.estimate((n1, n2)).map(|_| (dir, Some((n1, n2)))),
+                    Step::Died(_) | Step::ExitedAlone(_) => None,
+                }
+            })
+            .collect()
+    }
+
+    /// The moves to the state, following the trail of how each was reached
+    fn path(parents: &HashMap<State, (State, Dir)>, mut state: State) -> Vec<Dir> {
+        let mut moves = Vec::new();
+        while let Some(&(parent, dir)) = parents.get(&state) {
+            moves.push(dir);
+            state = parent;
+        }
+        moves.reverse();
+        moves
+    }
+
+    fn dfs(&mut self, start: State) -> Result<Vec<Dir>> {
+        let mut parents = HashMap::new();
+        let mut seen = HashSet::from([start]);
+        let mut stack = vec![start];
+
+        while let Some(state) = stack.pop() {
+            self.expand(state)
+                .map_err(|e| self.stop(Self::path(&parents, state), e))?;
+
+            for (dir, next) in self.next(state).into_iter().rev() {
+                match next {
+                    None => {
+                        let mut moves = Self::path(&parents, state);
+                        moves.push(dir);
+                        return Ok(moves);
+                    }
+                    Some(next) if seen.insert(next) => {
+                        parents.insert(next, (state, dir));
+                        stack.push(next);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        Err(Error::NoSolution)
+    }
+
+    fn astar(&mut self, start: State) -> Result<Vec<Dir>> {
+        let mut parents = HashMap::new();
+        let mut best = HashMap::from([(start, 0)]);
+        // Positions in the order found, with `None` for out the exit and the
+        // move that led there
+        let mut found = vec![(Some(start), None)];
+        // Indexes into `found`, by estimated total and then most moves made
+        let mut open = BinaryHeap::from([Reverse((
+            self.estimate(start).ok_or(Error::NoSolution)?,
+            Reverse(0),
+            0,
+        ))]);
+
+        while let Some(Reverse((_, Reverse(moves), i))) = open.pop() {
+            let (Some(state), _) = found[i] else {
+                let (parent, dir) = found[i].1.unwrap();
+                let mut path = Self::path(&parents, parent);
+                path.push(dir);
+                return Ok(path);
+            };
+            if best.get(&state).is_some_and(|&b| b < moves) {
+                continue;
+            }
+            self.expand(state)
+                .map_err(|e| self.stop(Self::path(&parents, state), e))?;
+
+            for (dir, next) in self.next(state) {
+                let estimate = match next {
+                    None => moves + 1,
+                    Some(next) if best.get(&next).is_none_or(|&b| moves + 1 < b) => {
+                        best.insert(next, moves + 1);
+                        parents.insert(next, (state, dir));
+                        moves + 1 + self.estimate(next).unwrap()
+                    }
+                    Some(_) => continue,
+                };
+                found.push((next, Some((state, dir))));
+                open.push(Reverse((estimate, Reverse(moves + 1), found.len() - 1)));
+            }
+        }
+
+        Err(Error::NoSolution)
+    }
+
+    /// Look for a solution from the state within `bound` moves in all,
+    /// returning the smallest estimate over the bound otherwise
+    fn ida_step(
+        &mut self,
+        state: State,
+        path: &mut Vec<Dir>,
+        on_path: &mut HashSet<State>,
+        bound: usize,
+    ) -> Result<std::result::Result<(), usize>> {
+        let estimate = path.len() + self.estimate(state).unwrap();
+        if estimate > bound {
+            return Ok(Err(estimate));
+        }
+        self.expand(state).map_err(|e| self.stop(path.clone(), e))?;
+
+        let mut over = usize::MAX;
+        for (dir, next) in self.next(state) {
+            path.push(dir);
+            let Some(next) = next else {
+                return Ok(Ok(()));
+            };
+            if on_path.insert(next) {
+                match self.ida_step(next, path, on_path, bound)? {
+                    Ok(()) => return Ok(Ok(())),
+                    Err(estimate) => over = over.min(estimate),
+                }
+                on_path.remove(&next);
+            }
+            path.pop();
+        }
+
+        Ok(Err(over))
+    }
+
+    fn ida(&mut self, start: State) -> Result<Vec<Dir>> {
+        let mut bound = self.estimate(start).ok_or(Error::NoSolution)?;
+
+        loop {
+            let mut path = Vec::new();
+            let mut on_path = HashSet::from([start]);
+            self.stats.depth = bound;
+
+            match self.ida_step(start, &mut path, &mut on_path, bound)? {
+                Ok(()) => return Ok(path),
+                Err(usize::MAX) => return Err(Error::NoSolution),
+                Err(over) => bound = over,
+            }
+        }
+    }
+
+    fn beam(&mut self, start: State, width: usize) -> Result<Vec<Dir>> {
+        let mut parents = HashMap::new();
+        let mut seen = HashSet::from([start]);
+        let mut frontier = vec![start];
+        let mut rng = Rng::new();
+
+        while !frontier.is_empty() {
+            self.stats.depth += 1;
+            let mut next_frontier = Vec::new();
+
+            for state in frontier {
+                self.expand(state)
+                    .map_err(|e| self.stop(Self::path(&parents, state), e))?;
+
+                for (dir, next) in self.next(state) {
+                    match next {
+                        None => {
+                            let mut moves = Self::path(&parents, state);
+                            moves.push(dir);
+                            return Ok(moves);
+                        }
+                        Some(next) if seen.insert(next) => {
+                            parents.insert(next, (state, dir));
+                            next_frontier.push(next);
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+
+            // Shuffling first breaks ties between equally promising
+            // positions by the seed, rather than always the same way
+            rng.shuffle(&mut next_frontier);
+            next_frontier.sort_by_key(|&s| self.estimate(s));
+            next_frontier.truncate(width);
+            frontier = next_frontier;
+        }
+
+        Err(Error::NoSolution)
+    }
+}
+
+/// Search for a solution with any algorithm but breadth-first search, which
+/// the solver does itself
+pub fn search(
+    algorithm: Algorithm,
+    (b1, p1): (&Board, Player),
+    (b2, p2): (&Board, Player),
+    stats: &mut Stats,
+    deadline: Option<Instant>,
+    progress: &mut dyn FnMut(&Stats),
+) -> Result<Vec<Dir>> {
+    let mut search = Search {
+        b1,
+        b2,
+        distances: [distances(b1), distances(b2)],
+        stats,
+        deadline,
+        progress,
+    };
+    if search.estimate((p1, p2)).is_none() {
+        return Err(Error::NoSolution);
+    }
+
+    let moves = match algorithm {
+        Algorithm::Bfs => unreachable!("breadth-first search is the solver's own"),
+        Algorithm::Dfs => search.dfs((p1, p2)),
+        Algorithm::Astar => search.astar((p1, p2)),
+        Algorithm::Ida => search.ida((p1, p2)),
+        Algorithm::Beam => search.beam((p1, p2), BEAM_WIDTH.load(Ordering::Relaxed)),
+    }?;
+
+    search.stats.depth = search.stats.depth.max(moves.len());
+    Ok(moves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Algorithm;
+    use crate::generate::{self, Layout, Params};
+    use crate::rng::Rng;
+    use crate::verify::{self, Verdict};
+    use crate::Error;
+
+    const PUZZLE: &str = "  x\n...\n.I.\n.R.\n\n  x\n...\nTPT\n.R.";
+
+    #[test]
+    fn distances() {
+        let ((b1, p1), _) = crate::parse_puzzle(PUZZLE).unwrap();
+        let distances = super::distances(&b1);
+
+        assert_eq!(Some(&3), distances.get(&p1));
+    }
+
+    #[test]
+    fn parse_order() {
+        use crate::Dir::*;
+
+        assert_eq!(Ok([Left, Right, Up, Down]), super::parse_order("lrud"));
+        assert!(super::parse_order("UDR").is_err());
+        assert!(super::parse_order("UDRR").is_err());
+        assert!(super::parse_order("4U").is_err());
+    }
+
+    #[test]
+    fn every_algorithm_solves() {
+        let shortest = crate::search_with(PUZZLE, Algorithm::Bfs).unwrap().0;
+
+        for algorithm in super::ALL {
+            let (moves, _) = crate::search_with(PUZZLE, algorithm).unwrap();
+            assert_eq!(
+                Ok(Verdict::Solved { moves: moves.len() }),
+                verify::verify(PUZZLE, &moves),
+                "{:?}",
+                algorithm
+            );
+            if matches!(algorithm, Algorithm::Astar | Algorithm::Ida) {
+                assert_eq!(shortest.len(), moves.len(), "{:?}", algorithm);
+            }
+        }
+
+        for algorithm in super::ALL {
+            assert_eq!(
+                Some(Error::NoSolution),
+                crate::search_with("x\nR\n\nx\nP\nR", algorithm).err(),
+                "{:?}",
+                algorithm
+            );
+        }
+    }
+
+    /// How many random puzzles `random_solutions_replay` tries
+    const CASES: u64 = 40;
+
+    #[test]
+    fn random_solutions_replay() {
+        let mut tried = 0;
+        for seed in 0..CASES {
+            let mut rng = Rng::from_seed(seed);
+            let params = Params {
+                width: 3 + rng.below(4),
+                height: 3 + rng.below(4),
+                layout: [Layout::Random, Layout::Backtracker, Layout::Prim][rng.below(3)],
+                walls: 0.3 * (rng.below(4) as f64 / 3.0),
+                ice: 0.3 * (rng.below(4) as f64 / 3.0),
+                pits: rng.below(3),
+                teleports: rng.chance(0.5),
+                tries: 200,
+                moves: None,
+                nodes: None,
+            };
+            // Not every draw of the parameters leaves room for a solution
+            let Ok(puzzle) = generate::generate(&params, seed) else {
+                continue;
+            };
+            let shortest = crate::search_with(&puzzle, Algorithm::Bfs).unwrap().0;
+            tried += 1;
+
+            for algorithm in super::ALL {
+                // Beam search is allowed to miss
+                let Ok((moves, _)) = crate::search_with(&puzzle, algorithm) else {
+                    assert_eq!(Algorithm::Beam, algorithm, "seed {}:\n{}", seed, puzzle);
+                    continue;
+                };
+                assert_eq!(
+                    Ok(Verdict::Solved { moves: moves.len() }),
+                    verify::verify(&puzzle, &moves),
+                    "{:?} on seed {}:\n{}",
+                    algorithm,
+                    seed,
+                    puzzle
+                );
+                match algorithm {
+                    Algorithm::Bfs | Algorithm::Astar | Algorithm::Ida => {
+                        assert_eq!(
+                            shortest.len(),
+                            moves.len(),
+                            "{:?} on seed {}",
+                            algorithm,
+                            seed
+                        )
+                    }
+                    Algorithm::Dfs | Algorithm::Beam => assert!(moves.len() >= shortest.len()),
+                }
+            }
+        }
+        assert!(tried > CASES / 2, "only {} puzzles generated", tried);
+    }
+}
@@ -0,0 +1,93 @@
+//! Counting heap memory, with the `alloc-stats` feature
+//!
+//! It's memory, not time, that stops a big search: the set of positions seen
+//! grows until the machine runs out. Every allocation goes through a counter
+//! here, so a solve can report the most it held at once.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The system allocator, keeping count of the bytes held
+struct Counting;
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Bytes held right now
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+/// The most bytes held since the last reset
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+/// Bytes held at the last reset, which the peak is counted from
+static BASE: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let now = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(now, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            let now = match new_size >= layout.size() {
+                true => {
+                    CURRENT.fetch_add(new_size - layout.size(), Ordering::Relaxed)
+                        + (new_size - layout.size())
+                }
+                false => {
+                    CURRENT.fetch_sub(layout.size() - new_size, Ordering::Relaxed)
+                        - (layout.size() - new_size)
+                }
+            };
+            PEAK.fetch_max(now, Ordering::Relaxed);
+        }
+        new
+    }
+}
+
+/// Start counting the peak again from what's held now
+pub fn reset() {
+    let now = CURRENT.load(Ordering::Relaxed);
+    BASE.store(now, Ordering::Relaxed);
+    PEAK.store(now, Ordering::Relaxed);
+}
+
+/// The most bytes held at once since the last reset, over what was held then
+pub fn peak() -> usize {
+    PEAK.load(Ordering::Relaxed)
+        .saturating_sub(BASE.load(Ordering::Relaxed))
+}
+
+/// A number of bytes in the largest unit that keeps it above 1
+pub fn bytes(n: usize) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = n as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < units.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{} B", n),
+        _ => format!("{:.1} {}", size, units[unit]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn bytes() {
+        assert_eq!("1.0 MiB", super::bytes(1 << 20));
+        assert_eq!("512 B", super::bytes(512));
+    }
+}
@@ -0,0 +1,126 @@
+//! Playing a solution back in the terminal
+
+use std::io::Write;
+use std::time::Duration;
+
+use crate::cli::Matches;
+use crate::{Board, Dir, Error, Player, Result, Tile};
+
+/// Clear the terminal and move the cursor to the top
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+/// Parse the pause between frames from `--delay`, like `200ms` or `0.5s`
+pub fn parse_delay(matches: &Matches) -> Result<Duration> {
+    let value = matches.value("delay").unwrap_or("200ms");
+    let invalid = || Error::Usage(format!("invalid delay {}", value));
+
+    let (number, scale) = if let Some(ms) = value.strip_suffix("ms") {
+        (ms, 0.001)
+    } else if let Some(s) = value.strip_suffix('s') {
+        (s, 1.0)
+    } else {
+        (value, 0.001)
+    };
+
+    let secs = number.parse::<f64>().map_err(|_| invalid())? * scale;
+    Duration::try_from_secs_f64(secs).map_err(|_| invalid())
+}
+
+/// Every cell a player passes through on a single move, sliding over ice and
+/// hopping through teleports on the way
+pub fn path(dir: Dir, board: &Board, from: Player) -> Vec<Player> {
+    let mut cells = vec![from];
+
+    loop {
+        let to = cells.last().unwrap().hop(dir);
+
+        match board.get_tile(to) {
+            Tile::Wall => break,
+            Tile::Ice => cells.push(to),
+            Tile::Teleport => {
+                cells.push(to);
+                cells.push(to.teleport(board));
+                break;
+            }
+            Tile::None | Tile::Pit | Tile::Exit => {
+                cells.push(to);
+                break;
+            }
+        }
+    }
+
+    cells
+}
+
+/// The positions of both players in each frame of the animation, along with
+/// the move being made
+pub fn frames(
+    b1: &Board,
+    b2: &Board,
+    start: (Player, Player),
+    moves: &[Dir],
+) -> Vec<(usize, Player, Player)> {
+    let (mut p1, mut p2) = start;
+    let mut frames = vec![(0, p1, p2)];
+
+    for (i, &dir) in moves.iter().enumerate() {
+        let (path1, path2) = (path(dir, b1, p1), path(dir, b2, p2));
+
+        for step in 1..path1.len().max(path2.len()) {
+            p1 = path1[step.min(path1.len() - 1)];
+            p2 = path2[step.min(path2.len() - 1)];
+            frames.push((i + 1, p1, p2));
+        }
+    }
+
+    frames
+}
+
+/// Play the moves on both boards, one frame per cell moved
+pub fn animate(input: &str, moves: &[Dir], delay: Duration) -> Result<()> {
+    let ((b1, p1), (b2, p2)) = crate::parse_puzzle(input)?;
+    let mut stdout = std::io::stdout().lock();
+
+    for (step, p1, p2) in frames(&b1, &b2, (p1, p2), moves) {
+        let heading = match step {
+            0 => "Start".to_string(),
+            n => format!("Move {}/{}: {:?}", n, moves.len(), moves[n - 1]),
+        };
+
+        write!(
+            stdout,
+            "{}{}\n\n{}",
+            CLEAR,
+            heading,
+            crate::render::puzzle((&b1, p1), (&b2, p2))
+        )
+        .and_then(|_| stdout.flush())
+        .map_err(|e| Error::Io(e.kind()))?;
+
+        std::thread::sleep(delay);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Dir::*;
+    use crate::{Board, Player};
+
+    #[test]
+    fn slides_and_teleports() {
+        let board = Board::parse(" x\n.IIT\nT...").unwrap();
+        let cell = |x, y| Player { x, y };
+
+        assert_eq!(
+            vec![cell(0, 0), cell(1, 0), cell(2, 0), cell(3, 0), cell(0, 1)],
+            super::path(Right, &board, cell(0, 0))
+        );
+        assert_eq!(vec![cell(0, 1)], super::path(Left, &board, cell(0, 1)));
+
+        let frames = super::frames(&board, &board, (cell(0, 0), cell(1, 1)), &[Right]);
+        assert_eq!(5, frames.len());
+        assert_eq!((1, cell(0, 1), cell(2, 1)), frames[4]);
+    }
+}
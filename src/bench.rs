@@ -0,0 +1,206 @@
+//! Timing the solver over a directory of puzzles
+//!
+//! Every puzzle is solved `--runs` times with the configured algorithm, and
+//! the table shows the spread of the times along with the nodes expanded.
+//! `--save-baseline FILE` keeps the results, and `--baseline FILE` compares a
+//! later run against them, to catch a change making the solver slower.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::cli::Matches;
+use crate::json::Json;
+use crate::{input, output, Error, Result};
+
+/// How one puzzle did over all its runs
+#[derive(Debug)]
+struct Timing {
+    puzzle: String,
+    /// The solution's length, or why there isn't one
+    moves: std::result::Result<usize, String>,
+    nodes: usize,
+    /// Every run's time, fastest first
+    times: Vec<Duration>,
+}
+
+impl Timing {
+    /// The time at the given percentile, by nearest rank
+    fn percentile(&self, p: usize) -> Duration {
+        let rank = (p * self.times.len()).div_ceil(100).max(1);
+        self.times[rank - 1]
+    }
+}
+
+/// Solve the puzzle `runs` times. A puzzle that fails does so the same way
+/// every time, so it only gets the one run.
+fn measure(puzzle: &str, input: &str, runs: usize) -> Timing {
+    let mut times = Vec::new();
+    let mut result = Err(Error::NoSolution);
+
+    for _ in 0..runs {
+        let start = Instant::now();
+        result = crate::search(input);
+        times.push(start.elapsed());
+        if result.is_err() {
+            break;
+        }
+    }
+    times.sort();
+
+    Timing {
+        puzzle: puzzle.to_string(),
+        moves: match &result {
+            Ok((moves, _)) => Ok(moves.len()),
+            Err(err) => Err(format!("{:?}", err)),
+        },
+        nodes: result.as_ref().map_or(0, |(_, stats)| stats.paths),
+        times,
+    }
+}
+
+/// The puzzle files in the directory, by name
+pub fn puzzles(dir: &str) -> Result<Vec<String>> {
+    let mut names = std::fs::read_dir(dir)
+        .map_err(|e| Error::Io(e.kind()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.'))
+        .collect::<Vec<_>>();
+    names.sort();
+    Ok(names)
+}
+
+/// A time in milliseconds
+fn ms(time: Duration) -> f64 {
+    time.as_secs_f64() * 1000.0
+}
+
+/// The results to compare later runs against
+fn baseline(timings: &[Timing]) -> Json {
+    let puzzles = timings
+        .iter()
+        .map(|t| {
+            let result = Json::object([
+                ("p50_ms", ms(t.percentile(50)).into()),
+                ("nodes", t.nodes.into()),
+            ]);
+            (t.puzzle.clone(), result)
+        })
+        .collect();
+
+    Json::object([
+        ("version", env!("CARGO_PKG_VERSION").into()),
+        ("puzzles", Json::Object(puzzles)),
+    ])
+}
+
+/// How the median time and nodes changed since the baseline
+fn change(timing: &Timing, baseline: &Json) -> String {
+    let Some(before) = baseline.get("puzzles").and_then(|p| p.get(&timing.puzzle)) else {
+        return "new".to_string();
+    };
+    let number = |key| before.get(key).and_then(Json::as_f64).unwrap_or(0.0);
+
+    let (p50, was) = (ms(timing.percentile(50)), number("p50_ms"));
+    let time = match was > 0.0 {
+        true => format!("{:+.0}%", (p50 - was) / was * 100.0),
+        false => "-".to_string(),
+    };
+    let nodes = timing.nodes as f64 - number("nodes");
+
+    format!("{} time, {:+} nodes", time, nodes)
+}
+
+/// Lay the timings out in a table, compared to the baseline if there is one
+fn table(timings: &[Timing], baseline: Option<&Json>) -> String {
+    let mut cells = vec![[
+        "puzzle",
+        "moves",
+        "nodes",
+        "p50",
+        "p90",
+        "max",
+        // The column stays empty without a baseline
+        if baseline.is_some() {
+            "vs baseline"
+        } else {
+            ""
+        },
+    ]
+    .map(String::from)];
+
+    for timing in timings {
+        let time = |p| format!("{:.1}ms", ms(timing.percentile(p)));
+        cells.push([
+            timing.puzzle.clone(),
+            match &timing.moves {
+                Ok(moves) => moves.to_string(),
+                Err(error) => error.clone(),
+            },
+            timing.nodes.to_string(),
+            time(50),
+            time(90),
+            time(100),
+            baseline.map_or(String::new(), |b| change(timing, b)),
+        ]);
+    }
+
+    output::table(&cells)
+}
+
+/// Benchmark every puzzle in the directory the way the flags ask
+pub fn bench(matches: &Matches) -> Result<String> {
+    let dir = matches.arg(0).unwrap();
+    let runs = match matches.value("runs") {
+        Some(n) => n
+            .parse()
+            .ok()
+            .filter(|&n| n > 0)
+            .ok_or(Error::Usage("--runs takes a positive number".to_string()))?,
+        None => 5,
+    };
+    let previous = match matches.value("baseline") {
+        Some(path) => Some(Json::parse(&input::file(path)?)?),
+        None => None,
+    };
+
+    let mut timings = Vec::new();
+    for name in puzzles(dir)? {
+        let input = input::file(&Path::new(dir).join(&name).to_string_lossy())?;
+        timings.push(measure(&name, &input, runs));
+    }
+
+    if let Some(path) = matches.value("save-baseline") {
+        std::fs::write(path, format!("{}\n", baseline(&timings)))
+            .map_err(|e| Error::Io(e.kind()))?;
+    }
+
+    Ok(table(&timings, previous.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Timing;
+    use crate::json::Json;
+
+    #[test]
+    fn table() {
+        let timing = Timing {
+            puzzle: "corridor.txt".to_string(),
+            moves: Ok(5),
+            nodes: 120,
+            times: [2, 3, 4, 10].map(Duration::from_millis).to_vec(),
+        };
+        let baseline =
+            Json::parse(r#"{"puzzles":{"corridor.txt":{"p50_ms":2,"nodes":100}}}"#).unwrap();
+
+        assert_eq!(
+            "puzzle        moves  nodes  p50    p90     max     vs baseline\n\
+             corridor.txt  5      120    3.0ms  10.0ms  10.0ms  +50% time, +20 nodes\n",
+            super::table(&[timing], Some(&baseline))
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Proof that a puzzle has no solution
+//!
+//! A certificate lists positions, one `x1,y1 x2,y2` per line. It proves the
+//! puzzle can't be solved when the start is one of them and every move from
+//! each of them either loses or leads to another of them: then no sequence of
+//! moves gets out of the set, and none of them solves the puzzle. Checking
+//! that takes one move from each position, so it needs no search and no trust
+//! in the solver.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::graph::{Graph, State, DIRS};
+use crate::verify::{self, Step};
+use crate::{Board, Dir, Error, Player, Result};
+
+/// The first line of every certificate
+const HEADER: &str = "# hive-mind-solver unsolvability certificate";
+
+/// Write a certificate for a puzzle that has no solution
+pub fn write(input: &str) -> Result<String> {
+    let graph = Graph::of(input)?;
+    if graph.to_goal()[0].is_some() {
+        return Err(Error::Usage(
+            "the puzzle has a solution, so there's nothing to certify".to_string(),
+        ));
+    }
+
+    let mut out = format!("{}\n", HEADER);
+    for (p1, p2) in &graph.states {
+        out.push_str(&format!("{},{} {},{}\n", p1.x, p1.y, p2.x, p2.y));
+    }
+    Ok(out)
+}
+
+/// Read the positions out of a certificate, in order
+fn parse(certificate: &str) -> Result<Vec<State>> {
+    let player = |text: &str| {
+        let (x, y) = text.split_once(',')?;
+        Some(Player {
+            x: x.parse().ok()?,
+            y: y.parse().ok()?,
+        })
+    };
+
+    certificate
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (p1, p2) = line.split_once(' ').ok_or(Error::InvalidCertificate)?;
+            Ok((
+                player(p1).ok_or(Error::InvalidCertificate)?,
+                player(p2.trim()).ok_or(Error::InvalidCertificate)?,
+            ))
+        })
+        .collect()
+}
+
+/// What checking a certificate found
+#[derive(Debug, PartialEq)]
+pub enum Finding {
+    /// The certificate proves there's no solution
+    Holds { states: usize },
+    /// The start isn't among the positions
+    MissingStart,
+    /// A move from one of the positions solves the puzzle
+    Solves { from: State, dir: Dir },
+    /// A move from one of the positions leads to one that isn't listed
+    Escapes { from: State, dir: Dir, to: State },
+}
+
+impl Finding {
+    /// Whether the certificate proves there's no solution
+    pub fn holds(&self) -> bool {
+        matches!(self, Finding::Holds { .. })
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = |(p1, p2): &State| format!("({}, {}) and ({}, {})", p1.x, p1.y, p2.x, p2.y);
+
+        match self {
+            Finding::Holds { states } => write!(
+                f,
+                "The certificate holds: no move leaves its {} positions, so there's no solution",
+                states
+            ),
+            Finding::MissingStart => write!(f, "The start isn't one of the positions"),
+            Finding::Solves { from, dir } => {
+                write!(f, "Moving {:?} from {} solves the puzzle", dir, at(from))
+            }
+            Finding::Escapes { from, dir, to } => write!(
+                f,
+                "Moving {:?} from {} leads to {}, which isn't listed",
+                dir,
+                at(from),
+                at(to)
+            ),
+        }
+    }
+}
+
+/// Whether the player is on the board or just off its edge, where stepping
+/// from can't overflow
+fn near(board: &Board, p: Player) -> bool {
+    let width = board.tiles.iter().map(Vec::len).max().unwrap_or(0) as isize;
+    let height = board.tiles.len() as isize;
+
+    (-1..=width).contains(&p.x) && (-1..=height).contains(&p.y)
+}
+
+/// Check a certificate against the puzzle
+pub fn check(certificate: &str, input: &str) -> Result<Finding> {
+    let listed = parse(certificate)?;
+    let states: HashSet<State> = listed.iter().copied().collect();
+    let ((b1, p1), (b2, p2)) = crate::parse_puzzle(input)?;
+    if !listed
+        .iter()
+        .all(|&(q1, q2)| near(&b1, q1) && near(&b2, q2))
+    {
+        return Err(Error::InvalidCertificate);
+    }
+    if !states.contains(&(p1, p2)) {
+        return Ok(Finding::MissingStart);
+    }
+
+    for &from in &listed {
+        for dir in DIRS {
+            match verify::step(dir, (&b1, from.0), (&b2, from.1)) {
+                Step::Solved => return Ok(Finding::Solves { from, dir }),
+                Step::Moved(n1, n2) if !states.contains(&(n1, n2)) => {
+                    return Ok(Finding::Escapes {
+                        from,
+                        dir,
+                        to: (n1, n2),
+                    })
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(Finding::Holds {
+        states: states.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Finding;
+    use crate::{Dir, Error, Player};
+
+    #[test]
+    fn certificate() {
+        // Player 2 can't get past the wall without player 1 going out alone
+        let puzzle = " x\n.R.\n\n x\nWW.\n.R.";
+        let certificate = super::write(puzzle).unwrap();
+
+        assert_eq!(
+            Finding::Holds { states: 9 },
+            super::check(&certificate, puzzle).unwrap()
+        );
+
+        let cut = certificate.replace("2,0 2,1\n", "");
+        assert_eq!(
+            Finding::Escapes {
+                from: (Player { x: 1, y: 0 }, Player { x: 1, y: 1 }),
+                dir: Dir::Right,
+                to: (Player { x: 2, y: 0 }, Player { x: 2, y: 1 })
+            },
+            super::check(&cut, puzzle).unwrap()
+        );
+        assert!(super::write(" x\n.R.\n\n x\n.R.").is_err());
+
+        let off_board = format!("{}9223372036854775807,0 1,0\n", certificate);
+        assert_eq!(
+            Err(Error::InvalidCertificate),
+            super::check(&off_board, puzzle)
+        );
+    }
+}
@@ -0,0 +1,927 @@
+//! Command-line parsing
+//!
+//! Each subcommand declares its positional arguments and flags in [`COMMANDS`],
+//! which drives both parsing and the generated `--help`.
+
+use crate::{Error, Result};
+
+/// A flag accepted by a command
+pub struct Flag {
+    pub long: &'static str,
+    pub short: Option<char>,
+    /// The name of the flag's value, for flags that take one
+    pub value: Option<&'static str>,
+    pub help: &'static str,
+}
+
+/// A flag without a value
+const fn switch(long: &'static str, help: &'static str) -> Flag {
+    Flag {
+        long,
+        short: None,
+        value: None,
+        help,
+    }
+}
+
+/// A flag taking a value
+const fn option(long: &'static str, value: &'static str, help: &'static str) -> Flag {
+    Flag {
+        long,
+        short: None,
+        value: Some(value),
+        help,
+    }
+}
+
+/// A subcommand
+pub struct Command {
+    pub name: &'static str,
+    pub about: &'static str,
+    /// What failed, for error messages ("Couldn't solve puzzle")
+    pub failure: &'static str,
+    /// Positional arguments, written `<NAME>` when required and `[NAME]` when
+    /// optional, with a trailing `...` on the last one if it can repeat
+    pub args: &'static [&'static str],
+    pub flags: &'static [&'static [Flag]],
+}
+
+/// Flags accepted by every command
+const GLOBAL_FLAGS: &[Flag] = &[
+    Flag {
+        long: "help",
+        short: Some('h'),
+        value: None,
+        help: "Print help",
+    },
+    Flag {
+        long: "quiet",
+        short: Some('q'),
+        value: None,
+        help: "Print nothing but the result",
+    },
+    Flag {
+        long: "verbose",
+        short: Some('v'),
+        value: None,
+        help: "Narrate the search (repeat for more detail)",
+    },
+    switch("trace", "Narrate every move the search tries"),
+    option(
+        "why-pruned",
+        "K",
+        "Say why the search dropped each of its first K dead ends",
+    ),
+    option(
+        "config",
+        "FILE",
+        "Read defaults from FILE instead of ~/.config/hive-mind-solver/config.toml",
+    ),
+    option(
+        "log-file",
+        "FILE",
+        "Write the full narration to FILE, whatever the verbosity",
+    ),
+    option(
+        "trace-out",
+        "FILE",
+        "Record every move the search tries to FILE, in binary",
+    ),
+    option("color", "WHEN", "Color boards: auto, always or never"),
+    option(
+        "legend",
+        "MODE",
+        "Explain the symbols under drawn boards: on (default) or off",
+    ),
+    option(
+        "max-width",
+        "N",
+        "Refuse boards wider than this (default 32)",
+    ),
+    option(
+        "max-height",
+        "N",
+        "Refuse boards taller than this (default 32)",
+    ),
+    option(
+        "max-cells",
+        "N",
+        "Refuse boards with more cells than this (default 1024)",
+    ),
+    option(
+        "algorithm",
+        "NAME",
+        "Search with bfs (default), dfs, astar, ida or beam",
+    ),
+    option(
+        "beam-width",
+        "N",
+        "Positions beam search keeps at each depth (default 1000)",
+    ),
+    option(
+        "direction-order",
+        "ORDER",
+        "Try moves in this order, like LRUD, picking between equally short solutions (default UDRL)",
+    ),
+    option(
+        "timeout",
+        "SECS",
+        "Give up on searches that take longer than this",
+    ),
+    option(
+        "seed",
+        "N",
+        "Seed everything random, like beam search's tie-breaking (default 0)",
+    ),
+];
+
+/// Flags choosing where the puzzle comes from, instead of a file or stdin
+const INPUT_FLAGS: &[Flag] = &[
+    Flag {
+        long: "expr",
+        short: Some('e'),
+        value: Some("PUZZLE"),
+        help: "Read the puzzle from the argument, with \\n for newlines",
+    },
+    switch("clipboard", "Read the puzzle from the system clipboard"),
+    option("url", "URL", "Download the puzzle over HTTP(S)"),
+    option("level", "ID", "Pick a level out of a level pack"),
+];
+
+/// Flags that write the result back to the puzzle file
+const WRITE_FLAGS: &[Flag] = &[switch("write", "Rewrite the file in place")];
+
+/// Where solves get recorded, with the `sqlite` feature
+#[cfg(feature = "sqlite")]
+const RESULTS_FLAGS: &[Flag] = &[option(
+    "results-db",
+    "FILE",
+    "Record solves in FILE instead of the default results database",
+)];
+#[cfg(not(feature = "sqlite"))]
+const RESULTS_FLAGS: &[Flag] = &[];
+
+/// Sampling the solve, with the `profile` feature
+#[cfg(feature = "profile")]
+const PROFILE_FLAGS: &[Flag] = &[option(
+    "profile",
+    "FILE",
+    "Sample the solve and draw where the time went to FILE as a flamegraph",
+)];
+#[cfg(not(feature = "profile"))]
+const PROFILE_FLAGS: &[Flag] = &[];
+
+/// Every subcommand, with the first being the default
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "solve",
+        about: "Find the shortest solution (default)",
+        failure: "solve puzzle",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[
+                option(
+                    "output",
+                    "FORMAT",
+                    "Print the solution as text, compact, rle or json",
+                ),
+                switch("animate", "Play the solution back on both boards first"),
+                switch("explain", "Say what each move does to both players"),
+                switch(
+                    "check",
+                    "Show how the puzzle was read and stop without solving",
+                ),
+                switch(
+                    "enforce-par",
+                    "Fail if the solution takes more moves than the puzzle's par",
+                ),
+                switch(
+                    "heatmap",
+                    "Shade cells by how often the search visited them",
+                ),
+                switch("watch", "Check and solve FILE again every time it's saved"),
+                option(
+                    "metrics-out",
+                    "FILE",
+                    "Write the search's performance data to FILE as JSON",
+                ),
+                option(
+                    "certificate",
+                    "FILE",
+                    "If there's no solution, write every reachable position to FILE as proof",
+                ),
+                option(
+                    "checkpoint",
+                    "FILE",
+                    "On Ctrl-C, save the line the search was following to FILE",
+                ),
+                option(
+                    "delay",
+                    "TIME",
+                    "Time between animation frames (default 200ms)",
+                ),
+                switch(
+                    "serve-stdio",
+                    "Answer one JSON request per stdin line instead",
+                ),
+            ],
+            RESULTS_FLAGS,
+            PROFILE_FLAGS,
+        ],
+    },
+    Command {
+        name: "play",
+        about: "Play the puzzle yourself",
+        failure: "play puzzle",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[
+                option(
+                    "report",
+                    "FILE",
+                    "Write how your solution compares to the shortest to FILE",
+                ),
+                option("resume", "FILE", "Pick up a game saved with `save FILE`"),
+                option(
+                    "stats-file",
+                    "FILE",
+                    "Keep per-level play stats in FILE instead of the default",
+                ),
+            ],
+        ],
+    },
+    Command {
+        name: "tui",
+        about: "Work through the puzzle on a full-screen dashboard",
+        failure: "run dashboard",
+        args: &["[FILE]"],
+        flags: &[INPUT_FLAGS],
+    },
+    Command {
+        name: "replay",
+        about: "Step back and forth through the solution",
+        failure: "replay solution",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[option(
+                "moves",
+                "MOVES",
+                "Replay these moves instead of the solution",
+            )],
+        ],
+    },
+    Command {
+        name: "validate",
+        about: "Check a puzzle for mistakes without solving it",
+        failure: "validate puzzle",
+        args: &["[FILE]"],
+        flags: &[INPUT_FLAGS],
+    },
+    Command {
+        name: "render",
+        about: "Print both boards side by side",
+        failure: "render puzzle",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[
+                switch("path", "Draw the solution's route on the boards"),
+                switch(
+                    "reach",
+                    "Mark where each player can get to, and get out from, on their own",
+                ),
+                option(
+                    "format",
+                    "FORMAT",
+                    "text, box for box-drawn boards, or svg with the route drawn",
+                ),
+            ],
+        ],
+    },
+    Command {
+        name: "verify",
+        about: "Replay a proposed solution, e.g. UURDD",
+        failure: "verify solution",
+        args: &["<MOVES>", "[FILE]"],
+        flags: &[INPUT_FLAGS],
+    },
+    Command {
+        name: "check-certificate",
+        about: "Check that a certificate from `solve --certificate` proves there's no solution",
+        failure: "check certificate",
+        args: &["<CERT>", "[FILE]"],
+        flags: &[INPUT_FLAGS],
+    },
+    Command {
+        name: "macro",
+        about: "Write a script that types the solution into the game",
+        failure: "write macro",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[
+                option("format", "FORMAT", "xdotool (default) or ahk"),
+                option(
+                    "keys",
+                    "MOVE=KEY,...",
+                    "Keys to press instead of the arrows, e.g. up=w,left=a",
+                ),
+                option("delay", "TIME", "Time between key presses (default 200ms)"),
+            ],
+        ],
+    },
+    Command {
+        name: "edit",
+        about: "Build or change a puzzle in a full-screen editor",
+        failure: "edit puzzle",
+        args: &["<FILE>"],
+        flags: &[&[
+            option(
+                "set",
+                "X,Y=TILE",
+                "Put a tile (. W T P I, or R for the player) on a cell instead",
+            ),
+            option(
+                "move-exit",
+                "EXIT",
+                "Move the exit along its side, or elsewhere like <2, instead",
+            ),
+            option(
+                "board",
+                "N",
+                "The board --set and --move-exit change (default 1)",
+            ),
+        ]],
+    },
+    Command {
+        name: "gen",
+        about: "Make up a random puzzle, or a pack of them, that has a solution",
+        failure: "generate puzzle",
+        args: &[],
+        flags: &[&[
+            option("width", "N", "Board width (default 6)"),
+            option("height", "N", "Board height (default 6)"),
+            option(
+                "layout",
+                "LAYOUT",
+                "random (default) scatters walls, backtracker or prim carves a maze",
+            ),
+            option(
+                "walls",
+                "SHARE",
+                "Share of cells that are walls, with --layout random (default 0.15)",
+            ),
+            option(
+                "ice",
+                "SHARE",
+                "Share of floor cells that are ice (default 0.1)",
+            ),
+            option("pits", "N", "Pits on each board (default 1)"),
+            switch("teleports", "Put a pair of teleports on each board"),
+            option(
+                "tries",
+                "N",
+                "Puzzles to try before giving up (default 1000)",
+            ),
+            option("moves", "MIN-MAX", "Aim for a shortest solution this long"),
+            option(
+                "nodes",
+                "MIN-MAX",
+                "Aim for the solver to expand this many nodes",
+            ),
+            switch(
+                "curriculum",
+                "Make a level pack bringing in walls, ice, pits and teleports in turn",
+            ),
+            option(
+                "levels",
+                "N",
+                "Curriculum levels for each mechanic (default 2)",
+            ),
+        ]],
+    },
+    Command {
+        name: "bench",
+        about: "Time the solver on every puzzle in a directory",
+        failure: "run benchmark",
+        args: &["<DIR>"],
+        flags: &[&[
+            option("runs", "N", "Solve each puzzle N times (default 5)"),
+            option("baseline", "FILE", "Compare against results saved earlier"),
+            option("save-baseline", "FILE", "Save the results to FILE"),
+        ]],
+    },
+    Command {
+        name: "rate",
+        about: "Score how hard puzzles are, easiest first (packs by level)",
+        failure: "rate puzzles",
+        args: &["<FILE>..."],
+        flags: &[],
+    },
+    Command {
+        name: "dedup",
+        about: "Find the puzzles in a directory that are the same turned, mirrored or swapped",
+        failure: "find duplicates",
+        args: &["<DIR>"],
+        flags: &[],
+    },
+    Command {
+        name: "hash",
+        about: "Print a fingerprint the puzzle shares with its turned, mirrored and swapped copies",
+        failure: "hash puzzle",
+        args: &["[FILE]"],
+        flags: &[INPUT_FLAGS],
+    },
+    Command {
+        name: "compare",
+        about: "Solve puzzles with several algorithms and tabulate how they did",
+        failure: "compare algorithms",
+        args: &["<FILE>..."],
+        flags: &[&[option(
+            "algorithms",
+            "NAME,...",
+            "The algorithms to compare (default all of them)",
+        )]],
+    },
+    Command {
+        name: "serve",
+        about: "Answer POST /solve requests over HTTP with JSON solutions",
+        failure: "serve",
+        args: &[],
+        flags: &[&[
+            option("port", "N", "The port to listen on (default 8080)"),
+            option(
+                "host",
+                "HOST",
+                "The address to listen on (default 127.0.0.1)",
+            ),
+        ]],
+    },
+    Command {
+        name: "selftest",
+        about: "Check every algorithm's answers against each other on the puzzles in a directory",
+        failure: "run self-test",
+        args: &["<DIR>"],
+        flags: &[&[option(
+            "algorithms",
+            "NAME,...",
+            "The algorithms to check (default all of them)",
+        )]],
+    },
+    Command {
+        name: "hint",
+        about: "Suggest the next move instead of the whole solution",
+        failure: "find a hint",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[
+                option("after", "MOVES", "Start from where these moves lead"),
+                option("at", "X1,Y1,X2,Y2", "Start with the players at these cells"),
+                option("next", "K", "How many moves to suggest (default 1)"),
+            ],
+        ],
+    },
+    Command {
+        name: "fmt",
+        about: "Print the puzzle in its canonical form",
+        failure: "format puzzle",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            WRITE_FLAGS,
+            &[switch("rle", "Print the run-length-encoded form instead")],
+        ],
+    },
+    Command {
+        name: "migrate",
+        about: "Upgrade the puzzle to the current format version",
+        failure: "migrate puzzle",
+        args: &["[FILE]"],
+        flags: &[INPUT_FLAGS, WRITE_FLAGS],
+    },
+    Command {
+        name: "minimize",
+        about: "Shrink the puzzle to the least that solves the same way",
+        failure: "minimize puzzle",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            WRITE_FLAGS,
+            &[option(
+                "keep",
+                "WHAT",
+                "length (default) keeps the shortest solution's length, solvable just a solution",
+            )],
+        ],
+    },
+    Command {
+        name: "variants",
+        about: "Try small changes to the puzzle and show how each changes the solution",
+        failure: "try variants",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[option("count", "N", "How many changes to try (default 10)")],
+        ],
+    },
+    Command {
+        name: "transform",
+        about: "Rotate or mirror both boards, in the order given",
+        failure: "transform puzzle",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[
+                option("rotate", "DEGREES", "Rotate clockwise by 90, 180 or 270"),
+                switch("mirror-h", "Mirror left to right"),
+                switch("mirror-v", "Mirror top to bottom"),
+                switch("transpose", "Swap rows and columns"),
+            ],
+        ],
+    },
+    Command {
+        name: "export",
+        about: "Print the parsed puzzle model",
+        failure: "export puzzle",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[option("format", "FORMAT", "json (default) or ron")],
+        ],
+    },
+    Command {
+        name: "unique",
+        about: "Check the puzzle has only one shortest solution, the intended one",
+        failure: "check uniqueness",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[option(
+                "intended",
+                "MOVES",
+                "The solution meant to be the only shortest one (default the `solution` metadata)",
+            )],
+        ],
+    },
+    Command {
+        name: "redundant",
+        about: "List the tiles no solution needs, or the shortest solution is as short without",
+        failure: "find redundant tiles",
+        args: &["[FILE]"],
+        flags: &[INPUT_FLAGS],
+    },
+    Command {
+        name: "dead",
+        about: "List the positions the puzzle can't be solved from any more",
+        failure: "find dead positions",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[switch(
+                "overlay",
+                "Also shade the boards by how many dead positions have a player on each cell",
+            )],
+        ],
+    },
+    Command {
+        name: "graph",
+        about: "Print every position the puzzle can reach and the moves between them",
+        failure: "export graph",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[option("format", "FORMAT", "dot (default), graphml or csv")],
+        ],
+    },
+    Command {
+        name: "render-template",
+        about: "Instantiate a puzzle template",
+        failure: "render template",
+        args: &["[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[option("set", "NAME=VALUE", "Set a template variable")],
+        ],
+    },
+    Command {
+        name: "diff",
+        about: "Compare two puzzles and their solutions",
+        failure: "diff puzzles",
+        args: &["<OLD>", "<NEW>"],
+        flags: &[],
+    },
+    Command {
+        name: "pack",
+        about: "List a pack's levels, extract one, or build one from a directory (ACTION is list, extract or build)",
+        failure: "read pack",
+        args: &["<ACTION>", "<FILE>", "[LEVEL]"],
+        flags: &[&[Flag {
+            long: "out",
+            short: Some('o'),
+            value: Some("FILE"),
+            help: "Where pack build writes the pack (default stdout)",
+        }]],
+    },
+    Command {
+        name: "trace",
+        about: "Browse a --trace-out file as a tree, or print its timeline (ACTION is view or timeline)",
+        failure: "read trace",
+        args: &["<ACTION>", "<FILE>"],
+        flags: &[],
+    },
+    Command {
+        name: "completions",
+        about: "Print a completion script for bash, zsh or fish",
+        failure: "generate completions",
+        args: &["<SHELL>"],
+        flags: &[],
+    },
+    #[cfg(feature = "gif")]
+    Command {
+        name: "gif",
+        about: "Save the solution being played as an animated GIF",
+        failure: "make GIF",
+        args: &["<OUTPUT>", "[FILE]"],
+        flags: &[
+            INPUT_FLAGS,
+            &[option(
+                "delay",
+                "TIME",
+                "Time between animation frames (default 200ms)",
+            )],
+        ],
+    },
+    #[cfg(feature = "sqlite")]
+    Command {
+        name: "stats",
+        about: "Sum up the solves in the results database, or query it",
+        failure: "read results",
+        args: &[],
+        flags: &[
+            RESULTS_FLAGS,
+            &[option("sql", "QUERY", "Run QUERY on the database instead")],
+        ],
+    },
+    #[cfg(feature = "image")]
+    Command {
+        name: "screenshot",
+        about: "Transcribe boards from PNG or PPM screenshots",
+        failure: "read screenshot",
+        args: &["<IMAGE>..."],
+        flags: &[&[option(
+            "sprites",
+            "DIR",
+            "The sprite pack to match against (default ./sprites)",
+        )]],
+    },
+];
+
+/// The parsed command line
+pub struct Matches {
+    pub command: &'static Command,
+    /// Whether the command was named, rather than the default
+    pub explicit: bool,
+    pub args: Vec<String>,
+    /// Flags by long name, in the order given
+    pub flags: Vec<(&'static str, Option<String>)>,
+}
+
+impl Matches {
+    /// A positional argument
+    pub fn arg(&self, i: usize) -> Option<&str> {
+        self.args.get(i).map(String::as_str)
+    }
+
+    /// Whether the flag was given
+    pub fn flag(&self, long: &str) -> bool {
+        self.flags.iter().any(|(f, _)| *f == long)
+    }
+
+    /// How many times the flag was given
+    pub fn count(&self, long: &str) -> usize {
+        self.flags.iter().filter(|(f, _)| *f == long).count()
+    }
+
+    /// The value of the flag, if given (the last one if repeated)
+    pub fn value(&self, long: &str) -> Option<&str> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|(f, _)| *f == long)
+            .and_then(|(_, v)| v.as_deref())
+    }
+
+    /// Every value given for a repeatable flag
+    pub fn values<'a>(&'a self, long: &'a str) -> impl Iterator<Item = &'a str> {
+        self.flags
+            .iter()
+            .filter(move |(f, _)| *f == long)
+            .filter_map(|(_, v)| v.as_deref())
+    }
+}
+
+impl Command {
+    /// Every flag this command accepts
+    pub fn all_flags(&self) -> impl Iterator<Item = &'static Flag> {
+        self.flags.iter().copied().flatten().chain(GLOBAL_FLAGS)
+    }
+}
+
+/// Parse the command line, including the program name
+pub fn parse(args: &[String]) -> Result<Matches> {
+    let mut args = args.iter().skip(1).peekable();
+
+    let named = args
+        .peek()
+        .and_then(|a| COMMANDS.iter().find(|c| c.name == a.as_str()));
+    let explicit = named.is_some();
+    let command = named.unwrap_or(&COMMANDS[0]);
+    if explicit {
+        args.next();
+    }
+
+    let mut matches = Matches {
+        command,
+        explicit,
+        args: Vec::new(),
+        flags: Vec::new(),
+    };
+
+    while let Some(arg) = args.next() {
+        let (flag, inline) = if arg == "--" {
+            matches.args.extend(args.by_ref().cloned());
+            break;
+        } else if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            let flag = command.all_flags().find(|f| f.long == name);
+            (
+                flag.ok_or(Error::Usage(format!("unknown option {}", arg)))?,
+                value,
+            )
+        } else if let Some(shorts) = arg.strip_prefix('-').filter(|s| !s.is_empty()) {
+            // Switches can be bunched up, as in `-vv`
+            let mut flags = shorts
+                .chars()
+                .map(|c| command.all_flags().find(|f| f.short == Some(c)))
+                .collect::<Option<Vec<_>>>()
+                .ok_or(Error::Usage(format!("unknown option {}", arg)))?;
+            let last = flags.pop().unwrap();
+
+            if let Some(flag) = flags.iter().find(|f| f.value.is_some()) {
+                return Err(Error::Usage(format!(
+                    "--{} needs a {}",
+                    flag.long,
+                    flag.value.unwrap()
+                )));
+            }
+            matches.flags.extend(flags.iter().map(|f| (f.long, None)));
+
+            (last, None)
+        } else {
+            matches.args.push(arg.clone());
+            continue;
+        };
+
+        let value = match (flag.value, inline) {
+            (None, None) => None,
+            (None, Some(_)) => {
+                return Err(Error::Usage(format!(
+                    "--{} doesn't take a value",
+                    flag.long
+                )))
+            }
+            (Some(_), Some(value)) => Some(value),
+            (Some(name), None) => Some(
+                args.next()
+                    .cloned()
+                    .ok_or(Error::Usage(format!("--{} needs a {}", flag.long, name)))?,
+            ),
+        };
+
+        matches.flags.push((flag.long, value));
+    }
+
+    if !matches.flag("help") {
+        let required = command.args.iter().filter(|a| a.starts_with('<')).count();
+        let variadic = command.args.last().is_some_and(|a| a.ends_with("..."));
+
+        if matches.args.len() < required {
+            return Err(Error::Usage(format!(
+                "missing {}",
+                command.args[matches.args.len()]
+            )));
+        }
+        if matches.args.len() > command.args.len() && !variadic {
+            return Err(Error::Usage(format!(
+                "unexpected argument {}",
+                matches.args[command.args.len()]
+            )));
+        }
+    }
+
+    Ok(matches)
+}
+
+/// The `--help` text for the parsed command line
+pub fn help(matches: &Matches) -> String {
+    let bin = env!("CARGO_PKG_NAME");
+    let mut out = String::new();
+
+    if !matches.explicit {
+        out.push_str(&format!(
+            "Usage: {} [COMMAND] [OPTIONS]\n\nCommands:\n",
+            bin
+        ));
+        for command in COMMANDS {
+            out.push_str(&format!("  {:<16} {}\n", command.name, command.about));
+        }
+        out.push_str(&format!(
+            "\nRun `{} <COMMAND> --help` for a command's options.\n",
+            bin
+        ));
+        return out;
+    }
+
+    let command = matches.command;
+    out.push_str(&format!(
+        "{}\n\nUsage: {} {}",
+        command.about, bin, command.name
+    ));
+    for arg in command.args {
+        out.push(' ');
+        out.push_str(arg);
+    }
+    out.push_str(" [OPTIONS]\n\nOptions:\n");
+
+    for flag in command.all_flags() {
+        let short = flag
+            .short
+            .map_or("    ".to_string(), |s| format!("-{}, ", s));
+        let long = match flag.value {
+            Some(value) => format!("--{} <{}>", flag.long, value),
+            None => format!("--{}", flag.long),
+        };
+        out.push_str(&format!("  {}{:<24} {}\n", short, long, flag.help));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    /// Parse a command line given as a single string
+    fn parse(line: &str) -> crate::Result<super::Matches> {
+        let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+        super::parse(&args)
+    }
+
+    #[test]
+    fn default_command() {
+        let matches = parse("bin -e x --max-cells=10").unwrap();
+
+        assert_eq!("solve", matches.command.name);
+        assert!(!matches.explicit);
+        assert_eq!(Some("x"), matches.value("expr"));
+        assert_eq!(Some("10"), matches.value("max-cells"));
+    }
+
+    #[test]
+    fn subcommand() {
+        let matches = parse("bin transform f.txt --rotate 90 --mirror-h --rotate 180").unwrap();
+
+        assert_eq!("transform", matches.command.name);
+        assert_eq!(Some("f.txt"), matches.arg(0));
+        assert_eq!(
+            vec!["90", "180"],
+            matches.values("rotate").collect::<Vec<_>>()
+        );
+        assert!(matches.flag("mirror-h"));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Some(Error::Usage("unknown option --nope".to_string())),
+            parse("bin --nope").err()
+        );
+        assert_eq!(
+            Some(Error::Usage("missing <MOVES>".to_string())),
+            parse("bin verify").err()
+        );
+        assert_eq!(
+            Some(Error::Usage("--url needs a URL".to_string())),
+            parse("bin --url").err()
+        );
+        assert!(parse("bin verify --help").is_ok());
+        assert_eq!(2, parse("bin -vv").unwrap().count("verbose"));
+    }
+}
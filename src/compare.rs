@@ -0,0 +1,174 @@
+//! Running several algorithms over the same puzzles, to see which suits them
+//!
+//! Each search runs in a process of its own, so one algorithm's memory use
+//! doesn't hide another's, and reports back through `--metrics-out`.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::algorithm::{Algorithm, ALL};
+use crate::cli::Matches;
+use crate::json::Json;
+use crate::output;
+use crate::{Error, Result};
+
+/// Flags passed on to every search
+const PASSED_ON: &[&str] = &[
+    "timeout",
+    "beam-width",
+    "seed",
+    "max-width",
+    "max-height",
+    "max-cells",
+    "config",
+];
+
+/// How one algorithm did on one puzzle
+#[derive(Debug, PartialEq)]
+struct Row {
+    puzzle: String,
+    algorithm: &'static str,
+    /// The solution's length, or why there isn't one
+    moves: std::result::Result<usize, String>,
+    nodes: Option<usize>,
+    elapsed_ms: Option<f64>,
+    memory: Option<f64>,
+}
+
+impl Row {
+    /// Read a row from the `--metrics-out` of a search
+    fn from_metrics(puzzle: &str, algorithm: &'static str, metrics: &Json) -> Self {
+        let number = |key| metrics.get(key).and_then(Json::as_f64);
+        let moves = match (number("moves"), metrics.get("error").and_then(Json::as_str)) {
+            (Some(moves), _) => Ok(moves as usize),
+            (None, error) => Err(error.unwrap_or("failed").to_string()),
+        };
+
+        Row {
+            puzzle: puzzle.to_string(),
+            algorithm,
+            moves,
+            nodes: number("nodes").map(|n| n as usize),
+            elapsed_ms: number("elapsed_ms"),
+            memory: number("peak_memory_bytes"),
+        }
+    }
+}
+
+/// Run one algorithm on one puzzle file
+fn run(matches: &Matches, path: &str, algorithm: Algorithm, out: &Path) -> Result<Row> {
+    let exe = std::env::current_exe().map_err(|e| Error::Io(e.kind()))?;
+    let mut command = Command::new(exe);
+    command
+        .args(["solve", path, "--quiet", "--output", "compact"])
+        .args(["--algorithm", algorithm.name()])
+        .arg("--metrics-out")
+        .arg(out)
+        .stdout(Stdio::null());
+    for flag in PASSED_ON {
+        if let Some(value) = matches.value(flag) {
+            command.arg(format!("--{}={}", flag, value));
+        }
+    }
+
+    let _ = std::fs::remove_file(out);
+    command.status().map_err(|e| Error::Io(e.kind()))?;
+
+    // The search only writes metrics once it has a puzzle to search
+    let metrics = match std::fs::read_to_string(out) {
+        Ok(text) => Json::parse(&text)?,
+        Err(_) => Json::object([("error", "unreadable".into())]),
+    };
+    let _ = std::fs::remove_file(out);
+
+    Ok(Row::from_metrics(path, algorithm.name(), &metrics))
+}
+
+/// Lay the rows out in a table
+fn table(rows: &[Row]) -> String {
+    let mut cells =
+        vec![["puzzle", "algorithm", "moves", "nodes", "time", "memory"].map(String::from)];
+    let mut last = None;
+
+    for row in rows {
+        // Only name the puzzle on its first row
+        let puzzle = match last == Some(&row.puzzle) {
+            true => String::new(),
+            false => row.puzzle.clone(),
+        };
+        last = Some(&row.puzzle);
+
+        cells.push([
+            puzzle,
+            row.algorithm.to_string(),
+            match &row.moves {
+                Ok(moves) => moves.to_string(),
+                Err(error) => error.clone(),
+            },
+            row.nodes.map_or("-".to_string(), |n| n.to_string()),
+            row.elapsed_ms
+                .map_or("-".to_string(), |ms| format!("{:.1}ms", ms)),
+            row.memory
+                .map_or("-".to_string(), |b| format!("{:.1}MB", b / 1_000_000.0)),
+        ]);
+    }
+
+    output::table(&cells)
+}
+
+/// The algorithms in `--algorithms`, or all of them
+pub fn algorithms(matches: &Matches) -> Result<Vec<Algorithm>> {
+    match matches.value("algorithms") {
+        Some(names) => names
+            .split(',')
+            .map(|name| Algorithm::parse(name.trim()))
+            .collect(),
+        None => Ok(ALL.to_vec()),
+    }
+}
+
+/// Run every algorithm in `--algorithms` (all of them by default) on each
+/// puzzle file, and tabulate how they did
+pub fn compare(matches: &Matches) -> Result<String> {
+    let algorithms = algorithms(matches)?;
+    let out = std::env::temp_dir().join(format!(
+        "{}-compare-{}.json",
+        env!("CARGO_PKG_NAME"),
+        std::process::id()
+    ));
+
+    let mut rows = Vec::new();
+    for path in &matches.args {
+        for &algorithm in &algorithms {
+            rows.push(run(matches, path, algorithm, &out)?);
+        }
+    }
+
+    Ok(table(&rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Row;
+    use crate::json::Json;
+
+    #[test]
+    fn table() {
+        let solved = Json::parse(
+            r#"{"moves":19,"nodes":231,"elapsed_ms":7.66,"peak_memory_bytes":3465216,"error":null}"#,
+        )
+        .unwrap();
+        let timed_out = Json::parse(r#"{"moves":null,"nodes":null,"error":"Timeout"}"#).unwrap();
+        let rows = [
+            Row::from_metrics("big.txt", "astar", &solved),
+            Row::from_metrics("big.txt", "bfs", &timed_out),
+        ];
+
+        assert_eq!(
+            "puzzle   algorithm  moves    nodes  time   memory\n\
+             big.txt  astar      19       231    7.7ms  3.5MB\n\
+             \x20        bfs        Timeout  -      -      -\n",
+            super::table(&rows)
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Shell completion scripts, generated from the commands and flags in
+//! [`COMMANDS`] so they never fall behind
+
+use crate::cli::{Command, COMMANDS};
+use crate::{Error, Result};
+
+/// What the program is run as
+const BIN: &str = env!("CARGO_PKG_NAME");
+
+/// The completion function, named without the `-`s some shells refuse
+fn function() -> String {
+    format!("_{}", BIN.replace('-', "_"))
+}
+
+/// Every way to write each of the command's flags, like `-h --help`
+fn spellings(command: &Command) -> Vec<String> {
+    command
+        .all_flags()
+        .flat_map(|f| {
+            f.short
+                .map(|s| format!("-{}", s))
+                .into_iter()
+                .chain([format!("--{}", f.long)])
+        })
+        .collect()
+}
+
+/// Completions for bash
+fn bash() -> String {
+    let names: Vec<&str> = COMMANDS.iter().map(|c| c.name).collect();
+    let mut out = format!(
+        "{}() {{
+    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"
+    local command={} opts
+    if [[ $COMP_CWORD -gt 1 ]]; then
+        case \"${{COMP_WORDS[1]}}\" in
+            {}) command=\"${{COMP_WORDS[1]}}\" ;;
+        esac
+    fi
+
+    case \"$command\" in
+",
+        function(),
+        COMMANDS[0].name,
+        names.join("|")
+    );
+
+    for command in COMMANDS {
+        out.push_str(&format!(
+            "        {}) opts=\"{}\" ;;\n",
+            command.name,
+            spellings(command).join(" ")
+        ));
+    }
+
+    out.push_str(&format!(
+        "    esac
+
+    if [[ $cur == -* ]]; then
+        COMPREPLY=($(compgen -W \"$opts\" -- \"$cur\"))
+    elif [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\") $(compgen -f -- \"$cur\"))
+    else
+        COMPREPLY=($(compgen -f -- \"$cur\"))
+    fi
+}}
+
+complete -o filenames -F {} {}
+",
+        names.join(" "),
+        function(),
+        BIN
+    ));
+
+    out
+}
+
+/// Escape text for a zsh `_arguments` or `_describe` spec in single quotes
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+/// Completions for zsh
+fn zsh() -> String {
+    let mut out = format!(
+        "#compdef {}\n\n{}() {{\n    local -a commands\n    commands=(\n",
+        BIN,
+        function()
+    );
+    for command in COMMANDS {
+        out.push_str(&format!(
+            "        '{}:{}'\n",
+            command.name,
+            zsh_escape(command.about)
+        ));
+    }
+
+    out.push_str(&format!(
+        "    )
+
+    local command={}
+    if (( CURRENT > 2 )) && [[ -n ${{commands[(r)${{words[2]}}:*]}} ]]; then
+        command=$words[2]
+        shift words
+        (( CURRENT-- ))
+    elif (( CURRENT == 2 )) && [[ $PREFIX != -* ]]; then
+        _describe 'command' commands
+        _files
+        return
+    fi
+
+    case $command in
+",
+        COMMANDS[0].name
+    ));
+
+    for command in COMMANDS {
+        out.push_str(&format!(
+            "        {})\n            _arguments -s",
+            command.name
+        ));
+        for flag in command.all_flags() {
+            let help = zsh_escape(flag.help);
+            let value = flag
+                .value
+                .map_or(String::new(), |v| format!(":{}: ", zsh_escape(v)));
+            if let Some(short) = flag.short {
+                out.push_str(&format!(
+                    " \\\n                '-{}[{}]{}'",
+                    short, help, value
+                ));
+            }
+            let eq = if flag.value.is_some() { "=" } else { "" };
+            out.push_str(&format!(
+                " \\\n                '--{}{}[{}]{}'",
+                flag.long, eq, help, value
+            ));
+        }
+        out.push_str(" \\\n                '*: :_files'\n            ;;\n");
+    }
+
+    out.push_str(&format!("    esac\n}}\n\n{} \"$@\"\n", function()));
+    out
+}
+
+/// Escape text for fish in single quotes
+fn fish_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+/// Completions for fish
+fn fish() -> String {
+    let mut out = String::new();
+    let names: Vec<&str> = COMMANDS.iter().map(|c| c.name).collect();
+
+    for command in COMMANDS {
+        out.push_str(&format!(
+            "complete -c {} -n __fish_use_subcommand -a {} -d '{}'\n",
+            BIN,
+            command.name,
+            fish_escape(command.about)
+        ));
+    }
+
+    for (i, command) in COMMANDS.iter().enumerate() {
+        // The default command's flags work without naming it
+        let condition = if i == 0 {
+            format!("not __fish_seen_subcommand_from {}", names[1..].join(" "))
+        } else {
+            format!("__fish_seen_subcommand_from {}", command.name)
+        };
+
+        for flag in command.all_flags() {
+            out.push_str(&format!("complete -c {} -n '{}'", BIN, condition));
+            if let Some(short) = flag.short {
+                out.push_str(&format!(" -s {}", short));
+            }
+            out.push_str(&format!(" -l {}", flag.long));
+            if flag.value.is_some() {
+                out.push_str(" -r");
+            }
+            out.push_str(&format!(" -d '{}'\n", fish_escape(flag.help)));
+        }
+    }
+
+    out
+}
+
+/// The completion script for the shell
+pub fn script(shell: &str) -> Result<String> {
+    match shell {
+        "bash" => Ok(bash()),
+        "zsh" => Ok(zsh()),
+        "fish" => Ok(fish()),
+        _ => Err(Error::Usage("expected bash, zsh or fish".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    #[test]
+    fn scripts() {
+        let bash = super::script("bash").unwrap();
+        assert!(bash.contains("complete -o filenames -F _hive_mind_solver hive-mind-solver"));
+        assert!(bash.contains("        transform) opts=\"-e --expr"));
+        assert!(bash.contains("--rotate --mirror-h"));
+
+        let zsh = super::script("zsh").unwrap();
+        assert!(zsh.starts_with("#compdef hive-mind-solver\n"));
+        assert!(zsh.contains("'--rotate=[Rotate clockwise by 90, 180 or 270]:DEGREES: '"));
+        assert!(zsh.contains("'--watch[Check and solve FILE again every time it'\\''s saved]'"));
+
+        let fish = super::script("fish").unwrap();
+        assert!(fish.contains(
+            "complete -c hive-mind-solver -n '__fish_seen_subcommand_from fmt' -l write -d 'Rewrite the file in place'"
+        ));
+        assert!(fish.contains("-s e -l expr -r"));
+
+        assert_eq!(
+            Err(Error::Usage("expected bash, zsh or fish".to_string())),
+            super::script("tcsh")
+        );
+    }
+}
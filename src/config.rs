@@ -0,0 +1,301 @@
+//! Defaults for flags from `~/.config/hive-mind-solver/config.toml`
+//!
+//! Keys are the long names of flags, and apply to every command that takes
+//! them. Keys under a `[command]` table apply to that command only. Flags on
+//! the command line win over both:
+//!
+//! ```toml
+//! color = "always"
+//! timeout = 30
+//! direction-order = "LRUD"
+//! legend = "off"
+//!
+//! [solve]
+//! output = "compact"
+//! heatmap = true
+//! ```
+//!
+//! Only as much TOML as that is understood: strings, numbers and booleans.
+
+use std::path::PathBuf;
+
+use crate::cli::{self, Command, Matches};
+use crate::{Error, Result};
+
+/// A value in the config file
+#[derive(Debug, PartialEq)]
+enum Value {
+    /// A string, or a number kept as written
+    Text(String),
+    Bool(bool),
+}
+
+/// A `key = value` line, along with the table it's in
+#[derive(Debug, PartialEq)]
+struct Entry {
+    table: Option<String>,
+    key: String,
+    value: Value,
+}
+
+/// Where the config file lives when `--config` doesn't say
+fn default_path() -> Option<PathBuf> {
+    let dir = match std::env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join(env!("CARGO_PKG_NAME")).join("config.toml"))
+}
+
+/// Parse a value written after the `=`
+fn parse_value(text: &str) -> Result<Value> {
+    if let Some(s) = text.strip_prefix('\'') {
+        return match s.strip_suffix('\'') {
+            Some(s) if !s.contains('\'') => Ok(Value::Text(s.to_string())),
+            _ => Err(Error::InvalidConfig),
+        };
+    }
+
+    if let Some(s) = text.strip_prefix('"') {
+        let mut out = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if chars.as_str().is_empty() => return Ok(Value::Text(out)),
+                '"' => return Err(Error::InvalidConfig),
+                '\\' => out.push(match chars.next() {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some(c @ ('"' | '\\')) => c,
+                    _ => return Err(Error::InvalidConfig),
+                }),
+                c => out.push(c),
+            }
+        }
+        return Err(Error::InvalidConfig);
+    }
+
+    match text {
+        "true" => Ok(Value::Bool(true)),
+        "false" => Ok(Value::Bool(false)),
+        _ if text.parse::<f64>().is_ok() => Ok(Value::Text(text.to_string())),
+        _ => Err(Error::InvalidConfig),
+    }
+}
+
+/// Strip a `#` comment off the line, minding `#`s inside strings
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+        escaped = false;
+    }
+
+    line
+}
+
+/// Parse the config file
+fn parse(text: &str) -> Result<Vec<Entry>> {
+    let mut table = None;
+    let mut entries = Vec::new();
+
+    for line in text.lines() {
+        let line = strip_comment(line).trim();
+
+        if line.is_empty() {
+            continue;
+        } else if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            table = Some(name.trim().to_string());
+        } else {
+            let (key, value) = line.split_once('=').ok_or(Error::InvalidConfig)?;
+            entries.push(Entry {
+                table: table.clone(),
+                key: key.trim().to_string(),
+                value: parse_value(value.trim())?,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+/// The flags the config file sets for the command, in the order written
+fn defaults(text: &str, command: &Command) -> Result<Vec<(&'static str, Option<String>)>> {
+    let mut flags = Vec::new();
+
+    for entry in parse(text)? {
+        if let Some(table) = &entry.table {
+            if !cli::COMMANDS.iter().any(|c| c.name == table) {
+                return Err(Error::InvalidConfig);
+            }
+            if table != command.name {
+                continue;
+            }
+        }
+
+        let Some(flag) = command.all_flags().find(|f| f.long == entry.key) else {
+            // Fine if it's meant for some other command
+            if cli::COMMANDS
+                .iter()
+                .any(|c| c.all_flags().any(|f| f.long == entry.key))
+            {
+                continue;
+            }
+            return Err(Error::InvalidConfig);
+        };
+
+        match (flag.value, entry.value) {
+            (Some(_), Value::Text(value)) => flags.push((flag.long, Some(value))),
+            (None, Value::Bool(true)) => flags.push((flag.long, None)),
+            (None, Value::Bool(false)) => {}
+            _ => return Err(Error::InvalidConfig),
+        }
+    }
+
+    Ok(flags)
+}
+
+/// Fill in the flags the command line left out from the config file, if
+/// there is one
+pub fn apply(matches: &mut Matches) -> Result<()> {
+    let text = match matches.value("config") {
+        Some(path) => std::fs::read_to_string(path).map_err(|e| Error::Io(e.kind()))?,
+        None => match default_path().map(std::fs::read_to_string) {
+            Some(Ok(text)) => text,
+            Some(Err(e)) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(Error::Io(e.kind()))
+            }
+            _ => return Ok(()),
+        },
+    };
+
+    let defaults = defaults(&text, matches.command)?
+        .into_iter()
+        .filter(|(long, _)| !matches.flag(long))
+        .collect::<Vec<_>>();
+    matches.flags.splice(0..0, defaults);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, Value};
+    use crate::cli::COMMANDS;
+    use crate::Error;
+
+    #[test]
+    fn parse() {
+        let text = "
+# Defaults
+color = 'always'  # everywhere
+timeout = 2.5
+
+[solve]
+output = \"a \\\"#\\\" b\"
+heatmap = true
+";
+
+        assert_eq!(
+            Ok(vec![
+                Entry {
+                    table: None,
+                    key: "color".to_string(),
+                    value: Value::Text("always".to_string()),
+                },
+                Entry {
+                    table: None,
+                    key: "timeout".to_string(),
+                    value: Value::Text("2.5".to_string()),
+                },
+                Entry {
+                    table: Some("solve".to_string()),
+                    key: "output".to_string(),
+                    value: Value::Text("a \"#\" b".to_string()),
+                },
+                Entry {
+                    table: Some("solve".to_string()),
+                    key: "heatmap".to_string(),
+                    value: Value::Bool(true),
+                },
+            ]),
+            super::parse(text)
+        );
+        assert_eq!(Err(Error::InvalidConfig), super::parse("color = always"));
+        assert_eq!(Err(Error::InvalidConfig), super::parse("color"));
+    }
+
+    #[test]
+    fn defaults() {
+        let text =
+            "color = \"never\"\n[solve]\noutput = \"rle\"\nanimate = false\n[render]\npath = true";
+        let solve = &COMMANDS[0];
+        let render = COMMANDS.iter().find(|c| c.name == "render").unwrap();
+
+        assert_eq!(
+            Ok(vec![
+                ("color", Some("never".to_string())),
+                ("output", Some("rle".to_string()))
+            ]),
+            super::defaults(text, solve)
+        );
+        assert_eq!(
+            Ok(vec![("color", Some("never".to_string())), ("path", None)]),
+            super::defaults(text, render)
+        );
+
+        assert_eq!(
+            Ok(vec![
+                ("direction-order", Some("LRUD".to_string())),
+                ("legend", Some("off".to_string()))
+            ]),
+            super::defaults("direction-order = \"LRUD\"\nlegend = 'off'", render)
+        );
+
+        // Flags only some other command takes are skipped, not refused
+        assert_eq!(Ok(vec![]), super::defaults("rotate = 90", solve));
+        assert_eq!(
+            Err(Error::InvalidConfig),
+            super::defaults("colour = \"never\"", solve)
+        );
+        assert_eq!(
+            Err(Error::InvalidConfig),
+            super::defaults("heatmap = \"yes\"", solve)
+        );
+        assert_eq!(
+            Err(Error::InvalidConfig),
+            super::defaults("[nope]\ncolor = \"never\"", solve)
+        );
+    }
+
+    #[test]
+    fn command_line_wins() {
+        let path = std::env::temp_dir().join("hive-mind-solver-config-test.toml");
+        std::fs::write(&path, "color = \"never\"\ntimeout = 3").unwrap();
+
+        let args = [
+            "bin",
+            "--color",
+            "always",
+            "--config",
+            path.to_str().unwrap(),
+        ];
+        let mut matches = crate::cli::parse(&args.map(String::from)).unwrap();
+        super::apply(&mut matches).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(Some("always"), matches.value("color"));
+        assert_eq!(Some("3"), matches.value("timeout"));
+    }
+}
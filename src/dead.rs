@@ -0,0 +1,81 @@
+//! Positions that have already lost
+//!
+//! A dead position is one the players can reach but never get out of both
+//! together from, whatever they do next: the game isn't over, but it's lost.
+//! How many there are, and how easy they are to walk into, is a measure of
+//! how punishing a puzzle is. The overlay shades each cell by how many dead
+//! positions have a player on it, to show where on the boards the traps are.
+
+use std::collections::HashMap;
+
+use crate::graph::Graph;
+use crate::{output, render, Player, Result};
+
+/// List the dead positions, with the fewest moves that reach each one, and
+/// shade the boards by them if `overlay` is set
+pub fn report(input: &str, overlay: bool) -> Result<String> {
+    let graph = Graph::of(input)?;
+    let to_goal = graph.to_goal();
+    let paths = graph.paths();
+    let dead: Vec<usize> = (0..graph.states.len())
+        .filter(|&i| to_goal[i].is_none())
+        .collect();
+
+    if dead.is_empty() {
+        return Ok(format!(
+            "All {} reachable positions can still be solved\n",
+            graph.states.len()
+        ));
+    }
+
+    let mut out = format!(
+        "{} of {} reachable positions can't be solved any more ({:.0}%)\n\n",
+        dead.len(),
+        graph.states.len(),
+        dead.len() as f64 * 100.0 / graph.states.len() as f64
+    );
+
+    let mut rows = vec![["player 1", "player 2", "reached by"].map(String::from)];
+    let mut counts: [HashMap<Player, usize>; 2] = Default::default();
+    for &i in &dead {
+        let (p1, p2) = graph.states[i];
+        *counts[0].entry(p1).or_default() += 1;
+        *counts[1].entry(p2).or_default() += 1;
+        rows.push([
+            format!("({}, {})", p1.x, p1.y),
+            format!("({}, {})", p2.x, p2.y),
+            match paths[i].is_empty() {
+                true => "the start".to_string(),
+                false => output::compact(&paths[i]),
+            },
+        ]);
+    }
+    out.push_str(&output::table(&rows));
+
+    if overlay {
+        let ((b1, _), (b2, _)) = crate::parse_puzzle(input)?;
+        out.push('\n');
+        out.push_str(&render::heatmap([&b1, &b2], &counts, "dead positions"));
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn report() {
+        // Going left wins at once, and any other first move loses for good
+        let puzzle = "<1\nPI.\nIR.\n...\n\n<0\nRW.\nPII\nWI.";
+
+        assert_eq!(
+            "3 of 4 reachable positions can't be solved any more (75%)\n\
+             \n\
+             player 1  player 2  reached by\n\
+             (1, 0)    (0, 0)    U\n\
+             (2, 1)    (0, 0)    R\n\
+             (2, 0)    (0, 0)    UR\n",
+            super::report(puzzle, false).unwrap()
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Spotting the same puzzle twice
+//!
+//! Turning or mirroring both boards together, or swapping them, gives a
+//! puzzle that plays exactly the same with the moves turned to match, so
+//! they all share a canonical form: whichever way of writing the boards
+//! comes first. Metadata and formatting don't count. A board only has the
+//! one pair of teleports, so there's no telling one labelling of them from
+//! another to worry about.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::transform::Transform;
+use crate::{bench, fmt, metrics, rate, validate, Error, Result};
+
+/// The ways of turning a board over, the plain one first
+const SYMMETRIES: [&[Transform]; 8] = {
+    use Transform::{MirrorH as M, Rotate as R};
+    [
+        &[],
+        &[R],
+        &[R, R],
+        &[R, R, R],
+        &[M],
+        &[M, R],
+        &[M, R, R],
+        &[M, R, R, R],
+    ]
+};
+
+/// The puzzle's boards in their canonical form
+pub fn canonical(input: &str) -> Result<String> {
+    let (board1, board2) = crate::parse_puzzle(input)?;
+    // An exit off to the side of the board has nowhere to turn to
+    if [&board1.0, &board2.0]
+        .into_iter()
+        .any(|board| validate::exit_inside(board).is_none())
+    {
+        return Err(Error::NoExit);
+    }
+
+    let mut forms = Vec::new();
+    for transforms in SYMMETRIES {
+        let (mut board1, mut board2) = (board1.clone(), board2.clone());
+        // Some boards can't be turned every way, so those ways are left out
+        let turned = transforms.iter().try_for_each(|t| -> Result<()> {
+            board1 = t.apply(&board1.0, board1.1)?;
+            board2 = t.apply(&board2.0, board2.1)?;
+            Ok(())
+        });
+        if turned.is_ok() {
+            forms.push(fmt::write(vec![], [&board1, &board2]));
+            forms.push(fmt::write(vec![], [&board2, &board1]));
+        }
+    }
+
+    Ok(forms.into_iter().min().unwrap())
+}
+
+/// A fingerprint of the puzzle that every equivalent puzzle shares
+pub fn hash(input: &str) -> Result<String> {
+    Ok(metrics::hash(&canonical(input)?))
+}
+
+/// Group the puzzles in the directory, and the levels of the packs there, by
+/// canonical form, and list the groups with more than one in
+pub fn dedup(dir: &str) -> Result<String> {
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    let mut order = Vec::new();
+    let (mut total, mut skipped) = (0, 0);
+
+    for name in bench::puzzles(dir)? {
+        let path = Path::new(dir).join(&name).to_string_lossy().into_owned();
+        let Ok(puzzles) = rate::puzzles(&path) else {
+            skipped += 1;
+            continue;
+        };
+        for (name, puzzle) in puzzles {
+            let Ok(hash) = hash(&puzzle) else {
+                skipped += 1;
+                continue;
+            };
+            total += 1;
+            let group = groups.entry(hash.clone()).or_default();
+            if group.is_empty() {
+                order.push(hash);
+            }
+            group.push(name);
+        }
+    }
+
+    let duplicated: Vec<&String> = order.iter().filter(|h| groups[*h].len() > 1).collect();
+    let mut out = match duplicated.len() {
+        0 => format!("No duplicates among {} puzzles\n", total),
+        n => format!("{} sets of duplicates among {} puzzles\n", n, total),
+    };
+    if skipped > 0 {
+        out.push_str(&format!("{} files weren't puzzles\n", skipped));
+    }
+    for hash in duplicated {
+        out.push_str(&format!("\n{}:\n", hash));
+        for name in &groups[hash] {
+            out.push_str(&format!("  {}\n", name));
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn canonical() {
+        let puzzle = "title: Corner\n\n  x\nP..\n.R.\n\n x\n..\n.R";
+        // Turned a quarter, then the boards swapped
+        let turned = ">1\n..\nR.\n\n>2\n.P\nR.\n..";
+
+        assert_eq!(super::hash(puzzle), super::hash(turned));
+        assert_ne!(
+            super::hash(puzzle),
+            super::hash("  x\n..P\n.R.\n\n x\n..\n.R")
+        );
+
+        let dir = std::env::temp_dir().join("hive-mind-solver-dedup-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.txt"), puzzle).unwrap();
+        std::fs::write(dir.join("b.txt"), turned).unwrap();
+        std::fs::write(dir.join("notes.md"), "Nothing to see").unwrap();
+        let dir = dir.to_string_lossy();
+
+        assert_eq!(
+            format!(
+                "1 sets of duplicates among 2 puzzles\n\
+                 1 files weren't puzzles\n\
+                 \n\
+                 {}:\n  {}/a.txt\n  {}/b.txt\n",
+                super::hash(puzzle).unwrap(),
+                dir,
+                dir
+            ),
+            super::dedup(&dir).unwrap()
+        );
+    }
+
+    #[test]
+    fn exit_off_board() {
+        assert_eq!(
+            Err(crate::Error::NoExit),
+            super::hash(">5\n...\n.R.\n\n x\n.R.")
+        );
+    }
+}
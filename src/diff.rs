@@ -0,0 +1,106 @@
+//! Comparing two versions of a puzzle
+
+use std::fmt::Write;
+
+use crate::{Board, Player, Result};
+
+/// The character at a cell of a board definition, if the cell exists
+fn cell(board: &Board, player: Player, x: usize, y: usize) -> Option<char> {
+    let tile = board.tiles.get(y)?.get(x)?;
+
+    Some(if (player.x, player.y) == (x as isize, y as isize) {
+        'R'
+    } else {
+        tile.to_char()
+    })
+}
+
+/// List the differences between two versions of a board
+fn diff_board((b1, p1): &(Board, Player), (b2, p2): &(Board, Player)) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    if b1.exit != b2.exit {
+        changes.push(format!(
+            "exit: {} -> {}",
+            b1.exit.to_text().trim(),
+            b2.exit.to_text().trim()
+        ));
+    }
+
+    let height = b1.tiles.len().max(b2.tiles.len());
+    for y in 0..height {
+        let width = [b1, b2]
+            .iter()
+            .map(|b| b.tiles.get(y).map_or(0, Vec::len))
+            .max()
+            .unwrap_or(0);
+
+        for x in 0..width {
+            let (c1, c2) = (cell(b1, *p1, x, y), cell(b2, *p2, x, y));
+            if c1 != c2 {
+                changes.push(format!(
+                    "({}, {}): {} -> {}",
+                    x,
+                    y,
+                    c1.unwrap_or('-'),
+                    c2.unwrap_or('-')
+                ));
+            }
+        }
+    }
+
+    changes
+}
+
+/// Describe a solve result for the comparison
+fn describe(solution: &Result<Vec<crate::Dir>>) -> String {
+    match solution {
+        Ok(moves) => format!("{} moves {:?}", moves.len(), moves),
+        Err(err) => format!("{:?}", err),
+    }
+}
+
+/// Compare two puzzles cell by cell, along with their optimal solutions
+pub fn diff(old: &str, new: &str) -> Result<String> {
+    let (old1, old2) = crate::parse_puzzle(old)?;
+    let (new1, new2) = crate::parse_puzzle(new)?;
+    let mut out = String::new();
+
+    for (i, (old, new)) in [(old1, new1), (old2, new2)].iter().enumerate() {
+        let changes = diff_board(old, new);
+
+        if changes.is_empty() {
+            writeln!(out, "Board {}: unchanged", i + 1).unwrap();
+        } else {
+            writeln!(out, "Board {}:", i + 1).unwrap();
+            for change in changes {
+                writeln!(out, "  {}", change).unwrap();
+            }
+        }
+    }
+
+    let (old, new) = (crate::solve_puzzle(old), crate::solve_puzzle(new));
+    if old == new {
+        writeln!(out, "Solution unchanged: {}", describe(&old)).unwrap();
+    } else {
+        writeln!(out, "Solution changed:").unwrap();
+        writeln!(out, "  old: {}", describe(&old)).unwrap();
+        writeln!(out, "  new: {}", describe(&new)).unwrap();
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn diff() {
+        let old = " x\n...\n...\n.R.\n\n x\n...\n...\n..R";
+        let new = " x\n...\n..W\n.R.\n\n x\n...\n...\n..R";
+
+        assert_eq!(
+            Ok("Board 1:\n  (2, 1): . -> W\nBoard 2: unchanged\nSolution unchanged: 5 moves [Up, Up, Right, Left, Up]\n".to_string()),
+            super::diff(old, new)
+        );
+    }
+}
@@ -0,0 +1,448 @@
+//! A full-screen editor for building puzzles
+//!
+//! Tiles go wherever the cursor is. Every edit checks the puzzle again and
+//! solves it from scratch, so mistakes and the length of the shortest solution
+//! show up while you work.
+//!
+//! The same edits can be made from the command line instead, with `--set` and
+//! `--move-exit`, for scripts that build levels.
+
+use crate::algorithm::{self, Algorithm};
+use crate::cli::Matches;
+use crate::metadata::{self, Metadata};
+use crate::term::{Key, Screen};
+use crate::{fmt, input, limits, output, render, validate};
+use crate::{Board, Error, Exit, Player, Result, Side, Stats, Tile};
+
+/// The key bindings, shown at the bottom of the screen
+const HELP: &str = "arrows move · tab other board · . w t p i tiles · r player · x exit\n\
+                    [ ] fewer/more columns · { } fewer/more rows · s save · q quit";
+
+/// What new puzzles start out as
+const BLANK: &str = "  x\n.....\n.....\n.....\n..R..\n\n  x\n.....\n.....\n.....\n..R..";
+
+struct Editor {
+    path: String,
+    metadata: Metadata,
+    boards: [(Board, Player); 2],
+    /// Which of the boards the cursor is on
+    active: usize,
+    cursor: Player,
+    /// What the checks and the solver made of the puzzle after the last edit
+    analysis: String,
+    message: String,
+    /// Whether there are edits that haven't been saved
+    dirty: bool,
+}
+
+impl Editor {
+    /// Open the puzzle, or a blank one to fill in
+    fn new(path: &str, input: Option<&str>) -> Result<Self> {
+        let input = input.unwrap_or(BLANK);
+        let (metadata, _) = metadata::split(input);
+        let ((b1, p1), (b2, p2)) = crate::parse_puzzle(input)?;
+
+        let mut editor = Editor {
+            path: path.to_string(),
+            metadata,
+            boards: [(b1, p1), (b2, p2)],
+            active: 0,
+            cursor: p1,
+            analysis: String::new(),
+            message: String::new(),
+            dirty: false,
+        };
+        for (board, _) in &mut editor.boards {
+            // Short rows behave like they end in walls, so that's what
+            // widening them adds
+            let width = board.tiles.iter().map(Vec::len).max().unwrap_or(0);
+            for row in &mut board.tiles {
+                row.resize(width, Tile::Wall);
+            }
+        }
+        editor.analyze();
+        Ok(editor)
+    }
+
+    /// The puzzle as it would be saved
+    fn text(&self) -> String {
+        fmt::write(self.metadata.clone(), [&self.boards[0], &self.boards[1]])
+    }
+
+    /// Check the puzzle and find its shortest solution
+    fn analyze(&mut self) {
+        let problems = match validate::validate(&self.text()) {
+            Ok(problems) => problems,
+            Err(err) => {
+                self.analysis = format!("Can't use the puzzle: {:?}", err);
+                return;
+            }
+        };
+
+        // A* finds a shortest solution too, and far sooner than breadth-first
+        // search on open boards
+        let [(b1, p1), (b2, p2)] = &self.boards;
+        let solution = algorithm::search(
+            Algorithm::Astar,
+            (b1, *p1),
+            (b2, *p2),
+            &mut Stats::default(),
+            limits::deadline(),
+            &mut |_| {},
+        );
+
+        let mut lines = problems;
+        lines.push(match solution {
+            Ok(moves) => format!(
+                "Shortest solution: {} moves ({})",
+                moves.len(),
+                output::compact(&moves)
+            ),
+            Err(err) => format!("Solver: {:?}", err),
+        });
+        self.analysis = lines.join("\n");
+    }
+
+    /// The board and player being edited
+    fn board(&mut self) -> &mut (Board, Player) {
+        &mut self.boards[self.active]
+    }
+
+    /// The cursor's cell in the tiles
+    fn cell(&self) -> (usize, usize) {
+        (self.cursor.x as usize, self.cursor.y as usize)
+    }
+
+    /// Keep the cursor on the board after it changes size
+    fn clamp(&mut self) {
+        let (board, _) = &self.boards[self.active];
+        let (width, height) = (board.tiles[0].len(), board.tiles.len());
+        self.cursor.x = self.cursor.x.min(width as isize - 1);
+        self.cursor.y = self.cursor.y.min(height as isize - 1);
+    }
+
+    /// Note that the puzzle changed, and look at it again
+    fn edited(&mut self) {
+        self.dirty = true;
+        self.message.clear();
+        self.analyze();
+    }
+
+    /// Put a tile under the cursor
+    fn place(&mut self, tile: Tile) {
+        let (x, y) = self.cell();
+        let cursor = self.cursor;
+        let (board, player) = self.board();
+
+        if *player == cursor && !matches!(tile, Tile::None) {
+            self.message = "Move the player off this cell first".to_string();
+            return;
+        }
+        board.tiles[y][x] = tile;
+        self.edited();
+    }
+
+    /// Move the player to the cursor, clearing the tile there since players
+    /// always start on floor
+    fn place_player(&mut self) {
+        let (x, y) = self.cell();
+        let cursor = self.cursor;
+        let (board, player) = self.board();
+
+        board.tiles[y][x] = Tile::None;
+        *player = cursor;
+        self.edited();
+    }
+
+    /// Move the exit to the edge of the board beside the cursor
+    fn place_exit(&mut self) {
+        let (x, y) = self.cell();
+        let (board, _) = self.board();
+        let (width, height) = (board.tiles[0].len(), board.tiles.len());
+
+        board.exit = match (x, y) {
+            (x, 0) => Exit {
+                side: Side::Top,
+                pos: x,
+            },
+            (x, y) if y == height - 1 => Exit {
+                side: Side::Bottom,
+                pos: x,
+            },
+            (0, y) => Exit {
+                side: Side::Left,
+                pos: y,
+            },
+            (x, y) if x == width - 1 => Exit {
+                side: Side::Right,
+                pos: y,
+            },
+            _ => {
+                self.message = "The exit has to go beside an edge cell".to_string();
+                return;
+            }
+        };
+        self.edited();
+    }
+
+    /// Add or take away a column at the right of the board
+    fn resize_columns(&mut self, grow: bool) {
+        let (board, player) = self.board();
+        let width = board.tiles[0].len();
+
+        if !grow && (width == 1 || player.x as usize == width - 1) {
+            self.message = "Can't take away that column".to_string();
+            return;
+        }
+        for row in &mut board.tiles {
+            row.resize(if grow { width + 1 } else { width - 1 }, Tile::None);
+        }
+        self.clamp();
+        self.edited();
+    }
+
+    /// Add or take away a row at the bottom of the board
+    fn resize_rows(&mut self, grow: bool) {
+        let (board, player) = self.board();
+        let (width, height) = (board.tiles[0].len(), board.tiles.len());
+
+        if !grow && (height == 1 || player.y as usize == height - 1) {
+            self.message = "Can't take away that row".to_string();
+            return;
+        }
+        match grow {
+            true => board.tiles.push(vec![Tile::None; width]),
+            false => drop(board.tiles.pop()),
+        }
+        self.clamp();
+        self.edited();
+    }
+
+    /// Move the cursor, staying on the board
+    fn step(&mut self, dx: isize, dy: isize) {
+        let (board, _) = &self.boards[self.active];
+        let x = self.cursor.x + dx;
+        let y = self.cursor.y + dy;
+
+        if (0..board.tiles[0].len() as isize).contains(&x)
+            && (0..board.tiles.len() as isize).contains(&y)
+        {
+            self.cursor = Player { x, y };
+        }
+    }
+
+    /// Write the puzzle to its file
+    fn save(&mut self) -> Result<()> {
+        std::fs::write(&self.path, self.text()).map_err(|e| Error::Io(e.kind()))?;
+        self.dirty = false;
+        self.message = format!("Saved to {}", self.path);
+        Ok(())
+    }
+
+    /// Handle a key press, returning false to quit
+    fn key(&mut self, key: Key) -> Result<bool> {
+        let quitting = self.message.starts_with("Unsaved");
+        self.message.clear();
+
+        match key {
+            Key::Up => self.step(0, -1),
+            Key::Down => self.step(0, 1),
+            Key::Left => self.step(-1, 0),
+            Key::Right => self.step(1, 0),
+            Key::Tab => {
+                self.active = 1 - self.active;
+                self.clamp();
+            }
+            Key::Char('.' | ' ') => self.place(Tile::None),
+            Key::Char('w' | 'W') => self.place(Tile::Wall),
+            Key::Char('t' | 'T') => self.place(Tile::Teleport),
+            Key::Char('p' | 'P') => self.place(Tile::Pit),
+            Key::Char('i' | 'I') => self.place(Tile::Ice),
+            Key::Char('r' | 'R') => self.place_player(),
+            Key::Char('x' | 'X') => self.place_exit(),
+            Key::Char('[') => self.resize_columns(false),
+            Key::Char(']') => self.resize_columns(true),
+            Key::Char('{') => self.resize_rows(false),
+            Key::Char('}') => self.resize_rows(true),
+            Key::Char('s') => self.save()?,
+            Key::Char('q') | Key::Esc if self.dirty && !quitting => {
+                self.message = "Unsaved changes: press q again to quit anyway".to_string();
+            }
+            Key::Char('q') | Key::Esc => return Ok(false),
+            _ => {}
+        }
+
+        Ok(true)
+    }
+
+    /// Lay out the whole screen
+    fn screen(&self) -> String {
+        let [(b1, p1), (b2, p2)] = &self.boards;
+        let left = b1.to_text(*p1);
+        let boards = render::side_by_side(&left, &b2.to_text(*p2));
+
+        // Highlight the cell under the cursor, below the exit line
+        let offset = match self.active {
+            0 => 0,
+            _ => left.lines().map(|l| l.chars().count()).max().unwrap_or(0) + render::GUTTER,
+        };
+        let (x, y) = self.cell();
+        let boards = boards
+            .lines()
+            .enumerate()
+            .map(|(i, line)| match i == y + 1 {
+                true => line
+                    .chars()
+                    .enumerate()
+                    .map(|(j, c)| match j == offset + x {
+                        true => format!("\x1b[7m{}\x1b[0m", c),
+                        false => c.to_string(),
+                    })
+                    .collect(),
+                false => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        format!(
+            "{}{}\n\n{}\nBoard {}, ({}, {})\n\n{}\n\n{}\n{}\n",
+            self.path,
+            if self.dirty { " (modified)" } else { "" },
+            boards,
+            self.active + 1,
+            x,
+            y,
+            self.analysis,
+            self.message,
+            HELP
+        )
+    }
+}
+
+/// Make the edits from `--set X,Y=TILE` and `--move-exit EXIT` to the board
+/// from `--board`, the way the keys would
+fn script(editor: &mut Editor, matches: &Matches) -> Result<()> {
+    editor.active = match matches.value("board").unwrap_or("1") {
+        "1" => 0,
+        "2" => 1,
+        _ => return Err(Error::Usage("--board must be 1 or 2".to_string())),
+    };
+
+    for edit in matches.values("set") {
+        let invalid = || Error::Usage(format!("--set takes X,Y=TILE, not {}", edit));
+        let (cell, tile) = edit.split_once('=').ok_or_else(invalid)?;
+        let (x, y) = cell.split_once(',').ok_or_else(invalid)?;
+        let tile = match tile.chars().collect::<Vec<_>>()[..] {
+            [c @ ('.' | 'W' | 'T' | 'P' | 'I' | 'R')] => c,
+            _ => return Err(invalid()),
+        };
+        let cursor = Player {
+            x: x.trim().parse().map_err(|_| invalid())?,
+            y: y.trim().parse().map_err(|_| invalid())?,
+        };
+
+        let (board, _) = &editor.boards[editor.active];
+        if !(0..board.tiles[0].len() as isize).contains(&cursor.x)
+            || !(0..board.tiles.len() as isize).contains(&cursor.y)
+        {
+            return Err(Error::Usage(format!("{},{} is off the board", x, y)));
+        }
+        editor.cursor = cursor;
+        editor.key(Key::Char(tile))?;
+        if !editor.message.is_empty() {
+            return Err(Error::Usage(editor.message.clone()));
+        }
+    }
+
+    if let Some(exit) = matches.value("move-exit") {
+        let (board, _) = editor.board();
+        board.exit = match exit.parse() {
+            Ok(pos) => Exit { pos, ..board.exit },
+            Err(_) => Exit::parse(exit).map_err(|_| {
+                Error::Usage(format!(
+                    "--move-exit takes a position like 5 or <2, not {}",
+                    exit
+                ))
+            })?,
+        };
+    }
+
+    Ok(())
+}
+
+/// Open the editor on a puzzle file, which doesn't have to exist yet, or make
+/// the edits the command line asks for to it
+pub fn run(path: &str, matches: &Matches) -> Result<()> {
+    let input = input::file(path).ok();
+    let mut editor = Editor::new(path, input.as_deref())?;
+
+    if matches.flag("set") || matches.flag("move-exit") {
+        script(&mut editor, matches)?;
+        return editor.save();
+    }
+
+    let mut screen = Screen::enter()?;
+
+    loop {
+        screen.draw(&editor.screen())?;
+
+        match screen.key()? {
+            Some(key) if editor.key(key)? => {}
+            _ => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Editor;
+    use crate::term::Key;
+
+    #[test]
+    fn edit() {
+        let mut editor = Editor::new("new.txt", None).unwrap();
+        assert!(editor
+            .analysis
+            .contains("Shortest solution: 4 moves (UUUU)"));
+
+        // Wall off the way straight up on the first board
+        editor.key(Key::Up).unwrap();
+        editor.key(Key::Char('w')).unwrap();
+        assert!(editor.dirty);
+        assert!(editor.analysis.contains("Shortest solution: 6 moves"));
+
+        editor.key(Key::Tab).unwrap();
+        editor.key(Key::Char('}')).unwrap();
+        assert_eq!(5, editor.boards[1].0.tiles.len());
+
+        assert!(editor.key(Key::Char('q')).unwrap());
+        assert!(editor.message.starts_with("Unsaved"));
+        assert!(!editor.key(Key::Char('q')).unwrap());
+    }
+
+    #[test]
+    fn script() {
+        let args = [
+            "bin", "edit", "x.txt", "--board", "2", "--set", "0,1=W", "--set", "4,3=R",
+        ]
+        .map(String::from);
+        let matches = crate::cli::parse(&args).unwrap();
+        let mut editor = Editor::new("x.txt", None).unwrap();
+
+        super::script(&mut editor, &matches).unwrap();
+        assert!(editor.text().ends_with("  x\n.....\nW....\n.....\n....R\n"));
+
+        let args = [
+            "bin",
+            "edit",
+            "x.txt",
+            "--move-exit",
+            "<2",
+            "--set",
+            "9,9=W",
+        ]
+        .map(String::from);
+        let matches = crate::cli::parse(&args).unwrap();
+        assert!(super::script(&mut editor, &matches).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! Walking through a solution move by move, in words
+
+use crate::verify::{self, End, Step, Walk};
+use crate::{Dir, Player, Result};
+
+/// Where a player is, for people
+fn cell(p: Player) -> String {
+    format!("({}, {})", p.x, p.y)
+}
+
+/// Say what a move did to one player
+fn describe(walk: &Walk) -> String {
+    let slide = match walk.slid {
+        0 => None,
+        1 => Some("slides across 1 ice tile".to_string()),
+        n => Some(format!("slides across {} ice tiles", n)),
+    };
+
+    match (slide, walk.end) {
+        (None, End::Walked) => format!("steps to {}", cell(walk.to)),
+        (None, End::Blocked) => "bumps into a wall".to_string(),
+        (None, End::Teleported) => format!("teleports to {}", cell(walk.to)),
+        (None, End::Exited) => "leaves through the exit".to_string(),
+        (None, End::Fell) => "falls into a pit".to_string(),
+        (Some(slide), End::Walked) => format!("{} to {}", slide, cell(walk.to)),
+        (Some(slide), End::Blocked) => {
+            format!("{} to {}, where a wall stops them", slide, cell(walk.to))
+        }
+        (Some(slide), End::Teleported) => {
+            format!("{} and teleports to {}", slide, cell(walk.to))
+        }
+        (Some(slide), End::Exited) => format!("{} and out the exit", slide),
+        (Some(slide), End::Fell) => format!("{} into a pit", slide),
+    }
+}
+
+/// Narrate the moves, a line each, saying what every move does to both
+/// players
+pub fn explain(input: &str, moves: &[Dir]) -> Result<String> {
+    let ((b1, mut p1), (b2, mut p2)) = crate::parse_puzzle(input)?;
+    let mut lines = Vec::new();
+
+    for (i, &dir) in moves.iter().enumerate() {
+        let (walk1, walk2) = (Walk::new(dir, &b1, p1), Walk::new(dir, &b2, p2));
+        lines.push(format!(
+            "{}. {:?}: player 1 {}; player 2 {}",
+            i + 1,
+            dir,
+            describe(&walk1),
+            describe(&walk2)
+        ));
+
+        match verify::step(dir, (&b1, p1), (&b2, p2)) {
+            Step::Moved(n1, n2) => (p1, p2) = (n1, n2),
+            _ => break,
+        }
+    }
+
+    Ok(lines.join("\n") + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Dir;
+
+    #[test]
+    fn explain() {
+        let input = "  x\n...\n.I.\n.R.\n\n  x\n...\nTPT\n.R.";
+        let moves = Dir::parse_moves("LURUU").unwrap();
+
+        assert_eq!(
+            "1. Left: player 1 steps to (0, 2); player 2 steps to (0, 2)\n\
+             2. Up: player 1 steps to (0, 1); player 2 teleports to (2, 1)\n\
+             3. Right: player 1 slides across 1 ice tile to (2, 1); player 2 bumps into a wall\n\
+             4. Up: player 1 steps to (2, 0); player 2 steps to (2, 0)\n\
+             5. Up: player 1 leaves through the exit; player 2 leaves through the exit\n",
+            super::explain(input, &moves).unwrap()
+        );
+    }
+}
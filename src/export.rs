@@ -0,0 +1,149 @@
+//! Exporting the parsed puzzle model for other tools
+
+use crate::json::Json;
+use crate::metadata;
+use crate::{Board, Error, Player, Result, Side, Tile};
+
+/// Output formats for the exported model
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Json,
+    Ron,
+}
+
+impl Format {
+    /// Parse a format name from the command line
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "json" => Ok(Format::Json),
+            "ron" => Ok(Format::Ron),
+            _ => Err(Error::UnknownFormat),
+        }
+    }
+}
+
+impl From<Player> for Json {
+    fn from(p: Player) -> Self {
+        Json::object([("x", p.x.into()), ("y", p.y.into())])
+    }
+}
+
+/// The model of a single board
+fn board(board: &Board, player: Player) -> Json {
+    let side = match board.exit.side {
+        Side::Top => "top",
+        Side::Bottom => "bottom",
+        Side::Left => "left",
+        Side::Right => "right",
+    };
+
+    let teleports = board
+        .tiles
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.iter().enumerate().filter_map(move |(x, tile)| {
+                matches!(tile, Tile::Teleport).then_some(Player {
+                    x: x as isize,
+                    y: y as isize,
+                })
+            })
+        })
+        .map(|from| {
+            Json::object([
+                ("from", from.into()),
+                (
+                    "to",
+                    board
+                        .teleport_target(from)
+                        .map_or(Json::Null, |to| Json::Present(Box::new(to.into()))),
+                ),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    Json::object([
+        (
+            "width",
+            board.tiles.iter().map(Vec::len).max().unwrap_or(0).into(),
+        ),
+        ("height", board.tiles.len().into()),
+        (
+            "rows",
+            board
+                .tiles
+                .iter()
+                .map(|row| row.iter().map(|t| t.to_char()).collect::<String>())
+                .collect::<Vec<_>>()
+                .into(),
+        ),
+        (
+            "exit",
+            Json::object([("side", side.into()), ("pos", board.exit.pos.into())]),
+        ),
+        ("player", player.into()),
+        ("teleports", teleports.into()),
+    ])
+}
+
+/// The model of a whole puzzle
+pub fn puzzle(input: &str) -> Result<Json> {
+    let (metadata, _) = metadata::split(input);
+    let ((b1, p1), (b2, p2)) = crate::parse_puzzle(input)?;
+
+    Ok(Json::object([
+        ("version", crate::migrate::version(&metadata)?.into()),
+        (
+            "metadata",
+            Json::Object(metadata.into_iter().map(|(k, v)| (k, v.into())).collect()),
+        ),
+        ("boards", vec![board(&b1, p1), board(&b2, p2)].into()),
+    ]))
+}
+
+/// Export a puzzle in the given format
+pub fn export(input: &str, format: Format) -> Result<String> {
+    let model = puzzle(input)?;
+
+    Ok(match format {
+        Format::Json => model.to_string(),
+        Format::Ron => model.to_ron(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Format;
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            Ok(concat!(
+                r#"{"version":1,"metadata":{"title":"Hop"},"boards":["#,
+                r#"{"width":3,"height":1,"rows":["T.T"],"exit":{"side":"top","pos":1},"#,
+                r#""player":{"x":1,"y":0},"teleports":[{"from":{"x":0,"y":0},"to":{"x":2,"y":0}},"#,
+                r#"{"from":{"x":2,"y":0},"to":{"x":0,"y":0}}]},"#,
+                r#"{"width":2,"height":1,"rows":[".."],"exit":{"side":"left","pos":0},"#,
+                r#""player":{"x":1,"y":0},"teleports":[]}]}"#
+            )
+            .to_string()),
+            super::export("title: Hop\n x\nTRT\n\n<0\n.R", Format::Json)
+        );
+    }
+
+    #[test]
+    fn ron() {
+        assert_eq!(
+            Ok(concat!(
+                r#"(version: 1, metadata: (to: "Hop"), boards: ["#,
+                r#"(width: 3, height: 1, rows: ["T.T"], exit: (side: "top", pos: 1), "#,
+                r#"player: (x: 1, y: 0), teleports: [(from: (x: 0, y: 0), to: Some((x: 2, y: 0))), "#,
+                r#"(from: (x: 2, y: 0), to: Some((x: 0, y: 0)))]), "#,
+                r#"(width: 2, height: 1, rows: ["T."], exit: (side: "left", pos: 0), "#,
+                r#"player: (x: 1, y: 0), teleports: [(from: (x: 0, y: 0), to: None)])])"#
+            )
+            .to_string()),
+            super::export("to: Hop\n x\nTRT\n\n<0\nTR", Format::Ron)
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Canonical puzzle formatting
+
+use crate::metadata::{self, Metadata};
+use crate::{Board, Player, Result, Side};
+
+/// Metadata keys that always come first, in this order; the rest follow alphabetically
+const KEY_ORDER: &[&str] = &["version", "title", "author", "par"];
+
+/// Sort metadata into its canonical order
+fn order(mut metadata: Metadata) -> Metadata {
+    metadata.sort_by_key(|(key, _)| {
+        let rank = KEY_ORDER.iter().position(|k| k == key);
+        (rank.unwrap_or(KEY_ORDER.len()), key.clone())
+    });
+    metadata
+}
+
+/// Pad the board rows to the same width
+///
+/// Stepping off the end of a short row already behaves like hitting a wall, so
+/// padding with walls keeps the puzzle the same. The exception is the row of
+/// an exit on the right, which sits just past that row's own end, so that row
+/// is left as it is.
+fn pad((board, player): &(Board, Player)) -> String {
+    let text = board.to_text(*player);
+    let mut lines = text.lines();
+    let exit = lines.next().unwrap_or_default().trim_end();
+    let rows: Vec<&str> = lines.collect();
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+
+    std::iter::once(exit.to_string())
+        .chain(rows.iter().enumerate().map(|(y, r)| {
+            match board.exit.side == Side::Right && board.exit.pos == y {
+                true => r.to_string(),
+                false => format!("{:W<width$}", r, width = width),
+            }
+        }))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Format a puzzle in its canonical form
+pub fn format(input: &str) -> Result<String> {
+    let input = input
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let (metadata, _) = metadata::split(&input);
+    let (board1, board2) = crate::parse_puzzle(&input)?;
+
+    Ok(write(metadata, [&board1, &board2]))
+}
+
+/// Write out a puzzle in its canonical form
+pub fn write(metadata: Metadata, boards: [&(Board, Player); 2]) -> String {
+    let mut out = String::new();
+
+    let metadata = order(metadata);
+    for (key, value) in &metadata {
+        out.push_str(&format!("{}: {}\n", key, value));
+    }
+    if !metadata.is_empty() {
+        out.push('\n');
+    }
+
+    let boards = boards.map(pad);
+    out.push_str(&boards.join("\n\n"));
+    out.push('\n');
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn format() {
+        let input = "par: 3\nauthor: Someone\ntitle: Ragged\n  x   \n.R. \n..\n\n x\n R\n";
+
+        assert_eq!(
+            Ok("title: Ragged\nauthor: Someone\npar: 3\n\n  x\n.R.\n..W\n\n x\n.R\n".to_string()),
+            super::format(input)
+        );
+    }
+
+    #[test]
+    fn ragged_right_exit() {
+        let input = ">1\n...\n.R\n\n>1\n...\n.R";
+        let formatted = super::format(input).unwrap();
+
+        assert_eq!(">1\n...\n.R\n\n>1\n...\n.R\n", formatted);
+        assert_eq!(Ok(vec![crate::Dir::Right]), crate::solve_puzzle(&formatted));
+    }
+}
@@ -0,0 +1,46 @@
+//! Entry points for the fuzz targets under `fuzz/`
+//!
+//! Each takes arbitrary bytes as puzzle text. Errors are fine, panics are
+//! bugs. Searching is held to small boards and a short timeout, so a slow
+//! input shows up as a timeout instead of stalling the fuzzer.
+
+use std::time::Duration;
+
+use crate::{limits, Player};
+
+/// The most cells a board may have when fuzzing the solver
+const MAX_CELLS: usize = 64;
+
+/// How long the solver gets on one input
+const TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Parse the bytes as a puzzle, and look at every cell of both boards
+pub fn parse(data: &[u8]) {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(boards) = crate::parse_puzzle(text) else {
+        return;
+    };
+
+    for (board, _) in [boards.0, boards.1] {
+        board.exit_cell();
+        for (y, row) in board.tiles.iter().enumerate() {
+            for x in 0..row.len() {
+                board.get_tile(Player {
+                    x: x as isize,
+                    y: y as isize,
+                });
+            }
+        }
+    }
+}
+
+/// Solve the bytes as a puzzle
+pub fn solve(data: &[u8]) {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    limits::restrict(MAX_CELLS, TIMEOUT);
+    let _ = crate::solve_puzzle(text);
+}
@@ -0,0 +1,631 @@
+//! Making up random puzzles to practice on
+//!
+//! Boards are filled at random and kept only if the solver can get both
+//! players out. The puzzle records its seed, so running `gen` again with that
+//! `--seed` and the same options gives the same puzzle.
+//!
+//! Asking for a difficulty, as a range of solution lengths or of nodes the
+//! solver expands, turns it into a search: the puzzle is changed a little at a
+//! time, keeping each change that doesn't take it further from the range.
+//!
+//! The walls are either scattered at random or laid out as a maze, carved by
+//! a recursive backtracker (long winding corridors) or by Prim's algorithm
+//! (short branching dead ends). Ice, pits and teleports go on the floor after.
+//!
+//! A curriculum is a level pack for new players: a few puzzles for each
+//! mechanic in turn, each taking a few more moves than the last.
+
+use crate::algorithm::{self, Algorithm};
+use crate::cli::Matches;
+use crate::rng::{self, Rng};
+use crate::{fmt, limits};
+use crate::{Board, Error, Exit, Player, Result, Side, Stats, Tile};
+
+type Boards = [(Board, Player); 2];
+
+/// A range of numbers, written `N`, `MIN-MAX`, `MIN-` or `-MAX`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Band {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl Band {
+    /// Parse a range from the command line
+    pub fn parse(text: &str) -> Option<Self> {
+        let bound = |s: &str, default| match s.trim() {
+            "" => Some(default),
+            s => s.parse().ok(),
+        };
+        let band = match text.split_once('-') {
+            Some((min, max)) => Band {
+                min: bound(min, 0)?,
+                max: bound(max, usize::MAX)?,
+            },
+            None => {
+                let n = text.trim().parse().ok()?;
+                Band { min: n, max: n }
+            }
+        };
+
+        (band.min <= band.max).then_some(band)
+    }
+
+    /// How far the number is outside the range
+    fn distance(self, n: usize) -> usize {
+        self.min.saturating_sub(n) + n.saturating_sub(self.max)
+    }
+}
+
+/// How the walls are laid out
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Layout {
+    /// Each cell is a wall by chance
+    Random,
+    /// A maze carved by a depth-first walk that backs up at dead ends
+    Backtracker,
+    /// A maze grown from a random edge of the part carved so far
+    Prim,
+}
+
+impl Layout {
+    /// Parse a layout name from the command line
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "random" => Ok(Layout::Random),
+            "backtracker" => Ok(Layout::Backtracker),
+            "prim" => Ok(Layout::Prim),
+            _ => Err(Error::Usage(
+                "--layout must be random, backtracker or prim".to_string(),
+            )),
+        }
+    }
+}
+
+/// What the boards are made of, from the command line
+#[derive(Debug, PartialEq)]
+pub struct Params {
+    pub width: usize,
+    pub height: usize,
+    pub layout: Layout,
+    /// The share of cells that are walls, when they're laid out at random
+    pub walls: f64,
+    /// The share of floor cells that are ice
+    pub ice: f64,
+    /// How many pits each board has
+    pub pits: usize,
+    /// Whether each board has a pair of teleports
+    pub teleports: bool,
+    /// How many puzzles to try before giving up
+    pub tries: usize,
+    /// The solution lengths to aim for
+    pub moves: Option<Band>,
+    /// The numbers of nodes to aim for the solver to expand
+    pub nodes: Option<Band>,
+}
+
+impl Params {
+    /// Read the parameters from `--width`, `--height`, `--layout`, `--walls`,
+    /// `--ice`, `--pits`, `--teleports`, `--tries`, `--moves` and `--nodes`
+    pub fn parse_args(matches: &Matches) -> Result<Self> {
+        let count = |flag, default| match matches.value(flag) {
+            Some(value) => value
+                .parse()
+                .ok()
+                .filter(|&n| n > 0 || flag == "pits")
+                .ok_or_else(|| Error::Usage(format!("--{} takes a number", flag))),
+            None => Ok(default),
+        };
+        let share = |flag, default| match matches.value(flag) {
+            Some(value) => value
+                .parse()
+                .ok()
+                .filter(|p| (0.0..=1.0).contains(p))
+                .ok_or_else(|| Error::Usage(format!("--{} takes a number from 0 to 1", flag))),
+            None => Ok(default),
+        };
+        let band = |flag| match matches.value(flag) {
+            Some(value) => Band::parse(value)
+                .map(Some)
+                .ok_or_else(|| Error::Usage(format!("--{} takes N or MIN-MAX", flag))),
+            None => Ok(None),
+        };
+
+        Ok(Params {
+            width: count("width", 6)?,
+            height: count("height", 6)?,
+            layout: Layout::parse(matches.value("layout").unwrap_or("random"))?,
+            walls: share("walls", 0.15)?,
+            ice: share("ice", 0.1)?,
+            pits: count("pits", 1)?,
+            teleports: matches.flag("teleports"),
+            tries: count("tries", 1000)?,
+            moves: band("moves")?,
+            nodes: band("nodes")?,
+        })
+    }
+}
+
+/// The cells next to a maze cell that are two steps away, where the next
+/// maze cells are
+fn neighbours((x, y): (usize, usize), params: &Params) -> Vec<(usize, usize)> {
+    let mut found = Vec::new();
+    if x >= 2 {
+        found.push((x - 2, y));
+    }
+    if y >= 2 {
+        found.push((x, y - 2));
+    }
+    if x + 2 < params.width {
+        found.push((x + 2, y));
+    }
+    if y + 2 < params.height {
+        found.push((x, y + 2));
+    }
+    found
+}
+
+/// Carve a maze out of solid wall. Maze cells sit at even coordinates, and
+/// carving from one to the next opens the wall between them too.
+fn maze(rng: &mut Rng, params: &Params) -> Vec<Vec<Tile>> {
+    let mut tiles = vec![vec![Tile::Wall; params.width]; params.height];
+    let open = |tiles: &[Vec<Tile>], (x, y): (usize, usize)| matches!(tiles[y][x], Tile::None);
+    // Open a maze cell and the wall between it and the one it's carved from
+    let carve = |tiles: &mut [Vec<Tile>], (x, y): (usize, usize), (nx, ny): (usize, usize)| {
+        tiles[(y + ny) / 2][(x + nx) / 2] = Tile::None;
+        tiles[ny][nx] = Tile::None;
+    };
+    let start = (
+        rng.below(params.width.div_ceil(2)) * 2,
+        rng.below(params.height.div_ceil(2)) * 2,
+    );
+    carve(&mut tiles, start, start);
+
+    match params.layout {
+        Layout::Backtracker => {
+            let mut path = vec![start];
+            while let Some(&cell) = path.last() {
+                let next: Vec<_> = neighbours(cell, params)
+                    .into_iter()
+                    .filter(|&n| !open(&tiles, n))
+                    .collect();
+                if next.is_empty() {
+                    path.pop();
+                    continue;
+                }
+                let n = next[rng.below(next.len())];
+                carve(&mut tiles, cell, n);
+                path.push(n);
+            }
+        }
+        Layout::Prim | Layout::Random => {
+            // Walls between the maze and cells not in it yet, as (from, to)
+            let mut edges: Vec<_> = neighbours(start, params)
+                .into_iter()
+                .map(|n| (start, n))
+                .collect();
+            while !edges.is_empty() {
+                let (cell, n) = edges.swap_remove(rng.below(edges.len()));
+                if open(&tiles, n) {
+                    continue;
+                }
+                carve(&mut tiles, cell, n);
+                edges.extend(neighbours(n, params).into_iter().map(|m| (n, m)));
+            }
+        }
+    }
+
+    tiles
+}
+
+/// Lay out the walls, with ice on some of the floor
+fn tiles(rng: &mut Rng, params: &Params) -> Vec<Vec<Tile>> {
+    if params.layout == Layout::Random {
+        return (0..params.height)
+            .map(|_| {
+                (0..params.width)
+                    .map(
+                        |_| match (rng.chance(params.walls), rng.chance(params.ice)) {
+                            (true, _) => Tile::Wall,
+                            (false, true) => Tile::Ice,
+                            (false, false) => Tile::None,
+                        },
+                    )
+                    .collect()
+            })
+            .collect();
+    }
+
+    let mut tiles = maze(rng, params);
+    for tile in tiles.iter_mut().flatten() {
+        if matches!(tile, Tile::None) && rng.chance(params.ice) {
+            *tile = Tile::Ice;
+        }
+    }
+    tiles
+}
+
+/// Fill a board at random, with the player somewhere on the floor
+fn board(rng: &mut Rng, params: &Params) -> Option<(Board, Player)> {
+    let mut tiles = tiles(rng, params);
+
+    let mut floor: Vec<(usize, usize)> = (0..params.height)
+        .flat_map(|y| (0..params.width).map(move |x| (x, y)))
+        .filter(|&(x, y)| matches!(tiles[y][x], Tile::None))
+        .collect();
+    rng.shuffle(&mut floor);
+    let teleports = if params.teleports { 2 } else { 0 };
+    if floor.len() <= params.pits + teleports {
+        return None;
+    }
+    let (pits, rest) = floor.split_at(params.pits);
+    for &(x, y) in pits {
+        tiles[y][x] = Tile::Pit;
+    }
+    for &(x, y) in &rest[..teleports] {
+        tiles[y][x] = Tile::Teleport;
+    }
+    let (x, y) = rest[teleports];
+
+    let exit = exit(rng, params);
+    let player = Player {
+        x: x as isize,
+        y: y as isize,
+    };
+
+    Some((Board { tiles, exit }, player))
+}
+
+/// Put the exit somewhere around the board
+fn exit(rng: &mut Rng, params: &Params) -> Exit {
+    match rng.below(4) {
+        0 => Exit {
+            side: Side::Top,
+            pos: rng.below(params.width),
+        },
+        1 => Exit {
+            side: Side::Bottom,
+            pos: rng.below(params.width),
+        },
+        2 => Exit {
+            side: Side::Left,
+            pos: rng.below(params.height),
+        },
+        _ => Exit {
+            side: Side::Right,
+            pos: rng.below(params.height),
+        },
+    }
+}
+
+/// Change one thing about one of the boards: swap two cells, which keeps the
+/// mix of tiles the same, or move the player or the exit
+fn mutate(rng: &mut Rng, params: &Params, boards: &mut Boards) {
+    let (board, player) = &mut boards[rng.below(2)];
+    let mut cell = || Player {
+        x: rng.below(params.width) as isize,
+        y: rng.below(params.height) as isize,
+    };
+    let (a, b) = (cell(), cell());
+
+    match rng.below(6) {
+        0..=3 if a != *player && b != *player => {
+            let tile = board.get_tile(a);
+            board.tiles[a.y as usize][a.x as usize] = board.get_tile(b);
+            board.tiles[b.y as usize][b.x as usize] = tile;
+        }
+        4 if matches!(board.get_tile(a), Tile::None) => *player = a,
+        5 => board.exit = exit(rng, params),
+        _ => {}
+    }
+}
+
+/// How far a solved puzzle is from the difficulty asked for
+fn distance(params: &Params, (moves, nodes): (usize, usize)) -> usize {
+    params.moves.map_or(0, |band| band.distance(moves))
+        + params.nodes.map_or(0, |band| band.distance(nodes))
+}
+
+/// The length of the shortest solution and the nodes A* expanded to find it,
+/// if there is one
+fn measure([(b1, p1), (b2, p2)]: &Boards) -> Option<(usize, usize)> {
+    let mut stats = Stats::default();
+    let moves = algorithm::search(
+        Algorithm::Astar,
+        (b1, *p1),
+        (b2, *p2),
+        &mut stats,
+        limits::deadline(),
+        &mut |_| {},
+    )
+    .ok()?;
+
+    Some((moves.len(), stats.paths))
+}
+
+/// Make up puzzles from the seed until one has a solution of the difficulty
+/// asked for, returning it with its length and the nodes it took
+fn find(params: &Params, seed: u64) -> Result<(Boards, (usize, usize))> {
+    limits::check_size(params.width, params.height)?;
+    let mut rng = Rng::from_seed(seed);
+    // The puzzle nearest the difficulty so far, and how near
+    let mut current: Option<(Boards, usize)> = None;
+
+    for _ in 0..params.tries {
+        let boards = match &current {
+            Some((boards, _)) => {
+                let mut boards = boards.clone();
+                mutate(&mut rng, params, &mut boards);
+                boards
+            }
+            None => match (board(&mut rng, params), board(&mut rng, params)) {
+                (Some(first), Some(second)) => [first, second],
+                _ => continue,
+            },
+        };
+        limits::check(&boards[0].0)?;
+
+        let Some(measured) = measure(&boards) else {
+            continue;
+        };
+        let distance = distance(params, measured);
+        if distance == 0 {
+            return Ok((boards, measured));
+        }
+        if current.as_ref().is_none_or(|(_, d)| distance <= *d) {
+            current = Some((boards, distance));
+        }
+    }
+
+    Err(Error::NoSolution)
+}
+
+/// Make up a puzzle from the seed with a solution of the difficulty asked for
+pub fn generate(params: &Params, seed: u64) -> Result<String> {
+    let (boards, (moves, _)) = find(params, seed)?;
+    let metadata = vec![
+        ("par".to_string(), moves.to_string()),
+        ("seed".to_string(), seed.to_string()),
+    ];
+    Ok(fmt::write(metadata, [&boards[0], &boards[1]]))
+}
+
+/// The mechanics a curriculum brings in, one stage each, with the tile that
+/// has to be on the boards to show it
+const STAGES: [(&str, Tile); 4] = [
+    ("Walls", Tile::Wall),
+    ("Ice", Tile::Ice),
+    ("Pits", Tile::Pit),
+    ("Teleports", Tile::Teleport),
+];
+
+/// How many different seeds a curriculum level gets before giving up
+const LEVEL_SEEDS: usize = 20;
+
+/// Make up a level pack bringing in one mechanic at a time, `levels` puzzles
+/// each, where every puzzle takes more moves than the one before
+pub fn curriculum(params: &Params, seed: u64, levels: usize) -> Result<String> {
+    let mut rng = Rng::from_seed(seed);
+    let mut pack = format!("name: Curriculum\nseed: {}\n", seed);
+    let mut par = 0;
+
+    for (stage, (name, tile)) in STAGES.into_iter().enumerate() {
+        for level in 1..=levels {
+            // Each stage keeps the mechanics before it
+            let params = Params {
+                ice: if stage >= 1 { params.ice } else { 0.0 },
+                pits: if stage >= 2 { params.pits.max(1) } else { 0 },
+                teleports: stage >= 3,
+                moves: Some(Band {
+                    min: par + 1,
+                    max: par + 3,
+                }),
+                nodes: None,
+                ..*params
+            };
+            let shows = |boards: &Boards| {
+                boards
+                    .iter()
+                    .flat_map(|(b, _)| b.tiles.iter().flatten())
+                    .any(|t| t.to_char() == tile.to_char())
+            };
+            let (boards, (moves, _), seed) = (0..LEVEL_SEEDS)
+                .map(|_| rng.next_u64())
+                .find_map(|seed| {
+                    let (boards, measured) = find(&params, seed).ok()?;
+                    shows(&boards).then_some((boards, measured, seed))
+                })
+                .ok_or(Error::NoSolution)?;
+            par = moves;
+
+            let metadata = vec![
+                ("title".to_string(), format!("{} {}", name, level)),
+                ("par".to_string(), moves.to_string()),
+                ("seed".to_string(), seed.to_string()),
+            ];
+            let id = stage * levels + level;
+            pack.push_str(&format!(
+                "\n=== {}\n{}",
+                id,
+                fmt::write(metadata, [&boards[0], &boards[1]])
+            ));
+        }
+    }
+
+    Ok(pack)
+}
+
+/// Make up a puzzle, or a curriculum, the way the command line asks
+pub fn from_args(matches: &Matches) -> Result<String> {
+    let params = Params::parse_args(matches)?;
+    let seed = rng::seed_or_clock();
+    if !matches.flag("curriculum") {
+        return generate(&params, seed);
+    }
+
+    let levels = match matches.value("levels") {
+        Some(n) => n
+            .parse()
+            .ok()
+            .filter(|&n| n > 0)
+            .ok_or(Error::Usage("--levels takes a positive number".to_string()))?,
+        None => 2,
+    };
+    curriculum(&params, seed, levels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Band, Layout, Params};
+    use crate::pack::Pack;
+    use crate::rng::Rng;
+    use crate::{Error, Tile};
+
+    #[test]
+    fn generate() {
+        let params = Params {
+            width: 5,
+            height: 4,
+            layout: Layout::Random,
+            walls: 0.2,
+            ice: 0.2,
+            pits: 2,
+            teleports: false,
+            tries: 1000,
+            moves: None,
+            nodes: None,
+        };
+        let puzzle = super::generate(&params, 42).unwrap();
+
+        assert_eq!(Ok(puzzle.clone()), super::generate(&params, 42));
+        assert!(puzzle.contains("seed: 42"));
+        assert_eq!(2 * 2, puzzle.matches('P').count());
+
+        let (metadata, _) = crate::metadata::split(&puzzle);
+        let par = crate::metadata::get(&metadata, "par").unwrap();
+        let moves = crate::search_with(&puzzle, crate::algorithm::Algorithm::Bfs)
+            .unwrap()
+            .0;
+        assert_eq!(par, moves.len().to_string());
+    }
+
+    #[test]
+    fn too_large() {
+        let params = Params {
+            width: 100_000,
+            height: 100_000,
+            layout: Layout::Random,
+            walls: 0.15,
+            ice: 0.1,
+            pits: 1,
+            teleports: false,
+            tries: 1000,
+            moves: None,
+            nodes: None,
+        };
+        assert_eq!(Err(Error::BoardTooLarge), super::generate(&params, 1));
+
+        let params = Params {
+            width: usize::MAX,
+            height: 2,
+            ..params
+        };
+        assert_eq!(Err(Error::BoardTooLarge), super::generate(&params, 1));
+    }
+
+    #[test]
+    fn difficulty() {
+        assert_eq!(Some(Band { min: 8, max: 12 }), Band::parse("8-12"));
+        assert_eq!(
+            Some(Band {
+                min: 8,
+                max: usize::MAX
+            }),
+            Band::parse("8-")
+        );
+        assert_eq!(None, Band::parse("12-8"));
+
+        let params = Params {
+            width: 6,
+            height: 6,
+            layout: Layout::Random,
+            walls: 0.15,
+            ice: 0.1,
+            pits: 1,
+            teleports: false,
+            tries: 5000,
+            moves: Band::parse("14-16"),
+            nodes: None,
+        };
+        let puzzle = super::generate(&params, 1).unwrap();
+        let (metadata, _) = crate::metadata::split(&puzzle);
+        let par: usize = crate::metadata::get(&metadata, "par")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((14..=16).contains(&par), "{}", par);
+    }
+
+    #[test]
+    fn maze() {
+        for layout in [Layout::Backtracker, Layout::Prim] {
+            let params = Params {
+                width: 7,
+                height: 5,
+                layout,
+                walls: 0.0,
+                ice: 0.0,
+                pits: 0,
+                teleports: true,
+                tries: 1000,
+                moves: None,
+                nodes: None,
+            };
+            let tiles = super::maze(&mut Rng::from_seed(5), &params);
+            let floor = |x: usize, y: usize| matches!(tiles[y][x], Tile::None);
+
+            // Every maze cell is carved, joined to the rest by a tree of
+            // passages: one fewer than the cells
+            let cells = 4 * 3;
+            assert!((0..7)
+                .step_by(2)
+                .all(|x| (0..5).step_by(2).all(|y| floor(x, y))));
+            let open = (0..7).flat_map(|x| (0..5).map(move |y| (x, y)));
+            assert_eq!(2 * cells - 1, open.filter(|&(x, y)| floor(x, y)).count());
+            assert!(!floor(1, 1));
+
+            let puzzle = super::generate(&params, 5).unwrap();
+            assert_eq!(2 * 2, puzzle.matches('T').count());
+        }
+    }
+
+    #[test]
+    fn curriculum() {
+        let params = Params {
+            width: 5,
+            height: 5,
+            layout: Layout::Random,
+            walls: 0.15,
+            ice: 0.15,
+            pits: 1,
+            teleports: false,
+            tries: 500,
+            moves: None,
+            nodes: None,
+        };
+        let pack = Pack::parse(&super::curriculum(&params, 9, 2).unwrap()).unwrap();
+        let field = |i: usize, key| {
+            crate::metadata::get(&pack.levels[i].metadata(), key)
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(8, pack.levels.len());
+        assert_eq!("Walls 1", field(0, "title"));
+        assert_eq!("Teleports 2", field(7, "title"));
+        assert!(!pack.levels[3].text.contains('T'));
+        assert!(pack.levels[7].text.contains('T'));
+        let pars: Vec<usize> = (0..8).map(|i| field(i, "par").parse().unwrap()).collect();
+        assert!(pars.windows(2).all(|w| w[0] < w[1]), "{:?}", pars);
+    }
+}
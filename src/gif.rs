@@ -0,0 +1,278 @@
+//! Animated GIFs of a solution, for sharing in chat
+//!
+//! The frames are the same ones `--animate` plays in the terminal, drawn as
+//! colored squares and encoded by hand, since there's little to a GIF with a
+//! fixed eight-color palette.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::{Board, Dir, Error, Player, Result, Tile};
+
+/// The size of a tile, in pixels
+const CELL: usize = 16;
+
+/// The colors, indexed by [`color`]
+const PALETTE: [[u8; 3]; 8] = [
+    [0xff, 0xff, 0xff],
+    [0xee, 0xee, 0xee],
+    [0x55, 0x55, 0x55],
+    [0xaa, 0x44, 0xcc],
+    [0x11, 0x11, 0x11],
+    [0xbb, 0xdd, 0xff],
+    [0x33, 0xaa, 0x33],
+    [0xff, 0xcc, 0x00],
+];
+
+/// The palette index for drawing the players
+const PLAYER: u8 = 7;
+
+/// The palette index for each kind of tile
+fn color(tile: Tile) -> u8 {
+    match tile {
+        Tile::None => 1,
+        Tile::Wall => 2,
+        Tile::Teleport => 3,
+        Tile::Pit => 4,
+        Tile::Ice => 5,
+        Tile::Exit => 6,
+    }
+}
+
+/// A frame's pixels as palette indices, one row after another
+struct Canvas {
+    width: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    /// Fill a cell, leaving `inset` pixels of its edge as they were
+    fn fill(&mut self, (cx, cy): (usize, usize), inset: usize, color: u8) {
+        for y in cy * CELL + inset..(cy + 1) * CELL - inset {
+            let row = y * self.width;
+            self.pixels[row + cx * CELL + inset..row + (cx + 1) * CELL - inset].fill(color);
+        }
+    }
+
+    /// Draw a board with its left edge at the given cell, leaving a cell of
+    /// margin all round for the exit
+    fn board(&mut self, left: usize, board: &Board, player: Player) {
+        let cell = |p: Player| ((left as isize + p.x + 1) as usize, (p.y + 1) as usize);
+
+        for (y, row) in board.tiles.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                let p = Player {
+                    x: x as isize,
+                    y: y as isize,
+                };
+                self.fill(cell(p), 0, color(tile));
+            }
+        }
+
+        self.fill(cell(board.exit_cell()), 0, color(Tile::Exit));
+        self.fill(cell(player), CELL / 4, PLAYER);
+    }
+}
+
+/// Packs variable-width codes into bytes, least significant bit first
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    buffer: u32,
+    len: u8,
+}
+
+impl Bits {
+    fn push(&mut self, code: u16, size: u8) {
+        self.buffer |= (code as u32) << self.len;
+        self.len += size;
+        while self.len >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Compress palette indices with GIF's flavor of LZW
+fn lzw(min_code_size: u8, pixels: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+
+    let mut out = Bits::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut size = min_code_size + 1;
+    let mut next = end + 1;
+    out.push(clear, size);
+
+    let mut pixels = pixels.iter();
+    let Some(&first) = pixels.next() else {
+        out.push(end, size);
+        return out.finish();
+    };
+    let mut prefix = first as u16;
+
+    for &pixel in pixels {
+        if let Some(&code) = table.get(&(prefix, pixel)) {
+            prefix = code;
+            continue;
+        }
+
+        out.push(prefix, size);
+        table.insert((prefix, pixel), next);
+        if next == 1 << size && size < 12 {
+            size += 1;
+        }
+        next += 1;
+
+        // Start over once the table is full
+        if next == 4096 {
+            out.push(clear, size);
+            table.clear();
+            size = min_code_size + 1;
+            next = end + 1;
+        }
+
+        prefix = pixel as u16;
+    }
+
+    out.push(prefix, size);
+    out.push(end, size);
+    out.finish()
+}
+
+/// Encode the frames as a looping GIF
+fn encode(width: usize, height: usize, frames: &[Vec<u8>], delay: Duration) -> Vec<u8> {
+    let mut out = b"GIF89a".to_vec();
+    out.extend((width as u16).to_le_bytes());
+    out.extend((height as u16).to_le_bytes());
+    // A global color table of 2^(2+1) colors
+    out.extend([0xf2, 0, 0]);
+    out.extend(PALETTE.iter().flatten());
+    // Loop forever
+    out.extend(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+    let centiseconds = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+    for frame in frames {
+        out.extend([0x21, 0xf9, 0x04, 0x00]);
+        out.extend(centiseconds.to_le_bytes());
+        out.extend([0x00, 0x00]);
+
+        out.push(0x2c);
+        out.extend([0, 0, 0, 0]);
+        out.extend((width as u16).to_le_bytes());
+        out.extend((height as u16).to_le_bytes());
+        out.push(0);
+
+        out.push(3);
+        for block in lzw(3, frame).chunks(255) {
+            out.push(block.len() as u8);
+            out.extend(block);
+        }
+        out.push(0);
+    }
+
+    out.push(0x3b);
+    out
+}
+
+/// Render the moves being played on both boards as an animated GIF
+pub fn animate(input: &str, moves: &[Dir], delay: Duration) -> Result<Vec<u8>> {
+    let ((b1, p1), (b2, p2)) = crate::parse_puzzle(input)?;
+    let cols = |b: &Board| b.tiles.iter().map(Vec::len).max().unwrap_or(0) + 2;
+    let rows = b1.tiles.len().max(b2.tiles.len()) + 2;
+
+    let (width, height) = ((cols(&b1) + 1 + cols(&b2)) * CELL, rows * CELL);
+    if width > u16::MAX as usize || height > u16::MAX as usize {
+        return Err(Error::BoardTooLarge);
+    }
+
+    let frames = crate::animate::frames(&b1, &b2, (p1, p2), moves)
+        .into_iter()
+        .map(|(_, p1, p2)| {
+            let mut canvas = Canvas {
+                width,
+                pixels: vec![0; width * height],
+            };
+            canvas.board(0, &b1, p1);
+            canvas.board(cols(&b1) + 1, &b2, p2);
+            canvas.pixels
+        })
+        .collect::<Vec<_>>();
+
+    Ok(encode(width, height, &frames, delay))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    /// Undo [`super::lzw`]
+    fn unlzw(min_code_size: u8, data: &[u8]) -> Vec<u8> {
+        let clear = 1u16 << min_code_size;
+        let mut table: HashMap<u16, Vec<u8>> = HashMap::new();
+        let (mut size, mut next) = (min_code_size + 1, clear + 2);
+        let (mut pos, mut out, mut prev): (usize, Vec<u8>, Option<Vec<u8>>) = (0, vec![], None);
+
+        loop {
+            let code = (0..size as usize)
+                .map(|i| ((data[(pos + i) / 8] >> ((pos + i) % 8)) & 1) as u16)
+                .enumerate()
+                .map(|(i, bit)| bit << i)
+                .sum::<u16>();
+            pos += size as usize;
+
+            if code == clear {
+                table = (0..clear).map(|c| (c, vec![c as u8])).collect();
+                (size, next, prev) = (min_code_size + 1, clear + 2, None);
+                continue;
+            }
+            if code == clear + 1 {
+                return out;
+            }
+
+            let entry = match (table.get(&code), &prev) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(prev)) => [prev.clone(), vec![prev[0]]].concat(),
+                (None, None) => panic!("bad code"),
+            };
+            if let Some(prev) = prev {
+                table.insert(next, [prev, vec![entry[0]]].concat());
+                next += 1;
+                if next == 1 << size && size < 12 {
+                    size += 1;
+                }
+            }
+            out.extend(&entry);
+            prev = Some(entry);
+        }
+    }
+
+    #[test]
+    fn lzw() {
+        let pixels = (0..20_000u32)
+            .map(|i| ((i * i) % 7 / 2) as u8)
+            .collect::<Vec<_>>();
+        assert_eq!(pixels, unlzw(3, &super::lzw(3, &pixels)));
+    }
+
+    #[test]
+    fn animate() {
+        let gif = super::animate(
+            " x\nTRT\n\n x\nR.I.",
+            &[crate::Dir::Right],
+            Default::default(),
+        )
+        .unwrap();
+
+        assert!(gif.starts_with(b"GIF89a\xc0\x00\x30\x00"));
+        assert_eq!(Some(&0x3b), gif.last());
+    }
+}
@@ -0,0 +1,342 @@
+//! The whole graph of positions a puzzle can reach
+//!
+//! The solver stops as soon as it finds the exit, but questions about how
+//! hard or how punishing a puzzle is need every position both players can get
+//! to from the start, and where each move from there leads.
+//!
+//! `graph` writes the graph out for other tools. In Graphviz DOT, the start
+//! has a double outline, positions the puzzle can't be solved from any more
+//! are red, and every move that solves or loses the game leads to a shared
+//! "solved" or "lost" box. Moves that go nowhere are left out. GraphML, for
+//! Gephi or NetworkX, gives the same nodes a `kind` instead: start, position,
+//! dead, solved or lost. CSV is just the edges, one per line.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+use crate::verify::{self, Step};
+use crate::{interrupt, limits, Board, Dir, Error, Player, Result};
+
+/// Output formats for the graph
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Dot,
+    Graphml,
+    /// An edge list
+    Csv,
+}
+
+impl Format {
+    /// Parse a format name from the command line
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "dot" => Ok(Format::Dot),
+            "graphml" => Ok(Format::Graphml),
+            "csv" => Ok(Format::Csv),
+            _ => Err(Error::UnknownFormat),
+        }
+    }
+}
+
+/// The directions in the order every state's edges are kept in
+pub const DIRS: [Dir; 4] = [Dir::Up, Dir::Down, Dir::Right, Dir::Left];
+
+/// How often exploring checks whether it's out of time
+const CHECK_EVERY: usize = 1024;
+
+/// Where the players are on both boards
+pub type State = (Player, Player);
+
+/// Where a move from a state leads
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Edge {
+    /// To the state with this index, which may be the same one
+    To(usize),
+    /// Both players out the exit
+    Solved,
+    /// A player fell, or went out alone
+    Lost,
+}
+
+/// Every state reachable from the start, the start being the first
+#[derive(Debug)]
+pub struct Graph {
+    pub states: Vec<State>,
+    /// The edges out of each state, in the order of [`DIRS`]
+    pub edges: Vec<[Edge; 4]>,
+}
+
+impl Graph {
+    /// Visit every state reachable from the start, giving up at the deadline
+    pub fn explore(
+        (b1, p1): (&Board, Player),
+        (b2, p2): (&Board, Player),
+        deadline: Option<Instant>,
+    ) -> Result<Self> {
+        let mut index = HashMap::from([((p1, p2), 0)]);
+        let mut graph = Graph {
+            states: vec![(p1, p2)],
+            edges: Vec::new(),
+        };
+
+        // States are numbered as they're found, so the next one to expand is
+        // always the one after the last expanded
+        while graph.edges.len() < graph.states.len() {
+            let (p1, p2) = graph.states[graph.edges.len()];
+            if graph.edges.len().is_multiple_of(CHECK_EVERY) {
+                if interrupt::interrupted() {
+                    return Err(Error::Interrupted);
+                }
+                if deadline.is_some_and(|d| Instant::now() >= d) {
+                    return Err(Error::Timeout);
+                }
+            }
+
+            let edges = DIRS.map(|dir| match verify::step(dir, (b1, p1), (b2, p2)) {
+                Step::Solved => Edge::Solved,
+                Step::Died(_) | Step::ExitedAlone(_) => Edge::Lost,
+                Step::Moved(n1, n2) => Edge::To(*index.entry((n1, n2)).or_insert_with(|| {
+                    graph.states.push((n1, n2));
+                    graph.states.len() - 1
+                })),
+            });
+            graph.edges.push(edges);
+        }
+
+        Ok(graph)
+    }
+
+    /// Explore the puzzle's graph
+    pub fn of(input: &str) -> Result<Self> {
+        let ((b1, p1), (b2, p2)) = crate::parse_puzzle(input)?;
+        limits::check(&b1)?;
+        limits::check(&b2)?;
+
+        Graph::explore((&b1, p1), (&b2, p2), limits::deadline())
+    }
+
+    /// How many moves each state is from solving the puzzle, or `None` for
+    /// the states it can't be solved from any more
+    pub fn to_goal(&self) -> Vec<Option<usize>> {
+        let mut into = vec![Vec::new(); self.states.len()];
+        let mut distance = vec![None; self.states.len()];
+        let mut queue = VecDeque::new();
+
+        for (from, edges) in self.edges.iter().enumerate() {
+            for edge in edges {
+                match *edge {
+                    Edge::To(to) => into[to].push(from),
+                    Edge::Solved if distance[from].is_none() => {
+                        distance[from] = Some(1);
+                        queue.push_back(from);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        while let Some(state) = queue.pop_front() {
+            let next = distance[state].map(|d| d + 1);
+            for &from in &into[state] {
+                if distance[from].is_none() {
+                    distance[from] = next;
+                    queue.push_back(from);
+                }
+            }
+        }
+
+        distance
+    }
+
+    /// The fewest moves from the start to each state
+    pub fn paths(&self) -> Vec<Vec<Dir>> {
+        let mut paths: Vec<Option<Vec<Dir>>> = vec![None; self.states.len()];
+        paths[0] = Some(Vec::new());
+
+        // States were numbered breadth first, so every state's parent comes
+        // before it
+        for from in 0..self.states.len() {
+            let path = paths[from].clone().unwrap_or_default();
+            for (dir, edge) in DIRS.into_iter().zip(&self.edges[from]) {
+                if let Edge::To(to) = *edge {
+                    paths[to].get_or_insert_with(|| [&path[..], &[dir]].concat());
+                }
+            }
+        }
+
+        paths.into_iter().map(Option::unwrap_or_default).collect()
+    }
+
+    /// The moves between each pair of states, a move being grouped with the
+    /// others going the same way, in the order they're first found. A move
+    /// to `None` solves the game when it's `Edge::Solved` and loses it when
+    /// it's `Edge::Lost`.
+    fn moves(&self) -> Vec<(usize, Edge, Vec<Dir>)> {
+        let mut moves: Vec<(usize, Edge, Vec<Dir>)> = Vec::new();
+        for (from, edges) in self.edges.iter().enumerate() {
+            for (dir, edge) in DIRS.into_iter().zip(edges) {
+                if *edge == Edge::To(from) {
+                    continue;
+                }
+                match moves.iter_mut().find(|(f, e, _)| *f == from && e == edge) {
+                    Some((_, _, dirs)) => dirs.push(dir),
+                    None => moves.push((from, *edge, vec![dir])),
+                }
+            }
+        }
+        moves
+    }
+
+    /// Write the graph in Graphviz DOT
+    fn dot(&self) -> String {
+        let to_goal = self.to_goal();
+        let mut out = String::from("digraph puzzle {\n");
+
+        for (i, (p1, p2)) in self.states.iter().enumerate() {
+            let mut attrs = format!("label=\"({}, {}) ({}, {})\"", p1.x, p1.y, p2.x, p2.y);
+            if i == 0 {
+                attrs.push_str(", shape=doublecircle");
+            }
+            if to_goal[i].is_none() {
+                attrs.push_str(", style=filled, fillcolor=lightcoral");
+            }
+            out.push_str(&format!("  s{} [{}];\n", i, attrs));
+        }
+        let ends = self.edges.iter().flatten();
+        if ends.clone().any(|e| *e == Edge::Solved) {
+            out.push_str("  solved [shape=box, style=filled, fillcolor=palegreen];\n");
+        }
+        if ends.clone().any(|e| *e == Edge::Lost) {
+            out.push_str("  lost [shape=box, style=filled, fillcolor=gray];\n");
+        }
+
+        for (from, edge, dirs) in self.moves() {
+            let label: Vec<String> = dirs.iter().map(|d| d.to_char().to_string()).collect();
+            out.push_str(&format!(
+                "  s{} -> {} [label=\"{}\"];\n",
+                from,
+                Graph::target(edge),
+                label.join(",")
+            ));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// The id a move leads to in the written graph
+    fn target(edge: Edge) -> String {
+        match edge {
+            Edge::To(to) => format!("s{}", to),
+            Edge::Solved => "solved".to_string(),
+            Edge::Lost => "lost".to_string(),
+        }
+    }
+
+    /// Write the graph in GraphML
+    fn graphml(&self) -> String {
+        let to_goal = self.to_goal();
+        let mut out = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n  \
+               <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n  \
+               <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n  \
+               <key id=\"moves\" for=\"edge\" attr.name=\"moves\" attr.type=\"string\"/>\n  \
+               <graph id=\"puzzle\" edgedefault=\"directed\">\n",
+        );
+        let node = |id: &str, label: &str, kind| {
+            format!(
+                "    <node id=\"{}\"><data key=\"label\">{}</data><data key=\"kind\">{}</data></node>\n",
+                id, label, kind
+            )
+        };
+
+        for (i, (p1, p2)) in self.states.iter().enumerate() {
+            let kind = match (i, to_goal[i]) {
+                (0, _) => "start",
+                (_, None) => "dead",
+                _ => "position",
+            };
+            let label = format!("({}, {}) ({}, {})", p1.x, p1.y, p2.x, p2.y);
+            out.push_str(&node(&format!("s{}", i), &label, kind));
+        }
+        out.push_str(&node("solved", "solved", "solved"));
+        out.push_str(&node("lost", "lost", "lost"));
+
+        for (from, edge, dirs) in self.moves() {
+            let label: String = dirs.iter().map(|d| d.to_char()).collect();
+            out.push_str(&format!(
+                "    <edge source=\"s{}\" target=\"{}\"><data key=\"moves\">{}</data></edge>\n",
+                from,
+                Graph::target(edge),
+                label
+            ));
+        }
+
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// Write the graph's edges as CSV
+    fn csv(&self) -> String {
+        let mut out = String::from("source,target,moves\n");
+        for (from, edge, dirs) in self.moves() {
+            let label: String = dirs.iter().map(|d| d.to_char()).collect();
+            out.push_str(&format!("s{},{},{}\n", from, Graph::target(edge), label));
+        }
+        out
+    }
+
+    /// Write the graph in the given format
+    pub fn write(&self, format: Format) -> String {
+        match format {
+            Format::Dot => self.dot(),
+            Format::Graphml => self.graphml(),
+            Format::Csv => self.csv(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Edge, Format, Graph};
+
+    #[test]
+    fn explore() {
+        // Player 1 has to get past the pit in the corner
+        let graph = Graph::of("  x\nP..\n.R.\n\n x\n..\n.R").unwrap();
+
+        assert_eq!(Some(3), graph.to_goal()[0]);
+        assert_eq!(graph.states.len(), graph.edges.len());
+        assert!(graph.edges.iter().flatten().any(|e| *e == Edge::Lost));
+        assert!(graph.to_goal().iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn dot() {
+        // Going up wins, and going left falls
+        let graph = Graph::of(" x\nPR.\n\n x\n.R.").unwrap();
+
+        assert_eq!(
+            "digraph puzzle {\n  \
+               s0 [label=\"(1, 0) (1, 0)\", shape=doublecircle];\n  \
+               s1 [label=\"(2, 0) (2, 0)\"];\n  \
+               solved [shape=box, style=filled, fillcolor=palegreen];\n  \
+               lost [shape=box, style=filled, fillcolor=gray];\n  \
+               s0 -> solved [label=\"U\"];\n  \
+               s0 -> s1 [label=\"R\"];\n  \
+               s0 -> lost [label=\"L\"];\n  \
+               s1 -> s0 [label=\"L\"];\n\
+             }\n",
+            graph.write(Format::Dot)
+        );
+        assert_eq!(
+            "source,target,moves\ns0,solved,U\ns0,s1,R\ns0,lost,L\ns1,s0,L\n",
+            graph.write(Format::Csv)
+        );
+        let graphml = graph.write(Format::Graphml);
+        assert!(graphml.contains("<node id=\"s1\"><data key=\"label\">(2, 0) (2, 0)</data><data key=\"kind\">position</data></node>"));
+        assert!(graphml
+            .contains("<edge source=\"s0\" target=\"lost\"><data key=\"moves\">L</data></edge>"));
+    }
+}
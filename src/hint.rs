@@ -0,0 +1,119 @@
+//! Suggesting the next moves from partway through a puzzle
+
+use crate::cli::Matches;
+use crate::verify::{self, Step};
+use crate::{Dir, Error, Player, Result, Stats, Tile, Turn};
+
+/// Where the players are now
+#[derive(Debug, PartialEq)]
+pub enum Position {
+    /// Where the moves made so far lead
+    After(Vec<Dir>),
+    /// Explicit coordinates for both players
+    At(Player, Player),
+}
+
+impl Position {
+    /// Read the position from `--after MOVES` or `--at X1,Y1,X2,Y2`, defaulting
+    /// to the start of the puzzle
+    pub fn parse_args(matches: &Matches) -> Result<Self> {
+        match (matches.value("after"), matches.value("at")) {
+            (Some(_), Some(_)) => Err(Error::Usage(
+                "--after and --at can't be used together".to_string(),
+            )),
+            (Some(moves), None) => Ok(Position::After(Dir::parse_moves(moves)?)),
+            (None, Some(at)) => {
+                let coords = at
+                    .split(',')
+                    .map(|n| n.trim().parse())
+                    .collect::<std::result::Result<Vec<isize>, _>>();
+
+                match coords.as_deref() {
+                    Ok(&[x1, y1, x2, y2]) => Ok(Position::At(
+                        Player { x: x1, y: y1 },
+                        Player { x: x2, y: y2 },
+                    )),
+                    _ => Err(Error::Usage("--at takes X1,Y1,X2,Y2".to_string())),
+                }
+            }
+            (None, None) => Ok(Position::After(Vec::new())),
+        }
+    }
+}
+
+/// Find the next `count` moves of a shortest solution from the given position,
+/// along with how many moves the whole rest of the solution takes
+pub fn hint(input: &str, position: &Position, count: usize) -> Result<(Vec<Dir>, usize)> {
+    let ((b1, mut p1), (b2, mut p2)) = crate::parse_puzzle(input)?;
+    crate::limits::check(&b1)?;
+    crate::limits::check(&b2)?;
+
+    match position {
+        Position::After(moves) => {
+            for &dir in moves {
+                match verify::step(dir, (&b1, p1), (&b2, p2)) {
+                    Step::Moved(n1, n2) => (p1, p2) = (n1, n2),
+                    _ => return Err(Error::GameOver),
+                }
+            }
+        }
+        &Position::At(at1, at2) => {
+            for (board, at) in [(&b1, at1), (&b2, at2)] {
+                if matches!(board.get_tile(at), Tile::Wall | Tile::Pit | Tile::Exit) {
+                    return Err(Error::Usage(format!(
+                        "a player can't stand at ({}, {})",
+                        at.x, at.y
+                    )));
+                }
+            }
+            (p1, p2) = (at1, at2);
+        }
+    }
+
+    let solution = crate::solve(
+        vec![Turn::new(&b1, p1, &b2, p2)],
+        &mut Stats::default(),
+        crate::limits::deadline(),
+        &mut |_| {},
+    )?;
+    let remaining = solution.len();
+
+    Ok((solution.into_iter().take(count).collect(), remaining))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Position;
+    use crate::Dir::*;
+    use crate::{Error, Player};
+
+    const PUZZLE: &str = " x\nTRT\n\n x\nR.I.";
+
+    #[test]
+    fn hint() {
+        assert_eq!(
+            Ok((vec![Right], 4)),
+            super::hint(PUZZLE, &Position::After(vec![]), 1)
+        );
+        assert_eq!(
+            Ok((vec![Right, Up], 2)),
+            super::hint(PUZZLE, &Position::After(vec![Right, Left]), 5)
+        );
+        assert_eq!(
+            Ok((vec![Up], 1)),
+            super::hint(
+                PUZZLE,
+                &Position::At(Player { x: 1, y: 0 }, Player { x: 1, y: 0 }),
+                1
+            )
+        );
+    }
+
+    #[test]
+    fn game_over() {
+        assert_eq!(
+            Err(Error::GameOver),
+            super::hint(PUZZLE, &Position::After(vec![Right, Left, Right, Up]), 1)
+        );
+    }
+}
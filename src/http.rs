@@ -0,0 +1,284 @@
+//! Solving puzzles over HTTP, for web pages and bots
+//!
+//! `POST /solve` takes a puzzle, either as the body itself or as JSON like the
+//! `--serve-stdio` requests (`{"puzzle": "...", "algorithm": "astar"}`), and
+//! answers with the same document as `solve --output json`: the solution,
+//! each move's positions and events, and the search's stats. Failures come
+//! back as `{"error": "..."}`. Requests are answered one at a time, in the
+//! order they arrive, so each search gets `--timeout` to run, or 30 seconds
+//! without one.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use crate::cli::Matches;
+use crate::json::Json;
+use crate::{limits, log, output, serve, Error, Result};
+
+/// The biggest request body taken, in bytes
+const MAX_BODY: usize = 1 << 20;
+/// The most bytes taken for the request line and headers together
+const MAX_HEAD: u64 = 16 << 10;
+/// The most header lines taken
+const MAX_HEADERS: usize = 64;
+/// How long a client gets to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long to wait for the rest of a request that was turned down
+const LINGER: Duration = Duration::from_secs(1);
+/// How long a search can run without `--timeout`, so one hard puzzle can't
+/// hold up everyone else's
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A response: the status and the JSON to send
+type Response = (u16, Json);
+
+/// The reason phrase for a status code
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Content Too Large",
+        431 => "Request Header Fields Too Large",
+        422 => "Unprocessable Content",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+/// An error as a response, with the status that fits it
+fn failure(error: Error) -> Response {
+    let status = match error.exit_code() {
+        2 | 3 => 400,
+        4 => 422,
+        5 => 503,
+        _ => 500,
+    };
+    (
+        status,
+        Json::object([("error", format!("{:?}", error).into())]),
+    )
+}
+
+/// Solve the puzzle in a request body
+fn solve(body: &str) -> Result<Json> {
+    let request = match body.trim_start().starts_with('{') {
+        true => Json::parse(body)?,
+        false => Json::object([("puzzle", body.into())]),
+    };
+
+    let start = Instant::now();
+    let (moves, stats) = serve::solve(&request)?;
+    let elapsed = start.elapsed();
+    let puzzle = request.get("puzzle").and_then(Json::as_str).unwrap();
+    output::json(puzzle, &moves, &stats, elapsed)
+}
+
+/// Answer a request for the path with the method and body
+fn route(method: &str, path: &str, body: &str) -> Response {
+    match (method, path) {
+        ("POST", "/solve") => match solve(body) {
+            Ok(document) => (200, document),
+            Err(error) => failure(error),
+        },
+        (_, "/solve") => (405, Json::object([("error", "Use POST".into())])),
+        _ => (404, Json::object([("error", "Not found".into())])),
+    }
+}
+
+/// Read a request off the connection, as its method, path and body
+fn read(stream: &TcpStream) -> std::result::Result<(String, String, String), Response> {
+    let bad = |why: &str| (400, Json::object([("error", why.into())]));
+    let too_large = || (431, Json::object([("error", "Headers too large".into())]));
+    let mut reader = BufReader::new(stream);
+
+    // Every line of the head comes out of the same allowance
+    let mut allowance = MAX_HEAD;
+    let mut next_line = |line: &mut String| {
+        line.clear();
+        let read = (&mut reader)
+            .take(allowance)
+            .read_line(line)
+            .map_err(|_| bad("Unreadable"))?;
+        allowance -= read as u64;
+        match line.ends_with('\n') || read == 0 {
+            true => Ok(()),
+            false => Err(too_large()),
+        }
+    };
+
+    let mut line = String::new();
+    next_line(&mut line)?;
+    let mut words = line.split_whitespace();
+    let (Some(method), Some(path)) = (words.next(), words.next()) else {
+        return Err(bad("No request line"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut length = None;
+    for headers in 0.. {
+        next_line(&mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if headers == MAX_HEADERS {
+            return Err(too_large());
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| bad("Bad Content-Length"))?,
+                );
+            }
+        }
+    }
+
+    let length = match (length, method.as_str()) {
+        (Some(length), _) => length,
+        (None, "POST") => {
+            let error = Json::object([("error", "No Content-Length".into())]);
+            return Err((411, error));
+        }
+        (None, _) => 0,
+    };
+    if length > MAX_BODY {
+        return Err((413, Json::object([("error", "Too large".into())])));
+    }
+
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| bad("Body cut short"))?;
+    let body = String::from_utf8(body).map_err(|_| bad("Body isn't UTF-8"))?;
+
+    Ok((method, path, body))
+}
+
+/// Answer one connection
+fn handle(mut stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+    let (request, (status, json)) = match read(&stream) {
+        Ok((method, path, body)) => {
+            let response = route(&method, &path, &body);
+            (format!("{} {}", method, path), response)
+        }
+        Err(response) => ("-".to_string(), response),
+    };
+    log::narrate(1, || format!("{} {}", request, status));
+
+    let body = format!("{}\n", json);
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+
+    // Closing with some of the request unread would reset the connection
+    // before the client saw the response, so what's left is read first
+    stream.shutdown(Shutdown::Write)?;
+    stream.set_read_timeout(Some(LINGER))?;
+    let _ = std::io::copy(&mut (&stream).take(MAX_BODY as u64), &mut std::io::sink());
+    Ok(())
+}
+
+/// The `serve` command: answer requests on `--port` until stopped
+pub fn serve(matches: &Matches) -> Result<()> {
+    let port: u16 = matches
+        .value("port")
+        .unwrap_or("8080")
+        .parse()
+        .map_err(|_| Error::Usage("--port takes a port number".to_string()))?;
+    let host = matches.value("host").unwrap_or("127.0.0.1");
+    limits::default_timeout(SEARCH_TIMEOUT);
+
+    let listener = TcpListener::bind((host, port)).map_err(|e| Error::Io(e.kind()))?;
+    log::narrate(0, || format!("Listening on http://{}:{}", host, port));
+
+    // One client hanging up early shouldn't stop the server
+    for stream in listener.incoming().flatten() {
+        let _ = handle(stream);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+
+    /// Send a request to a server answering one connection, returning the
+    /// response
+    fn request(raw: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            super::handle(stream).unwrap();
+        });
+
+        let mut client = TcpStream::connect(address).unwrap();
+        client.write_all(raw.as_bytes()).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        drop(client);
+        server.join().unwrap();
+        response
+    }
+
+    #[test]
+    fn solve() {
+        let puzzle = " x\n.R.\n\n x\n.R.";
+        let response = request(&format!(
+            "POST /solve HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            puzzle.len(),
+            puzzle
+        ));
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.contains(r#"{"solution":["Up"],"steps":[{"move":"Up""#));
+
+        let json = r#"{"puzzle": "x\nR\n\nx\nP\nR", "algorithm": "astar"}"#;
+        let response = request(&format!(
+            "POST /solve HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            json.len(),
+            json
+        ));
+        assert!(response.starts_with("HTTP/1.1 422 "), "{}", response);
+        assert!(response.ends_with("\r\n\r\n{\"error\":\"NoSolution\"}\n"));
+
+        assert!(request("GET /solve HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 405 "));
+        assert!(request("POST /solve HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 411 "));
+    }
+
+    #[test]
+    fn limits() {
+        let deep = format!("{{\"a\":{}", "[".repeat(300_000));
+        let response = request(&format!(
+            "POST /solve HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            deep.len(),
+            deep
+        ));
+        assert!(
+            response.ends_with("{\"error\":\"InvalidJson\"}\n"),
+            "{}",
+            response
+        );
+
+        let long = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(20_000));
+        assert!(request(&long).starts_with("HTTP/1.1 431 "));
+        let many = format!("GET / HTTP/1.1\r\n{}\r\n", "X-A: b\r\n".repeat(100));
+        assert!(request(&many).starts_with("HTTP/1.1 431 "));
+    }
+}
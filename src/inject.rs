@@ -0,0 +1,156 @@
+//! Scripts that type a solution into the running game
+//!
+//! The script presses the key for each move in turn, after a few seconds to
+//! switch to the game's window.
+
+use std::time::Duration;
+
+use crate::cli::Matches;
+use crate::{animate, output, Dir, Error, Result};
+
+/// How long the script waits before the first key press
+const LEAD_IN: Duration = Duration::from_secs(3);
+
+/// The tool the script is written for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// A shell script running `xdotool`, for X11
+    Xdotool,
+    /// An AutoHotkey v2 script, for Windows
+    Ahk,
+}
+
+impl Format {
+    /// Parse a format name from the command line
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "xdotool" => Ok(Format::Xdotool),
+            "ahk" => Ok(Format::Ahk),
+            _ => Err(Error::UnknownFormat),
+        }
+    }
+}
+
+/// The key pressed for each move, in the order up, down, right, left
+#[derive(Debug, PartialEq)]
+pub struct Keys([String; 4]);
+
+impl Keys {
+    /// The arrow keys, unless `--keys` maps moves to others, like
+    /// `up=w,left=a,down=s,right=d`
+    pub fn parse_args(matches: &Matches) -> Result<Self> {
+        let mut keys = ["Up", "Down", "Right", "Left"].map(String::from);
+        let invalid = || Error::Usage("--keys takes MOVE=KEY,...".to_string());
+
+        for mapping in matches.value("keys").into_iter().flat_map(|k| k.split(',')) {
+            let (dir, key) = mapping.split_once('=').ok_or_else(invalid)?;
+            let i = match dir.trim().to_ascii_lowercase().as_str() {
+                "u" | "up" => 0,
+                "d" | "down" => 1,
+                "r" | "right" => 2,
+                "l" | "left" => 3,
+                _ => return Err(invalid()),
+            };
+            keys[i] = key.trim().to_string();
+        }
+
+        Ok(Keys(keys))
+    }
+
+    /// The key for a move
+    fn get(&self, dir: Dir) -> &str {
+        match dir {
+            Dir::Up => &self.0[0],
+            Dir::Down => &self.0[1],
+            Dir::Right => &self.0[2],
+            Dir::Left => &self.0[3],
+        }
+    }
+}
+
+/// Write a script pressing the keys for the moves, a delay apart
+pub fn script(format: Format, moves: &[Dir], keys: &Keys, delay: Duration) -> String {
+    let solution = output::compact(moves);
+
+    match format {
+        Format::Xdotool => {
+            let presses = moves.iter().map(|&d| keys.get(d)).collect::<Vec<_>>();
+            format!(
+                "#!/bin/sh\n\
+                 # Plays {}. Switch to the game within {} seconds.\n\
+                 sleep {}\n\
+                 xdotool key --delay {} {}\n",
+                solution,
+                LEAD_IN.as_secs(),
+                LEAD_IN.as_secs(),
+                delay.as_millis(),
+                presses.join(" ")
+            )
+        }
+        Format::Ahk => {
+            let mut out = format!(
+                "; Plays {}. Switch to the game within {} seconds.\nSleep {}\n",
+                solution,
+                LEAD_IN.as_secs(),
+                LEAD_IN.as_millis()
+            );
+            for (i, &dir) in moves.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(&format!("Sleep {}\n", delay.as_millis()));
+                }
+                out.push_str(&format!("Send \"{{{}}}\"\n", keys.get(dir)));
+            }
+            out
+        }
+    }
+}
+
+/// Write the script the command line asks for
+pub fn from_args(matches: &Matches, moves: &[Dir]) -> Result<String> {
+    let format = Format::parse(matches.value("format").unwrap_or("xdotool"))?;
+    let keys = Keys::parse_args(matches)?;
+    Ok(script(format, moves, &keys, animate::parse_delay(matches)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Format, Keys};
+    use crate::Dir::*;
+
+    #[test]
+    fn scripts() {
+        let keys = Keys(["w", "s", "d", "a"].map(String::from));
+        let moves = [Up, Up, Left];
+
+        assert_eq!(
+            "#!/bin/sh\n\
+             # Plays UUL. Switch to the game within 3 seconds.\n\
+             sleep 3\n\
+             xdotool key --delay 150 w w a\n",
+            super::script(Format::Xdotool, &moves, &keys, Duration::from_millis(150))
+        );
+        assert_eq!(
+            "; Plays UUL. Switch to the game within 3 seconds.\n\
+             Sleep 3000\n\
+             Send \"{w}\"\n\
+             Sleep 150\n\
+             Send \"{w}\"\n\
+             Sleep 150\n\
+             Send \"{a}\"\n",
+            super::script(Format::Ahk, &moves, &keys, Duration::from_millis(150))
+        );
+    }
+
+    #[test]
+    fn keys() {
+        let args = ["bin", "macro", "--keys", "up=w,l=a"].map(String::from);
+        let matches = crate::cli::parse(&args).unwrap();
+
+        assert_eq!(
+            Ok(Keys(["w", "Down", "Right", "a"].map(String::from))),
+            Keys::parse_args(&matches)
+        );
+    }
+}
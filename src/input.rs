@@ -0,0 +1,90 @@
+//! Where the puzzle text comes from
+
+use std::io::Read;
+use std::process::Command;
+
+use crate::cli::Matches;
+use crate::pack::Pack;
+use crate::{Error, Result};
+
+/// Commands that print the system clipboard, tried in order
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-out"],
+    &["xsel", "--clipboard", "--output"],
+    &["pbpaste"],
+    &["powershell.exe", "-NoProfile", "-Command", "Get-Clipboard"],
+];
+
+/// Read the puzzle from the system clipboard
+fn clipboard() -> Result<String> {
+    CLIPBOARD_COMMANDS
+        .iter()
+        .find_map(|cmd| {
+            let output = Command::new(cmd[0]).args(&cmd[1..]).output().ok()?;
+            output.status.success().then_some(output.stdout)
+        })
+        .ok_or(Error::NoClipboard)
+        .map(|out| String::from_utf8_lossy(&out).replace("\r\n", "\n"))
+}
+
+/// Commands that download a URL to stdout, tried in order
+const DOWNLOAD_COMMANDS: &[&[&str]] = &[&["curl", "-fsSL"], &["wget", "-qO-"]];
+
+/// Download the puzzle over HTTP(S)
+fn url(url: &str) -> Result<String> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(Error::DownloadFailed);
+    }
+
+    DOWNLOAD_COMMANDS
+        .iter()
+        .find_map(|cmd| {
+            let output = Command::new(cmd[0])
+                .args(&cmd[1..])
+                .arg(url)
+                .output()
+                .ok()?;
+            output.status.success().then_some(output.stdout)
+        })
+        .ok_or(Error::DownloadFailed)
+        .map(|out| String::from_utf8_lossy(&out).replace("\r\n", "\n"))
+}
+
+/// Read the puzzle from stdin
+fn stdin() -> Result<String> {
+    let mut input = String::new();
+
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|e| Error::Io(e.kind()))?;
+
+    Ok(input)
+}
+
+/// Read a puzzle or pack file
+pub fn file(path: &str) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| Error::Io(e.kind()))
+}
+
+/// Read the puzzle from the source selected on the command line, falling back
+/// to the given file and then stdin
+pub fn read(matches: &Matches, path: Option<&str>) -> Result<String> {
+    let input = if let Some(expr) = matches.value("expr") {
+        // Shells don't expand `\n` in plain quotes, so do it here
+        expr.replace("\\n", "\n")
+    } else if matches.flag("clipboard") {
+        clipboard()?
+    } else if let Some(address) = matches.value("url") {
+        url(address)?
+    } else if let Some(path) = path {
+        file(path)?
+    } else {
+        stdin()?
+    };
+
+    match matches.value("level") {
+        Some(id) => Ok(Pack::parse(&input)?.level(id)?.text.clone()),
+        None => Ok(input),
+    }
+}
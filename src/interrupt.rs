@@ -0,0 +1,132 @@
+//! Stopping a search with Ctrl-C without losing what it found
+//!
+//! While a search runs, Ctrl-C only asks it to stop. It then says how far it
+//! got and where it was heading, and with `--checkpoint FILE` saves that line
+//! as a game to pick up with `play --resume FILE`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::cli::Matches;
+use crate::verify::{self, Step};
+use crate::{output, save, Result, Stats};
+
+/// Whether Ctrl-C was pressed during the current search
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Where to save the line an interrupted search was following
+static CHECKPOINT: Mutex<Option<String>> = Mutex::new(None);
+
+#[cfg(unix)]
+mod sys {
+    pub const SIGINT: i32 = 2;
+    pub const SIG_DFL: usize = 0;
+
+    extern "C" {
+        pub fn signal(signum: i32, handler: usize) -> usize;
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn on_interrupt(_: i32) {
+    INTERRUPTED.store(true, Ordering::Relaxed);
+}
+
+/// Set where interrupted searches save their line, from `--checkpoint`
+pub fn configure(matches: &Matches) -> Result<()> {
+    *CHECKPOINT.lock().unwrap() = matches.value("checkpoint").map(String::from);
+    Ok(())
+}
+
+/// Catches Ctrl-C until dropped, after which it ends the process again
+pub struct Guard;
+
+impl Guard {
+    pub fn install() -> Self {
+        INTERRUPTED.store(false, Ordering::Relaxed);
+        #[cfg(unix)]
+        // SAFETY: the handler only stores to an atomic
+        unsafe {
+            sys::signal(sys::SIGINT, on_interrupt as extern "C" fn(i32) as usize);
+        }
+        Guard
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        // SAFETY: this puts back the default handling
+        unsafe {
+            sys::signal(sys::SIGINT, sys::SIG_DFL);
+        }
+    }
+}
+
+/// Whether the search should stop
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+/// Describe how far an interrupted search got
+fn summary(stats: &Stats, checkpoint: Option<&str>) -> String {
+    let mut lines = vec![format!(
+        "Interrupted after {} states, {} moves deep",
+        stats.paths, stats.depth
+    )];
+    if !stats.partial.is_empty() {
+        lines.push(format!(
+            "It was following {}",
+            output::compact(&stats.partial)
+        ));
+    }
+    if let Some(path) = checkpoint {
+        lines.push(format!(
+            "Saved that line to {}; pick it up with `play --resume {}`",
+            path, path
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// Say how far an interrupted search got, saving the line it was following
+/// if `--checkpoint` asks for it
+pub fn report(input: &str, stats: &Stats) -> Result<()> {
+    let checkpoint = CHECKPOINT.lock().unwrap().clone();
+
+    if let Some(path) = &checkpoint {
+        let ((b1, mut p1), (b2, mut p2)) = crate::parse_puzzle(input)?;
+        for &dir in &stats.partial {
+            if let Step::Moved(n1, n2) = verify::step(dir, (&b1, p1), (&b2, p2)) {
+                (p1, p2) = (n1, n2);
+            }
+        }
+        save::write(path, input, &stats.partial, (p1, p2))?;
+    }
+
+    eprintln!("{}", summary(stats, checkpoint.as_deref()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Dir, Stats};
+
+    #[test]
+    fn summary() {
+        let stats = Stats {
+            paths: 5000,
+            depth: 12,
+            partial: Dir::parse_moves("UURD").unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            "Interrupted after 5000 states, 12 moves deep\n\
+             It was following UURD\n\
+             Saved that line to cp.json; pick it up with `play --resume cp.json`",
+            super::summary(&stats, Some("cp.json"))
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! A minimal JSON value, for machine-readable input and output
+
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::{Error, Result};
+
+/// How deep arrays and objects can nest, so a document of nothing but `[`
+/// can't run the parser out of stack
+const MAX_DEPTH: usize = 128;
+
+/// A JSON value
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+    /// An optional value that's there: the value itself in JSON, but
+    /// `Some(...)` in RON, where `Null` is `None`
+    Present(Box<Json>),
+}
+
+impl Json {
+    /// Build an object from its fields
+    pub fn object<const N: usize>(fields: [(&str, Json); N]) -> Self {
+        Json::Object(
+            fields
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        )
+    }
+
+    /// Parse a JSON document
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut chars = input.chars().peekable();
+        let value = parse_value(&mut chars, 0)?;
+
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            None => Ok(value),
+            Some(_) => Err(Error::InvalidJson),
+        }
+    }
+
+    /// Look up a field of an object
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Look up a field of an object to change it
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Json> {
+        match self {
+            Json::Object(fields) => fields.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// The value as a string, if it is one
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The value as a number, if it is one
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Set a field of an object, replacing any already there
+    pub fn set(&mut self, key: &str, value: Json) {
+        if let Json::Object(fields) = self {
+            match fields.iter_mut().find(|(k, _)| k == key) {
+                Some((_, v)) => *v = value,
+                None => fields.push((key.to_string(), value)),
+            }
+        }
+    }
+
+    /// Write the value as RON instead, with objects as anonymous structs
+    pub fn to_ron(&self) -> String {
+        match self {
+            Json::Null => "None".to_string(),
+            Json::Present(value) => format!("Some({})", value.to_ron()),
+            Json::Array(items) => format!(
+                "[{}]",
+                items
+                    .iter()
+                    .map(Json::to_ron)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Json::Object(fields) => format!(
+                "({})",
+                fields
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k.replace('-', "_"), v.to_ron()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            value => value.to_string(),
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+/// Consume the expected literal, e.g. `true`
+fn expect(chars: &mut Peekable<Chars>, literal: &str) -> Result<()> {
+    literal
+        .chars()
+        .all(|c| chars.next() == Some(c))
+        .then_some(())
+        .ok_or(Error::InvalidJson)
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String> {
+    expect(chars, "\"")?;
+    let mut s = String::new();
+
+    loop {
+        match chars.next().ok_or(Error::InvalidJson)? {
+            '"' => return Ok(s),
+            '\\' => match chars.next().ok_or(Error::InvalidJson)? {
+                'n' => s.push('\n'),
+                'r' => s.push('\r'),
+                't' => s.push('\t'),
+                'b' => s.push('\u{8}'),
+                'f' => s.push('\u{c}'),
+                'u' => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    let code = u32::from_str_radix(&hex, 16).map_err(|_| Error::InvalidJson)?;
+                    s.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                c => s.push(c),
+            },
+            c => s.push(c),
+        }
+    }
+}
+
+/// Parse a value nested inside `depth` arrays and objects
+fn parse_value(chars: &mut Peekable<Chars>, depth: usize) -> Result<Json> {
+    skip_whitespace(chars);
+    if depth > MAX_DEPTH {
+        return Err(Error::InvalidJson);
+    }
+
+    match chars.peek().ok_or(Error::InvalidJson)? {
+        'n' => expect(chars, "null").map(|_| Json::Null),
+        't' => expect(chars, "true").map(|_| Json::Bool(true)),
+        'f' => expect(chars, "false").map(|_| Json::Bool(false)),
+        '"' => parse_string(chars).map(Json::String),
+        '[' => {
+            chars.next();
+            let mut items = Vec::new();
+
+            skip_whitespace(chars);
+            if chars.next_if_eq(&']').is_some() {
+                return Ok(Json::Array(items));
+            }
+
+            loop {
+                items.push(parse_value(chars, depth + 1)?);
+                skip_whitespace(chars);
+                match chars.next() {
+                    Some(',') => continue,
+                    Some(']') => return Ok(Json::Array(items)),
+                    _ => return Err(Error::InvalidJson),
+                }
+            }
+        }
+        '{' => {
+            chars.next();
+            let mut fields = Vec::new();
+
+            skip_whitespace(chars);
+            if chars.next_if_eq(&'}').is_some() {
+                return Ok(Json::Object(fields));
+            }
+
+            loop {
+                skip_whitespace(chars);
+                let key = parse_string(chars)?;
+                skip_whitespace(chars);
+                expect(chars, ":")?;
+                fields.push((key, parse_value(chars, depth + 1)?));
+                skip_whitespace(chars);
+                match chars.next() {
+                    Some(',') => continue,
+                    Some('}') => return Ok(Json::Object(fields)),
+                    _ => return Err(Error::InvalidJson),
+                }
+            }
+        }
+        _ => {
+            let mut number = String::new();
+            while let Some(c) =
+                chars.next_if(|c| matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9'))
+            {
+                number.push(c);
+            }
+            number
+                .parse()
+                .map(Json::Number)
+                .map_err(|_| Error::InvalidJson)
+        }
+    }
+}
+
+/// Write a string literal with JSON escaping
+fn write_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Json::Number(_) => f.write_str("null"),
+            Json::String(s) => write_str(f, s),
+            Json::Present(value) => write!(f, "{}", value),
+            Json::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(fields) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_str(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+macro_rules! from_number {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Json {
+                fn from(n: $t) -> Self {
+                    Json::Number(n as f64)
+                }
+            }
+        )*
+    };
+}
+
+from_number!(usize, isize, u32, u64, f64);
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(items: Vec<T>) -> Self {
+        Json::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Json;
+
+    #[test]
+    fn write() {
+        let value = Json::object([
+            ("name", "a \"b\"\n".into()),
+            ("sizes", vec![1usize, 2].into()),
+            ("ratio", 0.5.into()),
+            ("none", Json::Null),
+            ("some", Json::Present(Box::new(1usize.into()))),
+        ]);
+
+        assert_eq!(
+            r#"{"name":"a \"b\"\n","sizes":[1,2],"ratio":0.5,"none":null,"some":1}"#,
+            value.to_string()
+        );
+        assert_eq!(
+            r#"(name: "a \"b\"\n", sizes: [1, 2], ratio: 0.5, none: None, some: Some(1))"#,
+            value.to_ron()
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let input = r#" {"name": "a \"b\"\n", "sizes": [1, 2.5e1], "ok": true, "none": null} "#;
+
+        assert_eq!(
+            Ok(Json::object([
+                ("name", "a \"b\"\n".into()),
+                ("sizes", vec![1.0, 25.0].into()),
+                ("ok", true.into()),
+                ("none", Json::Null),
+            ])),
+            Json::parse(input)
+        );
+        assert!(Json::parse("[1,").is_err());
+        assert!(Json::parse("{} x").is_err());
+    }
+
+    #[test]
+    fn too_deep() {
+        let nested = |depth| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+        assert!(Json::parse(&nested(super::MAX_DEPTH)).is_ok());
+        assert_eq!(
+            Err(crate::Error::InvalidJson),
+            Json::parse(&nested(super::MAX_DEPTH + 2))
+        );
+        assert_eq!(
+            Err(crate::Error::InvalidJson),
+            Json::parse(&format!("{{\"a\":{}", "[".repeat(300_000)))
+        );
+    }
+}
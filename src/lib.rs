@@ -0,0 +1,1320 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicI8, Ordering};
+use std::time::Instant;
+
+use algorithm::Algorithm;
+use prune::Prune;
+
+mod algorithm;
+#[cfg(feature = "alloc-stats")]
+mod alloc;
+mod animate;
+mod bench;
+mod certificate;
+mod cli;
+mod compare;
+mod completions;
+mod config;
+mod dead;
+mod dedup;
+mod diff;
+mod edit;
+mod explain;
+mod export;
+mod fmt;
+pub mod fuzz;
+mod generate;
+#[cfg(feature = "gif")]
+mod gif;
+mod graph;
+mod hint;
+mod http;
+mod inject;
+mod input;
+mod interrupt;
+mod json;
+mod limits;
+mod log;
+mod metadata;
+mod metrics;
+mod migrate;
+mod minimize;
+mod output;
+mod pack;
+mod par;
+mod play;
+#[cfg(feature = "image")]
+mod png;
+#[cfg(feature = "profile")]
+mod profile;
+mod progress;
+mod prune;
+mod rate;
+mod redundant;
+mod render;
+mod replay;
+mod report;
+#[cfg(feature = "sqlite")]
+mod results;
+mod rle;
+mod rng;
+mod save;
+#[cfg(feature = "image")]
+mod screenshot;
+mod selftest;
+mod serve;
+mod session;
+mod svg;
+mod template;
+mod term;
+mod trace;
+mod transform;
+mod tui;
+mod unique;
+mod validate;
+mod variants;
+mod verify;
+mod watch;
+
+#[derive(PartialEq, Debug)]
+enum Error {
+    InputEmpty,
+    NoExit,
+    NoSolution,
+    NoPlayer,
+    InvalidRle,
+    #[cfg(feature = "image")]
+    InvalidImage,
+    NoClipboard,
+    DownloadFailed,
+    InvalidMoves,
+    InvalidPack,
+    NoSuchLevel,
+    InvalidTransform,
+    UnsupportedVersion,
+    UnknownFormat,
+    InvalidJson,
+    UnknownAlgorithm,
+    MissingSecondBoard,
+    TooManyBoards,
+    BoardTooLarge,
+    InvalidLimit,
+    InvalidTemplate,
+    InvalidConfig,
+    InvalidSave,
+    InvalidPar,
+    /// A certificate that can't be read, or doesn't prove what it claims
+    InvalidCertificate,
+    /// A `--trace-out` file that can't be read
+    InvalidTrace,
+    /// The solution takes more moves than the puzzle's par, with `--enforce-par`
+    MissedPar,
+    Usage(String),
+    Io(std::io::ErrorKind),
+    Timeout,
+    /// Ctrl-C stopped the search
+    Interrupted,
+    /// The puzzle has more than one shortest solution, or not the intended one
+    NotUnique,
+    /// The searches got a puzzle wrong, with `selftest`
+    SelfTestFailed,
+    /// The moves made so far already won or lost the puzzle
+    GameOver,
+    NotATerminal,
+    /// The results database refused a query, with what it said
+    #[cfg(feature = "sqlite")]
+    Database(String),
+}
+
+impl Error {
+    /// The process exit code for a command that failed with this error
+    ///
+    /// - 1: the input couldn't be read
+    /// - 2: the command line or config file was wrong
+    /// - 3: the puzzle couldn't be parsed
+    /// - 4: the puzzle has no solution
+    /// - 5: the search ran out of time
+    /// - 6: the solution missed par, with `--enforce-par`
+    /// - 7: the shortest solution isn't unique, or isn't the intended one
+    /// - 8: `selftest` caught a search getting a puzzle wrong
+    /// - 130: Ctrl-C stopped the search
+    fn exit_code(&self) -> i32 {
+        match self {
+            Error::NoClipboard | Error::DownloadFailed | Error::Io(_) | Error::NotATerminal => 1,
+            #[cfg(feature = "sqlite")]
+            Error::Database(_) => 1,
+            Error::Usage(_)
+            | Error::UnknownFormat
+            | Error::UnknownAlgorithm
+            | Error::InvalidLimit
+            | Error::InvalidTransform
+            | Error::InvalidMoves
+            | Error::InvalidConfig
+            | Error::GameOver => 2,
+            Error::InputEmpty
+            | Error::NoExit
+            | Error::NoPlayer
+            | Error::InvalidRle
+            | Error::InvalidPack
+            | Error::NoSuchLevel
+            | Error::UnsupportedVersion
+            | Error::InvalidJson
+            | Error::MissingSecondBoard
+            | Error::TooManyBoards
+            | Error::BoardTooLarge
+            | Error::InvalidTemplate
+            | Error::InvalidSave
+            | Error::InvalidPar
+            | Error::InvalidCertificate
+            | Error::InvalidTrace => 3,
+            #[cfg(feature = "image")]
+            Error::InvalidImage => 3,
+            Error::NoSolution => 4,
+            Error::Timeout => 5,
+            Error::MissedPar => 6,
+            Error::NotUnique => 7,
+            Error::SelfTestFailed => 8,
+            Error::Interrupted => 130,
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Clone, Copy, Debug)]
+enum Tile {
+    None,
+    Wall,
+    Teleport,
+    Pit,
+    Ice,
+    Exit,
+}
+
+impl Tile {
+    /// The character used for this tile in a board definition
+    fn to_char(self) -> char {
+        match self {
+            Tile::None => '.',
+            Tile::Wall => 'W',
+            Tile::Teleport => 'T',
+            Tile::Pit => 'P',
+            Tile::Ice => 'I',
+            Tile::Exit => 'x',
+        }
+    }
+}
+
+/// The edge of the board the exit sits on
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// Where the exit is: a side of the board, and the column or row along it
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Exit {
+    side: Side,
+    pos: usize,
+}
+
+impl Exit {
+    /// Parse the exit line at the top of a board definition
+    ///
+    /// An `x` marks an exit above that column. Exits on the other sides are
+    /// written as the side's arrow followed by the row or column, e.g. `<2`
+    /// for an exit left of the third row.
+    fn parse(line: &str) -> Result<Self> {
+        if let Some(pos) = line.find('x') {
+            return Ok(Self {
+                side: Side::Top,
+                pos,
+            });
+        }
+
+        let mut chars = line.trim().chars();
+        let side = match chars.next() {
+            Some('^') => Side::Top,
+            Some('v') => Side::Bottom,
+            Some('<') => Side::Left,
+            Some('>') => Side::Right,
+            _ => return Err(Error::NoExit),
+        };
+        let pos = chars.as_str().parse().map_err(|_| Error::NoExit)?;
+
+        Ok(Self { side, pos })
+    }
+
+    /// Write the exit line of a board definition
+    fn to_text(self) -> String {
+        match self.side {
+            Side::Top => format!("{}x", " ".repeat(self.pos)),
+            Side::Bottom => format!("v{}", self.pos),
+            Side::Left => format!("<{}", self.pos),
+            Side::Right => format!(">{}", self.pos),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Board {
+    tiles: Vec<Vec<Tile>>,
+    exit: Exit,
+}
+
+impl Board {
+    /// Parse a board definition from the given string
+    fn parse(input: &str) -> Result<Self> {
+        let mut lines = input.lines();
+
+        let exit = Exit::parse(lines.next().ok_or(Error::InputEmpty)?)?;
+
+        let tiles = lines
+            .map(|l| {
+                l.chars()
+                    .map(|c| match c {
+                        'T' => Tile::Teleport,
+                        'P' => Tile::Pit,
+                        'I' => Tile::Ice,
+                        'W' => Tile::Wall,
+                        _ => Tile::None,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self { tiles, exit })
+    }
+
+    /// Write the board definition back out, with the player at the given position
+    fn to_text(&self, player: Player) -> String {
+        let mut lines = vec![self.exit.to_text()];
+
+        lines.extend(self.tiles.iter().enumerate().map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(x, tile)| {
+                    if (player.x, player.y) == (x as isize, y as isize) {
+                        'R'
+                    } else {
+                        tile.to_char()
+                    }
+                })
+                .collect::<String>()
+        }));
+
+        lines.join("\n")
+    }
+
+    /// Find the teleport that the teleport at the given position leads to
+    fn teleport_target(&self, from: Player) -> Option<Player> {
+        self.tiles.iter().enumerate().find_map(|(y, row)| {
+            row.iter().enumerate().find_map(|(x, tile)| {
+                let to = Player {
+                    x: x as isize,
+                    y: y as isize,
+                };
+                (matches!(tile, Tile::Teleport) && to != from).then_some(to)
+            })
+        })
+    }
+
+    /// The position just outside the board that leads through the exit
+    fn exit_cell(&self) -> Player {
+        let pos = self.exit.pos as isize;
+
+        match self.exit.side {
+            Side::Top => Player { x: pos, y: -1 },
+            Side::Bottom => Player {
+                x: pos,
+                y: self.tiles.len() as isize,
+            },
+            Side::Left => Player { x: -1, y: pos },
+            Side::Right => Player {
+                x: self.tiles.get(self.exit.pos).map_or(0, Vec::len) as isize,
+                y: pos,
+            },
+        }
+    }
+
+    /// Get the tile at the player's position
+    fn get_tile(&self, p: Player) -> Tile {
+        let tile = usize::try_from(p.y)
+            .ok()
+            .zip(usize::try_from(p.x).ok())
+            .and_then(|(y, x)| self.tiles.get(y)?.get(x));
+
+        match tile {
+            Some(tile) => *tile,
+            None if p == self.exit_cell() => Tile::Exit,
+            None => Tile::Wall,
+        }
+    }
+}
+
+/// The most moves a move sequence expands to, well past any solution the
+/// solver would find, so a mistyped count can't ask for gigabytes of moves
+const MAX_MOVES: usize = 1 << 16;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Dir {
+    Up,
+    Down,
+    Right,
+    Left,
+}
+
+impl Dir {
+    /// The letter for this move in a move string
+    fn to_char(self) -> char {
+        match self {
+            Dir::Up => 'U',
+            Dir::Down => 'D',
+            Dir::Right => 'R',
+            Dir::Left => 'L',
+        }
+    }
+
+    /// Parse a move sequence, either as letters (`UURDD`, or `2U R 2D` with
+    /// repeat counts) or a JSON array (`["Up", "Up"]`)
+    fn parse_moves(input: &str) -> Result<Vec<Self>> {
+        let input = input.trim();
+
+        if let Some(list) = input.strip_prefix('[').and_then(|i| i.strip_suffix(']')) {
+            list.split(',')
+                .map(|m| m.trim().trim_matches('"'))
+                .filter(|m| !m.is_empty())
+                .map(|m| match m.to_ascii_lowercase().as_str() {
+                    "u" | "up" => Ok(Dir::Up),
+                    "d" | "down" => Ok(Dir::Down),
+                    "r" | "right" => Ok(Dir::Right),
+                    "l" | "left" => Ok(Dir::Left),
+                    _ => Err(Error::InvalidMoves),
+                })
+                .collect()
+        } else {
+            let mut moves = Vec::new();
+            let mut count = String::new();
+
+            for c in input.chars().filter(|c| !c.is_whitespace() && *c != ',') {
+                if c.is_ascii_digit() {
+                    count.push(c);
+                    continue;
+                }
+
+                let dir = match c.to_ascii_uppercase() {
+                    'U' => Dir::Up,
+                    'D' => Dir::Down,
+                    'R' => Dir::Right,
+                    'L' => Dir::Left,
+                    _ => return Err(Error::InvalidMoves),
+                };
+                let n = if count.is_empty() {
+                    1
+                } else {
+                    count.parse().map_err(|_| Error::InvalidMoves)?
+                };
+                if n > MAX_MOVES - moves.len() {
+                    return Err(Error::InvalidMoves);
+                }
+                moves.extend(std::iter::repeat_n(dir, n));
+                count.clear();
+            }
+
+            if count.is_empty() {
+                Ok(moves)
+            } else {
+                Err(Error::InvalidMoves)
+            }
+        }
+    }
+}
+
+enum PlayerState {
+    Success,
+    Dead,
+    Just(Player),
+}
+
+impl From<(Dir, Player, Player, &Board)> for PlayerState {
+    fn from((dir, from, to, board): (Dir, Player, Player, &Board)) -> Self {
+        let tile = board.get_tile(to);
+
+        match tile {
+            Tile::None => Self::Just(to),
+            Tile::Wall => Self::Just(from),
+            Tile::Teleport => Self::Just(to.teleport(board)),
+            Tile::Ice => to.slide(dir, board),
+            Tile::Pit => Self::Dead,
+            Tile::Exit => Self::Success,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct Player {
+    x: isize,
+    y: isize,
+}
+
+impl Player {
+    /// Find the initial player position in the given board string
+    fn parse(input: &str) -> Result<Self> {
+        input
+            .lines()
+            .skip(1)
+            .enumerate()
+            .find_map(|(y, r)| {
+                r.char_indices().find_map(|(x, t)| {
+                    matches!(t, 'R').then(|| Self {
+                        x: x as isize,
+                        y: y as isize,
+                    })
+                })
+            })
+            .ok_or(Error::NoPlayer)
+    }
+
+    /// Hop one space in a direction
+    fn hop(&self, d: Dir) -> Player {
+        match d {
+            Dir::Up => Player {
+                x: self.x,
+                y: self.y - 1,
+            },
+            Dir::Down => Player {
+                x: self.x,
+                y: self.y + 1,
+            },
+            Dir::Right => Player {
+                x: self.x + 1,
+                y: self.y,
+            },
+            Dir::Left => Player {
+                x: self.x - 1,
+                y: self.y,
+            },
+        }
+    }
+
+    /// Slide on ice
+    fn slide(self, d: Dir, b: &Board) -> PlayerState {
+        PlayerState::from((d, self, self.hop(d), b))
+    }
+
+    /// Use a teleport, staying put on one without a partner
+    fn teleport(self, b: &Board) -> Self {
+        if !matches!(b.get_tile(self), Tile::Teleport) {
+            panic!("Tried to get teleport target of non-teleport tile");
+        }
+
+        b.teleport_target(self).unwrap_or(self)
+    }
+}
+
+/// Move the player in the given direction and find out what happens
+fn apply(d: Dir, b: &Board, p: Player) -> PlayerState {
+    let new_p = p.hop(d);
+
+    PlayerState::from((d, p, new_p, b))
+}
+
+#[derive(Clone, Copy)]
+enum TurnState {
+    Success,
+    Fail,
+    Ok,
+}
+
+#[derive(Clone)]
+struct Turn<'a> {
+    b1: &'a Board,
+    p1: Player,
+    b2: &'a Board,
+    p2: Player,
+    visited: HashSet<(Player, Player)>,
+    history: Vec<Dir>,
+    state: TurnState,
+}
+
+impl<'a, 'b> Turn<'b>
+where
+    'a: 'b,
+{
+    fn new(b1: &'a Board, p1: Player, b2: &'a Board, p2: Player) -> Self {
+        Self {
+            b1,
+            p1,
+            b2,
+            p2,
+            visited: HashSet::from([(p1, p2)]),
+            history: Vec::new(),
+            state: TurnState::Ok,
+        }
+    }
+
+    fn apply(mut self, dir: Dir) -> Self {
+        #[cfg(feature = "profile")]
+        let _span = profile::span("apply");
+        let from = (self.p1, self.p2);
+        let state1 = apply(dir, self.b1, self.p1);
+        let state2 = apply(dir, self.b2, self.p2);
+
+        self.history.push(dir);
+
+        let pruned = match (state1, state2) {
+            (PlayerState::Success, PlayerState::Success) => {
+                self.state = TurnState::Success;
+                None
+            }
+            (PlayerState::Just(p1), PlayerState::Just(p2)) => {
+                let vis_entry = (p1, p2);
+
+                if self.visited.contains(&vis_entry) {
+                    Some(Prune::Revisited(p1, p2))
+                } else {
+                    self.visited.insert(vis_entry);
+                    self.p1 = p1;
+                    self.p2 = p2;
+                    self.state = TurnState::Ok;
+                    None
+                }
+            }
+            (PlayerState::Dead, _) => Some(Prune::Fell(1)),
+            (_, PlayerState::Dead) => Some(Prune::Fell(2)),
+            (PlayerState::Success, _) => Some(Prune::ExitedAlone(1)),
+            (_, PlayerState::Success) => Some(Prune::ExitedAlone(2)),
+        };
+
+        let outcome = match (&pruned, self.state) {
+            (None, TurnState::Success) => trace::Outcome::Solved,
+            (None, _) => trace::Outcome::Moved,
+            (Some(Prune::Revisited(..)), _) => trace::Outcome::Revisited,
+            (Some(Prune::Fell(_)), _) => trace::Outcome::Fell,
+            (Some(Prune::ExitedAlone(_)), _) => trace::Outcome::ExitedAlone,
+        };
+        let to = match outcome {
+            trace::Outcome::Moved | trace::Outcome::Revisited => (self.p1, self.p2),
+            _ => from,
+        };
+        trace::record(from, dir, outcome, to);
+
+        if let Some(why) = pruned {
+            prune::pruned(&self.history, why);
+            self.state = TurnState::Fail;
+        }
+
+        log::narrate(3, || {
+            let outcome = match self.state {
+                TurnState::Success => "both exit",
+                TurnState::Fail => "dead end",
+                TurnState::Ok => "new position",
+            };
+            format!("    {:?}: {}", self.history, outcome)
+        });
+
+        self
+    }
+}
+
+/// How much gets printed besides the result
+///
+/// - `-1` (`--quiet`): nothing
+/// - `0`: headings only
+/// - `1` (`-v`): a line per search depth
+/// - `2` (`-vv`): every path being extended
+/// - `3` (`--trace`): every move tried, and what became of it
+static VERBOSITY: AtomicI8 = AtomicI8::new(0);
+
+/// The current verbosity level
+fn verbosity() -> i8 {
+    VERBOSITY.load(Ordering::Relaxed)
+}
+
+/// Counters from a search
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Stats {
+    /// Paths evaluated, over all depths
+    paths: usize,
+    /// The deepest level searched
+    depth: usize,
+    /// How many search states had a player on each cell of each board
+    visits: [HashMap<Player, usize>; 2],
+    /// The moves to the position being looked at when the search stopped short
+    partial: Vec<Dir>,
+    /// The most heap the search held at once, in bytes, with `alloc-stats`
+    peak_alloc: Option<usize>,
+}
+
+/// Figure out how to get the player to the exit, giving up at the deadline
+///
+/// `progress` hears about the stats so far as each depth is reached.
+fn solve(
+    turns: Vec<Turn>,
+    stats: &mut Stats,
+    deadline: Option<Instant>,
+    progress: &mut dyn FnMut(&Stats),
+) -> Result<Vec<Dir>> {
+    stats.paths += turns.len();
+
+    if turns.is_empty() {
+        log::narrate(1, || "Ran out of paths".to_string());
+        Err(Error::NoSolution)
+    } else if let Some(turn) = turns.iter().find(|t| matches!(t.state, TurnState::Success)) {
+        log::narrate(1, || "We've made it!".to_string());
+        Ok(turn.history.clone())
+    } else if interrupt::interrupted() || deadline.is_some_and(|d| Instant::now() >= d) {
+        let states: Vec<_> = turns.iter().map(|t| (t.p1, t.p2)).collect();
+        if let Some(i) = algorithm::closest((turns[0].b1, turns[0].b2), &states) {
+            stats.partial = turns[i].history.clone();
+        }
+
+        if interrupt::interrupted() {
+            log::narrate(1, || "Interrupted".to_string());
+            Err(Error::Interrupted)
+        } else {
+            log::narrate(1, || "Ran out of time".to_string());
+            Err(Error::Timeout)
+        }
+    } else {
+        log::narrate(1, || format!("Evaluating {} paths", turns.len()));
+        if log::wants(2) {
+            for turn in turns.iter().filter(|t| matches!(t.state, TurnState::Ok)) {
+                log::narrate(2, || {
+                    format!(
+                        "  {:?}: ({}, {}) and ({}, {})",
+                        turn.history, turn.p1.x, turn.p1.y, turn.p2.x, turn.p2.y
+                    )
+                });
+            }
+        }
+
+        for turn in turns.iter().filter(|t| matches!(t.state, TurnState::Ok)) {
+            *stats.visits[0].entry(turn.p1).or_default() += 1;
+            *stats.visits[1].entry(turn.p2).or_default() += 1;
+        }
+
+        stats.depth += 1;
+        progress(stats);
+        solve(
+            turns
+                .into_iter()
+                .filter(|t| matches!(t.state, TurnState::Ok))
+                .flat_map(|turn| {
+                    algorithm::order().into_iter().map(move |dir| {
+                        #[cfg(feature = "profile")]
+                        let copying = profile::span("clone");
+                        let turn = turn.clone();
+                        #[cfg(feature = "profile")]
+                        drop(copying);
+                        turn.apply(dir)
+                    })
+                })
+                .collect(),
+            stats,
+            deadline,
+            progress,
+        )
+    }
+}
+
+/// Whether the line separates two boards: blank, or a `---` rule
+fn is_separator(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty() || (line.len() >= 3 && line.chars().all(|c| c == '-'))
+}
+
+/// Split a puzzle into the definitions of its two boards
+fn split_boards(input: &str) -> Result<[String; 2]> {
+    let mut boards: Vec<Vec<&str>> = vec![vec![]];
+
+    for line in input.lines() {
+        if !is_separator(line) {
+            boards.last_mut().unwrap().push(line);
+        } else if !boards.last().unwrap().is_empty() {
+            boards.push(vec![]);
+        }
+    }
+
+    boards.retain(|b| !b.is_empty());
+
+    match boards.as_slice() {
+        [] => Err(Error::InputEmpty),
+        [_] => Err(Error::MissingSecondBoard),
+        [b1, b2] => Ok([b1.join("\n"), b2.join("\n")]),
+        _ => Err(Error::TooManyBoards),
+    }
+}
+
+/// Parse both boards of a puzzle along with their starting player positions
+fn parse_puzzle(input: &str) -> Result<((Board, Player), (Board, Player))> {
+    let (metadata, input) = metadata::split(input);
+    migrate::version(&metadata)?;
+    let [input1, input2] = split_boards(&rle::expand(input)?)?;
+
+    Ok((
+        (Board::parse(&input1)?, Player::parse(&input1)?),
+        (Board::parse(&input2)?, Player::parse(&input2)?),
+    ))
+}
+
+/// Figure out the shortest path to get the player to the exit, and what it took
+fn search(input: &str) -> Result<(Vec<Dir>, Stats)> {
+    search_with(input, algorithm::configured())
+}
+
+/// Figure out a path to get the player to the exit with the given algorithm,
+/// and what it took
+fn search_with(input: &str, algorithm: Algorithm) -> Result<(Vec<Dir>, Stats)> {
+    #[cfg(feature = "profile")]
+    let parsing = profile::span("parse");
+    let ((b1, p1), (b2, p2)) = parse_puzzle(input)?;
+    limits::check(&b1)?;
+    limits::check(&b2)?;
+    #[cfg(feature = "profile")]
+    drop(parsing);
+
+    let mut stats = Stats::default();
+    #[cfg(feature = "alloc-stats")]
+    alloc::reset();
+    let mut bar = progress::Bar::start();
+    let mut progress = |stats: &Stats| bar.update(stats);
+    let guard = interrupt::Guard::install();
+    #[cfg(feature = "profile")]
+    let searching = profile::span(algorithm.name());
+    let moves = match algorithm {
+        Algorithm::Bfs => solve(
+            vec![Turn::new(&b1, p1, &b2, p2)],
+            &mut stats,
+            limits::deadline(),
+            &mut progress,
+        ),
+        algorithm => algorithm::search(
+            algorithm,
+            (&b1, p1),
+            (&b2, p2),
+            &mut stats,
+            limits::deadline(),
+            &mut progress,
+        ),
+    };
+    #[cfg(feature = "profile")]
+    drop(searching);
+    drop(guard);
+    drop(bar);
+    trace::flush()?;
+    log::flush()?;
+    #[cfg(feature = "alloc-stats")]
+    {
+        stats.peak_alloc = Some(alloc::peak());
+    }
+
+    if moves == Err(Error::Interrupted) {
+        interrupt::report(input, &stats)?;
+    }
+    Ok((moves?, stats))
+}
+
+/// Figure out the shortest path to get the player to the exit
+fn solve_puzzle(input: &str) -> Result<Vec<Dir>> {
+    search(input).map(|(moves, _)| moves)
+}
+
+/// Write the result back to the puzzle file with `--write`, or print it
+fn write_or_print(matches: &cli::Matches, path: Option<&str>, output: &str) -> Result<()> {
+    match path.filter(|_| matches.flag("write")) {
+        Some(path) => std::fs::write(path, output).map_err(|e| Error::Io(e.kind())),
+        None => {
+            print!("{}", output);
+            Ok(())
+        }
+    }
+}
+
+/// Solve the puzzle and print the solution the way the flags ask for
+fn solve_command(matches: &cli::Matches, input: &str) -> Result<()> {
+    if matches.flag("check") {
+        print!("{}", validate::check(input)?);
+        return Ok(());
+    }
+
+    let format = output::Format::parse(matches.value("output").unwrap_or("text"))?;
+    if format == output::Format::Json {
+        // Stdout carries the document, so the search has to keep quiet
+        VERBOSITY.store(-1, Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "profile")]
+    let profiler = matches.value("profile").map(profile::Profiler::start);
+    let start = Instant::now();
+    let result = {
+        #[cfg(feature = "profile")]
+        let _span = profile::span("solve");
+        search(input)
+    };
+    let elapsed = start.elapsed();
+    #[cfg(feature = "profile")]
+    if let Some(profiler) = profiler {
+        profiler.finish()?;
+    }
+    if let Some(path) = matches.value("metrics-out") {
+        metrics::write(path, input, &result, elapsed)?;
+    }
+    #[cfg(feature = "sqlite")]
+    results::record(matches, input, &result, elapsed)?;
+    if let (Err(Error::NoSolution), Some(path)) = (&result, matches.value("certificate")) {
+        std::fs::write(path, certificate::write(input)?).map_err(|e| Error::Io(e.kind()))?;
+    }
+    let (directions, stats) = result?;
+
+    match format {
+        output::Format::Text => {
+            if matches.flag("animate") {
+                animate::animate(input, &directions, animate::parse_delay(matches)?)?;
+            }
+            if verbosity() >= 0 {
+                println!("SOLUTION:");
+            }
+            if matches.flag("explain") {
+                print!("{}", explain::explain(input, &directions)?);
+            } else {
+                for &dir in &directions {
+                    println!("{:?}", dir);
+                }
+            }
+            if matches.flag("heatmap") {
+                let ((b1, _), (b2, _)) = parse_puzzle(input)?;
+                print!("\n{}", render::heatmap([&b1, &b2], &stats.visits, "visits"));
+            }
+            // On stderr, so the feature doesn't change what a solve prints
+            #[cfg(feature = "alloc-stats")]
+            if let (Some(peak), true) = (stats.peak_alloc, verbosity() >= 0) {
+                eprintln!("Peak memory: {}", alloc::bytes(peak));
+            }
+        }
+        output::Format::Compact => println!("{}", output::compact(&directions)),
+        output::Format::Rle => println!("{}", output::rle(&directions)),
+        output::Format::Json => {
+            println!("{}", output::json(input, &directions, &stats, elapsed)?)
+        }
+    }
+
+    if let Some(par) = par::Par::check(input, directions.len())? {
+        if format == output::Format::Text && verbosity() >= 0 {
+            println!("\n{}", par);
+        }
+        if matches.flag("enforce-par") && par.missed() {
+            return Err(Error::MissedPar);
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the parsed command
+fn run(matches: &cli::Matches) -> Result<()> {
+    limits::configure(matches)?;
+    algorithm::configure(matches)?;
+    rng::configure(matches)?;
+    render::configure(matches)?;
+
+    let level = if matches.flag("quiet") {
+        -1
+    } else if matches.flag("trace") {
+        3
+    } else {
+        matches.count("verbose").min(2) as i8
+    };
+    VERBOSITY.store(level, Ordering::Relaxed);
+    log::configure(matches)?;
+    trace::configure(matches)?;
+    prune::configure(matches)?;
+    interrupt::configure(matches)?;
+
+    match matches.command.name {
+        "completions" => {
+            print!("{}", completions::script(matches.arg(0).unwrap())?);
+            return Ok(());
+        }
+        "bench" => {
+            print!("{}", bench::bench(matches)?);
+            return Ok(());
+        }
+        "edit" => return edit::run(matches.arg(0).unwrap(), matches),
+        "gen" => {
+            print!("{}", generate::from_args(matches)?);
+            return Ok(());
+        }
+        "trace" => return trace::run(matches.arg(0).unwrap(), matches.arg(1).unwrap()),
+        "rate" => {
+            print!("{}", rate::rate(&matches.args)?);
+            return Ok(());
+        }
+        #[cfg(feature = "sqlite")]
+        "stats" => {
+            print!("{}", results::stats(matches)?);
+            return Ok(());
+        }
+        "dedup" => {
+            print!("{}", dedup::dedup(matches.arg(0).unwrap())?);
+            return Ok(());
+        }
+        "compare" => {
+            print!("{}", compare::compare(matches)?);
+            return Ok(());
+        }
+        "serve" => return http::serve(matches),
+        "selftest" => {
+            let (report, agreed) = selftest::selftest(matches)?;
+            print!("{}", report);
+            return match agreed {
+                true => Ok(()),
+                false => Err(Error::SelfTestFailed),
+            };
+        }
+        "diff" => {
+            let old = input::file(matches.arg(0).unwrap())?;
+            let new = input::file(matches.arg(1).unwrap())?;
+            print!("{}", diff::diff(&old, &new)?);
+            return Ok(());
+        }
+        "pack" if matches.arg(0) == Some("build") => {
+            let pack = pack::build(matches.arg(1).unwrap())?;
+            match matches.value("out") {
+                Some(path) => std::fs::write(path, pack).map_err(|e| Error::Io(e.kind()))?,
+                None => print!("{}", pack),
+            }
+            return Ok(());
+        }
+        "pack" => {
+            let pack = pack::Pack::parse(&input::file(matches.arg(1).unwrap())?)?;
+            match (matches.arg(0).unwrap(), matches.arg(2)) {
+                ("list", _) => pack::list(&pack),
+                ("extract", Some(id)) => println!("{}", pack.level(id)?.text),
+                _ => {
+                    return Err(Error::Usage(
+                        "expected list, extract <LEVEL> or build".to_string(),
+                    ))
+                }
+            }
+            return Ok(());
+        }
+        #[cfg(feature = "image")]
+        "screenshot" => {
+            let sprites = matches.value("sprites").unwrap_or("sprites");
+            let screenshots: Vec<&str> = matches.args.iter().map(String::as_str).collect();
+            println!("{}", screenshot::read_boards(&screenshots, sprites)?);
+            return Ok(());
+        }
+        "solve" if matches.flag("watch") => {
+            let path = matches
+                .arg(0)
+                .ok_or(Error::Usage("--watch needs a FILE".to_string()))?;
+
+            return watch::watch(path, |input| {
+                for problem in validate::validate(input).unwrap_or_default() {
+                    println!("{}", problem);
+                }
+                if let Err(err) = solve_command(matches, input) {
+                    println!("Couldn't {}: {:?}", matches.command.failure, err);
+                }
+            });
+        }
+        "solve" if matches.flag("serve-stdio") => {
+            // Stdout carries the protocol, so the search has to keep quiet
+            VERBOSITY.store(-1, Ordering::Relaxed);
+            return serve::serve();
+        }
+        "play" | "tui" | "replay"
+            if matches.arg(0).is_none()
+                && !["expr", "clipboard", "url"].iter().any(|f| matches.flag(f)) =>
+        {
+            // Stdin is where the moves come from
+            return Err(Error::Usage(format!(
+                "{} needs a FILE",
+                matches.command.name
+            )));
+        }
+        _ => {}
+    }
+
+    let path = match matches.command.name {
+        "verify" | "gif" | "check-certificate" => matches.arg(1),
+        _ => matches.arg(0),
+    };
+    let input = input::read(matches, path)?;
+
+    match matches.command.name {
+        "play" => play::play(&input, matches)?,
+        "tui" => tui::run(&input)?,
+        #[cfg(feature = "gif")]
+        "gif" => {
+            let moves = solve_puzzle(&input)?;
+            let gif = gif::animate(&input, &moves, animate::parse_delay(matches)?)?;
+            std::fs::write(matches.arg(0).unwrap(), gif).map_err(|e| Error::Io(e.kind()))?;
+        }
+        "replay" => {
+            let moves = match matches.value("moves") {
+                Some(moves) => Dir::parse_moves(moves)?,
+                None => solve_puzzle(&input)?,
+            };
+            replay::run(&input, moves)?;
+        }
+        "validate" => {
+            let problems = validate::validate(&input)?;
+            if problems.is_empty() {
+                println!("Puzzle looks good");
+            }
+            for problem in problems {
+                println!("{}", problem);
+            }
+        }
+        "render" if matches.value("format") == Some("svg") => {
+            let ((b1, p1), (b2, p2)) = parse_puzzle(&input)?;
+            let moves = match solve_puzzle(&input) {
+                Err(Error::NoSolution) => Vec::new(),
+                moves => moves?,
+            };
+            print!("{}", svg::puzzle((&b1, p1), (&b2, p2), &moves));
+        }
+        "render" if matches.value("format") == Some("box") => {
+            let ((b1, p1), (b2, p2)) = parse_puzzle(&input)?;
+            print!("{}", render::pretty((&b1, p1), (&b2, p2)));
+        }
+        "render" if matches.value("format").is_some_and(|f| f != "text") => {
+            return Err(Error::UnknownFormat)
+        }
+        "render" if matches.flag("reach") => {
+            let ((b1, p1), (b2, p2)) = parse_puzzle(&input)?;
+            print!("{}", render::reachability((&b1, p1), (&b2, p2)));
+        }
+        "render" if matches.flag("path") => {
+            let ((b1, p1), (b2, p2)) = parse_puzzle(&input)?;
+            let moves = solve_puzzle(&input)?;
+            print!("{}", render::solution((&b1, p1), (&b2, p2), &moves));
+        }
+        "render" => {
+            let ((b1, p1), (b2, p2)) = parse_puzzle(&input)?;
+            print!("{}", render::puzzle((&b1, p1), (&b2, p2)));
+        }
+        "macro" => print!("{}", inject::from_args(matches, &solve_puzzle(&input)?)?),
+        "verify" => {
+            let moves = Dir::parse_moves(matches.arg(0).unwrap())?;
+            println!("{}", verify::verify(&input, &moves)?);
+        }
+        "fmt" if matches.flag("rle") => {
+            write_or_print(matches, path, &format!("{}\n", rle::compress(&input)?))?
+        }
+        "fmt" => write_or_print(matches, path, &fmt::format(&input)?)?,
+        "migrate" => write_or_print(matches, path, &migrate::migrate(&input)?)?,
+        "minimize" => {
+            let keep = minimize::Keep::parse(matches.value("keep").unwrap_or("length"))?;
+            write_or_print(matches, path, &minimize::minimize(&input, keep)?)?
+        }
+        "variants" => {
+            let count = match matches.value("count") {
+                Some(n) => n
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or(Error::Usage("--count takes a positive number".to_string()))?,
+                None => 10,
+            };
+            print!(
+                "{}",
+                variants::variants(&input, count, &mut rng::Rng::new())?
+            );
+        }
+        "transform" => {
+            let (metadata, _) = metadata::split(&input);
+            let (mut board1, mut board2) = parse_puzzle(&input)?;
+
+            for t in transform::Transform::parse_args(matches)? {
+                board1 = t.apply(&board1.0, board1.1)?;
+                board2 = t.apply(&board2.0, board2.1)?;
+            }
+
+            print!("{}", fmt::write(metadata, [&board1, &board2]));
+        }
+        "check-certificate" => {
+            let finding = certificate::check(&input::file(matches.arg(0).unwrap())?, &input)?;
+            println!("{}", finding);
+            if !finding.holds() {
+                return Err(Error::InvalidCertificate);
+            }
+        }
+        "unique" => {
+            let (report, unique) = unique::check(&input, matches.value("intended"))?;
+            print!("{}", report);
+            if !unique {
+                return Err(Error::NotUnique);
+            }
+        }
+        "dead" => print!("{}", dead::report(&input, matches.flag("overlay"))?),
+        "redundant" => print!("{}", redundant::report(&input)?),
+        "hash" => println!("{}", dedup::hash(&input)?),
+        "graph" => {
+            let format = graph::Format::parse(matches.value("format").unwrap_or("dot"))?;
+            print!("{}", graph::Graph::of(&input)?.write(format));
+        }
+        "export" => {
+            let format = export::Format::parse(matches.value("format").unwrap_or("json"))?;
+            println!("{}", export::export(&input, format)?);
+        }
+        "hint" => {
+            let position = hint::Position::parse_args(matches)?;
+            let count = match matches.value("next") {
+                Some(n) => n
+                    .parse()
+                    .map_err(|_| Error::Usage("--next takes a number".to_string()))?,
+                None => 1,
+            };
+
+            let (moves, remaining) = hint::hint(&input, &position, count)?;
+            let moves = moves.iter().map(|d| format!("{:?}", d)).collect::<Vec<_>>();
+            println!("{} ({} to go)", moves.join(" "), remaining);
+        }
+        "render-template" => {
+            let overrides = template::parse_overrides(matches)?;
+            println!("{}", template::render(&input, &overrides)?);
+        }
+        _ => solve_command(matches, &input)?,
+    }
+
+    Ok(())
+}
+
+/// Run the command line, exiting with the error's code if it fails
+pub fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    let matches = match cli::parse(&args).and_then(|mut matches| {
+        config::apply(&mut matches)?;
+        Ok(matches)
+    }) {
+        Ok(matches) => matches,
+        Err(Error::Usage(message)) => {
+            println!("error: {}\n\nRun with --help for usage.", message);
+            std::process::exit(2);
+        }
+        Err(err) => {
+            println!("error: {:?}", err);
+            std::process::exit(err.exit_code());
+        }
+    };
+
+    if matches.flag("help") {
+        print!("{}", cli::help(&matches));
+        return;
+    }
+
+    let result = run(&matches);
+    // The log is buffered, and should have everything whatever happened
+    let flushed = log::flush();
+    if let Err(err) = result.and(flushed) {
+        println!("Couldn't {}: {:?}", matches.command.failure, err);
+        std::process::exit(err.exit_code());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dir::*;
+
+    #[test]
+    fn simple() {
+        let input = "
+ x
+...
+...
+.R.
+
+ x
+...
+...
+..R
+"
+        .trim_matches('\n');
+        assert_eq!(
+            Ok(vec![Up, Up, Right, Left, Up]),
+            super::solve_puzzle(input)
+        )
+    }
+
+    #[test]
+    fn ice() {
+        let input = "
+ x
+...
+.IW
+..R
+
+  x
+...
+.II
+..R
+"
+        .trim_matches('\n');
+
+        assert_eq!(
+            Ok(vec![Left, Left, Up, Right, Up, Up]),
+            super::solve_puzzle(input)
+        );
+    }
+
+    #[test]
+    fn separators() {
+        let input = "\n x\n.R.\r\n\n\n\n x\n.R.\n\n";
+        assert_eq!(Ok(vec![Up]), super::solve_puzzle(input));
+
+        let input = " x\n.R.\n---\n x\n.R.";
+        assert_eq!(Ok(vec![Up]), super::solve_puzzle(input));
+
+        assert_eq!(
+            Err(super::Error::MissingSecondBoard),
+            super::solve_puzzle(" x\n.R.\n")
+        );
+    }
+
+    #[test]
+    fn teleport_and_pit() {
+        let input = "
+  x
+...
+.I.
+.R.
+
+  x
+...
+TPT
+.R.
+"
+        .trim_matches('\n');
+
+        assert_eq!(
+            Ok(vec![Left, Up, Right, Up, Up]),
+            super::solve_puzzle(input)
+        );
+    }
+
+    #[test]
+    fn lone_teleport() {
+        // With nowhere to go, the teleport is just somewhere to stand
+        assert_eq!(Ok(vec![Left, Up]), super::solve_puzzle("x\n.R\n\nx\nTR"));
+    }
+
+    #[test]
+    fn exit_codes() {
+        use super::Error;
+
+        assert_eq!(
+            Err(4),
+            super::solve_puzzle("x\nR\n\nx\nP\nR").map_err(|e| e.exit_code())
+        );
+        assert_eq!(3, Error::MissingSecondBoard.exit_code());
+        assert_eq!(5, Error::Timeout.exit_code());
+    }
+}
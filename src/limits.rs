@@ -0,0 +1,116 @@
+//! Guards against boards too big to search
+//!
+//! The search space grows with the product of both boards' cell counts, so a
+//! board that's merely large can need more memory than any machine has. The
+//! solver refuses boards over these limits up front instead, and gives up on
+//! searches that run past `--timeout`.
+//!
+//! By default a board can have 1024 cells, which keeps the positions of two
+//! such boards to about a million, few enough to search in memory. The
+//! width and height limits of 32 are as far as a square board of that many
+//! cells goes, so a board that's too long one way gets told so, rather than
+//! having too many cells.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::cli::Matches;
+use crate::{Board, Error, Result};
+
+static MAX_WIDTH: AtomicUsize = AtomicUsize::new(32);
+static MAX_HEIGHT: AtomicUsize = AtomicUsize::new(32);
+static MAX_CELLS: AtomicUsize = AtomicUsize::new(1024);
+/// In milliseconds, with 0 for no timeout
+static TIMEOUT: AtomicU64 = AtomicU64::new(0);
+
+/// Override the limits from `--max-width`, `--max-height`, `--max-cells` and `--timeout`
+pub fn configure(matches: &Matches) -> Result<()> {
+    for (flag, limit) in [
+        ("max-width", &MAX_WIDTH),
+        ("max-height", &MAX_HEIGHT),
+        ("max-cells", &MAX_CELLS),
+    ] {
+        if let Some(value) = matches.value(flag) {
+            limit.store(
+                value.parse().map_err(|_| Error::InvalidLimit)?,
+                Ordering::Relaxed,
+            );
+        }
+    }
+
+    if let Some(value) = matches.value("timeout") {
+        let secs: f64 = value.parse().map_err(|_| Error::InvalidLimit)?;
+        if !(secs.is_finite() && secs > 0.0) {
+            return Err(Error::InvalidLimit);
+        }
+        TIMEOUT.store((secs * 1000.0).ceil() as u64, Ordering::Relaxed);
+    }
+
+    Ok(())
+}
+
+/// Hold every board to `cells` cells and every search to `timeout`, for
+/// callers without a command line
+pub fn restrict(cells: usize, timeout: Duration) {
+    MAX_CELLS.store(cells, Ordering::Relaxed);
+    TIMEOUT.store(timeout.as_millis() as u64, Ordering::Relaxed);
+}
+
+/// Give every search `timeout` to run, unless `--timeout` already says
+pub fn default_timeout(timeout: Duration) {
+    let _ = TIMEOUT.compare_exchange(
+        0,
+        timeout.as_millis() as u64,
+        Ordering::Relaxed,
+        Ordering::Relaxed,
+    );
+}
+
+/// When a search starting now has to give up, if ever
+pub fn deadline() -> Option<Instant> {
+    match TIMEOUT.load(Ordering::Relaxed) {
+        0 => None,
+        ms => Some(Instant::now() + Duration::from_millis(ms)),
+    }
+}
+
+/// Check that the board is small enough to search
+pub fn check(board: &Board) -> Result<()> {
+    let width = board.tiles.iter().map(Vec::len).max().unwrap_or(0);
+    let height = board.tiles.len();
+    let cells: usize = board.tiles.iter().map(Vec::len).sum();
+
+    fits(width, height, cells)
+}
+
+/// Check that a board of the given size would be small enough to search,
+/// before going to the trouble of making it
+pub fn check_size(width: usize, height: usize) -> Result<()> {
+    let cells = width.checked_mul(height).ok_or(Error::BoardTooLarge)?;
+
+    fits(width, height, cells)
+}
+
+fn fits(width: usize, height: usize, cells: usize) -> Result<()> {
+    if width > MAX_WIDTH.load(Ordering::Relaxed)
+        || height > MAX_HEIGHT.load(Ordering::Relaxed)
+        || cells > MAX_CELLS.load(Ordering::Relaxed)
+    {
+        Err(Error::BoardTooLarge)
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    #[test]
+    fn too_wide() {
+        let row = ".".repeat(33);
+        let input = format!(" x\n{}\nR\n\n x\nR", row);
+
+        assert_eq!(Err(Error::BoardTooLarge), crate::solve_puzzle(&input));
+    }
+}
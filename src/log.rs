@@ -0,0 +1,61 @@
+//! Narrating the search, to stdout as the verbosity allows and in full to
+//! `--log-file`
+//!
+//! The log file gets every line down to `--trace` detail whatever the
+//! verbosity, so a solve that went wrong under `--quiet` can still be picked
+//! apart afterwards.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::cli::Matches;
+use crate::{verbosity, Error, Result};
+
+/// The `--log-file`, if one was given, buffered since a trace-level search
+/// writes millions of lines
+static FILE: Mutex<Option<BufWriter<File>>> = Mutex::new(None);
+/// Whether [`FILE`] is open, checked without taking the lock
+static LOGGING: AtomicBool = AtomicBool::new(false);
+
+/// Open the file from `--log-file`, replacing anything already in it
+pub fn configure(matches: &Matches) -> Result<()> {
+    if let Some(path) = matches.value("log-file") {
+        let file = File::create(path).map_err(|e| Error::Io(e.kind()))?;
+        *FILE.lock().unwrap() = Some(BufWriter::new(file));
+        LOGGING.store(true, Ordering::Relaxed);
+    }
+
+    Ok(())
+}
+
+/// Whether anyone will see narration at this verbosity level, so it's worth
+/// putting together
+pub fn wants(level: i8) -> bool {
+    verbosity() >= level || LOGGING.load(Ordering::Relaxed)
+}
+
+/// Narrate a line at the given verbosity level
+pub fn narrate(level: i8, line: impl FnOnce() -> String) {
+    if !wants(level) {
+        return;
+    }
+
+    let line = line();
+    if verbosity() >= level {
+        println!("{}", line);
+    }
+    if let Some(file) = FILE.lock().unwrap().as_mut() {
+        // A log that can't be written shouldn't stop the search
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+/// Write out whatever of the log is still buffered
+pub fn flush() -> Result<()> {
+    if let Some(file) = FILE.lock().unwrap().as_mut() {
+        file.flush().map_err(|e| Error::Io(e.kind()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! Puzzle metadata
+//!
+//! A puzzle may start with `key: value` lines (title, author, par, ...) before
+//! the first board. Exit rows never contain a colon, so the two can't be
+//! confused.
+
+/// Metadata fields in the order they were written
+pub type Metadata = Vec<(String, String)>;
+
+/// Whether the line is a `key: value` metadata line
+fn is_field(line: &str) -> bool {
+    line.split_once(':').is_some_and(|(key, _)| {
+        let key = key.trim();
+        !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    })
+}
+
+/// Split the leading metadata off a puzzle, returning it along with the boards
+pub fn split(input: &str) -> (Metadata, &str) {
+    let mut metadata = Metadata::new();
+    let mut rest = input;
+
+    while let Some(line) = rest.lines().next() {
+        if is_field(line) {
+            let (key, value) = line.split_once(':').unwrap();
+            metadata.push((key.trim().to_string(), value.trim().to_string()));
+        } else if !line.trim().is_empty() || metadata.is_empty() {
+            break;
+        }
+
+        rest = rest[line.len()..].trim_start_matches('\r');
+        rest = rest.strip_prefix('\n').unwrap_or(rest);
+    }
+
+    (metadata, rest)
+}
+
+/// Look up a metadata field
+pub fn get<'a>(metadata: &'a Metadata, key: &str) -> Option<&'a str> {
+    metadata
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn split() {
+        let (metadata, rest) = super::split("title: Corridor\npar: 5\n\n  x\n.R.");
+
+        assert_eq!(Some("Corridor"), super::get(&metadata, "title"));
+        assert_eq!(Some("5"), super::get(&metadata, "par"));
+        assert_eq!("  x\n.R.", rest);
+
+        assert_eq!((vec![], " x\n.R."), super::split(" x\n.R."));
+    }
+}
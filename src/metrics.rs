@@ -0,0 +1,93 @@
+//! Solver performance data for `--metrics-out`, to track regressions across
+//! versions on a corpus of puzzles
+
+use std::time::Duration;
+
+use crate::algorithm;
+use crate::json::Json;
+use crate::{Dir, Result, Stats};
+
+/// A stable fingerprint of the puzzle text (64-bit FNV-1a), to line runs of
+/// the same puzzle up
+pub fn hash(input: &str) -> String {
+    let hash = input.bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// The most memory the process has held, in bytes, where the OS says
+fn peak_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Describe how the search went, whether or not it found a solution
+fn metrics(input: &str, result: &Result<(Vec<Dir>, Stats)>, elapsed: Duration) -> Json {
+    let (moves, stats) = match result {
+        Ok((moves, stats)) => (Some(moves.len()), Some(stats)),
+        Err(_) => (None, None),
+    };
+
+    Json::object([
+        ("version", env!("CARGO_PKG_VERSION").into()),
+        ("puzzle_hash", hash(input).into()),
+        ("algorithm", algorithm::configured().name().into()),
+        ("solved", result.is_ok().into()),
+        (
+            "error",
+            result.as_ref().err().map(|e| format!("{:?}", e)).into(),
+        ),
+        ("moves", moves.into()),
+        ("nodes", stats.map(|s| s.paths).into()),
+        ("depth", stats.map(|s| s.depth).into()),
+        ("elapsed_ms", (elapsed.as_secs_f64() * 1000.0).into()),
+        ("peak_memory_bytes", peak_memory().into()),
+        ("peak_alloc_bytes", stats.and_then(|s| s.peak_alloc).into()),
+    ])
+}
+
+/// Write the metrics for a search to the file
+pub fn write(
+    path: &str,
+    input: &str,
+    result: &Result<(Vec<Dir>, Stats)>,
+    elapsed: Duration,
+) -> Result<()> {
+    std::fs::write(path, format!("{}\n", metrics(input, result, elapsed)))
+        .map_err(|e| crate::Error::Io(e.kind()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::json::Json;
+    use crate::Error;
+
+    #[test]
+    fn metrics() {
+        let input = " x\nTRT\n\n x\n.R.";
+        let doc = super::metrics(input, &crate::search(input), Duration::ZERO);
+
+        assert_eq!(Some(&Json::from(true)), doc.get("solved"));
+        assert_eq!(Some(&Json::from(1usize)), doc.get("moves"));
+        assert_eq!(Some(&Json::from("bfs")), doc.get("algorithm"));
+        assert_eq!(
+            Some(&Json::from(super::hash(input))),
+            doc.get("puzzle_hash")
+        );
+
+        let doc = super::metrics(input, &Err(Error::Timeout), Duration::ZERO);
+        assert_eq!(Some(&Json::from("Timeout")), doc.get("error"));
+        assert_eq!(Some(&Json::Null), doc.get("nodes"));
+    }
+
+    #[test]
+    fn hash() {
+        assert_eq!("cbf29ce484222325", super::hash(""));
+        assert_eq!("af63dc4c8601ec8c", super::hash("a"));
+    }
+}
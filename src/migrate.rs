@@ -0,0 +1,93 @@
+//! Puzzle format versions
+//!
+//! Puzzles declare their format with a `version` metadata field, with files
+//! that have none being version 1. Each migration upgrades a puzzle by one
+//! version, so old level collections keep working as the format grows.
+//!
+//! - Version 1: the original format. Any character the parser didn't know was
+//!   read as an empty tile.
+//! - Version 2: adds metadata and exits on any side. Migrating spells out
+//!   the empty tiles that version 1 read from unknown characters as `.`.
+
+use crate::metadata::{self, Metadata};
+use crate::{Error, Result};
+
+/// The format version written by this version of the solver
+pub const CURRENT_VERSION: u32 = 2;
+
+/// Upgrades from each version to the next, starting at version 1
+const MIGRATIONS: &[fn(&str) -> Result<String>] = &[v1_to_v2];
+
+/// The format version a puzzle declares
+pub fn version(metadata: &Metadata) -> Result<u32> {
+    match metadata::get(metadata, "version") {
+        None => Ok(1),
+        Some(v) => match v.parse() {
+            Ok(v @ 1..=CURRENT_VERSION) => Ok(v),
+            _ => Err(Error::UnsupportedVersion),
+        },
+    }
+}
+
+/// Spell out the empty tiles that version 1 read from unknown characters
+fn v1_to_v2(input: &str) -> Result<String> {
+    let (mut metadata, boards) = metadata::split(input);
+    metadata.retain(|(key, _)| key != "version");
+    metadata.push(("version".to_string(), "2".to_string()));
+
+    let boards = crate::split_boards(&crate::rle::expand(boards)?)?
+        .iter()
+        .map(|board| {
+            let mut lines = board.lines();
+            let exit = lines.next().unwrap_or_default().to_string();
+
+            std::iter::once(exit)
+                .chain(lines.map(|row| {
+                    row.chars()
+                        .map(|c| if "RTPIW".contains(c) { c } else { '.' })
+                        .collect()
+                }))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    Ok(metadata
+        .iter()
+        .map(|(key, value)| format!("{}: {}\n", key, value))
+        .chain([boards])
+        .collect())
+}
+
+/// Upgrade a puzzle to the current format version
+pub fn migrate(input: &str) -> Result<String> {
+    let mut input = input.to_string();
+
+    for migration in &MIGRATIONS[version(&metadata::split(&input).0)? as usize - 1..] {
+        input = migration(&input)?;
+    }
+
+    crate::fmt::format(&input)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    #[test]
+    fn migrate() {
+        assert_eq!(
+            Ok("version: 2\n\n x\n.R.\n\n x\nR..\n".to_string()),
+            super::migrate(" x\n.Rs\n\n x\nR..")
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        assert_eq!(
+            Err(Error::UnsupportedVersion),
+            crate::solve_puzzle("version: 3\n x\n.R.\n\n x\n.R.")
+        );
+    }
+}
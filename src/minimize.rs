@@ -0,0 +1,182 @@
+//! Shrinking a puzzle to the least that still behaves the same
+//!
+//! Edge rows and columns are cut off and tiles turned to floor one at a time,
+//! keeping each change that leaves the shortest solution as long as it was
+//! (or, with `--keep solvable`, leaves the puzzle solvable at all), until no
+//! change does. What's left is small enough for a bug report or an example.
+
+use crate::algorithm::{self, Algorithm};
+use crate::metadata;
+use crate::{fmt, limits, Board, Error, Player, Result, Side, Stats, Tile};
+
+/// What a smaller puzzle has to keep
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Keep {
+    /// A shortest solution the same length
+    Length,
+    /// Some solution
+    Solvable,
+}
+
+impl Keep {
+    /// Parse `--keep`
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "length" => Ok(Keep::Length),
+            "solvable" => Ok(Keep::Solvable),
+            _ => Err(Error::Usage(
+                "--keep must be length or solvable".to_string(),
+            )),
+        }
+    }
+}
+
+type Boards = [(Board, Player); 2];
+
+/// The length of the shortest solution, if there is one
+fn shortest([(b1, p1), (b2, p2)]: &Boards) -> Option<usize> {
+    algorithm::search(
+        Algorithm::Astar,
+        (b1, *p1),
+        (b2, *p2),
+        &mut Stats::default(),
+        limits::deadline(),
+        &mut |_| {},
+    )
+    .ok()
+    .map(|moves| moves.len())
+}
+
+/// Cut off the row or column along a side of the board, unless the player,
+/// the exit or a teleport is on it
+fn crop((board, player): &(Board, Player), side: Side) -> Option<(Board, Player)> {
+    let (mut board, mut player) = (board.clone(), *player);
+    let (width, height) = (board.tiles[0].len(), board.tiles.len());
+    let exit = &mut board.exit;
+
+    match side {
+        Side::Top | Side::Bottom => {
+            let y = if side == Side::Top { 0 } else { height - 1 };
+            if height == 1 || player.y as usize == y {
+                return None;
+            }
+            if matches!(exit.side, Side::Left | Side::Right) {
+                if exit.pos == y {
+                    return None;
+                }
+                exit.pos -= usize::from(side == Side::Top);
+            }
+            let row = board.tiles.remove(y);
+            if row.iter().any(|t| matches!(t, Tile::Teleport)) {
+                return None;
+            }
+            player.y -= isize::from(side == Side::Top);
+        }
+        Side::Left | Side::Right => {
+            let x = if side == Side::Left { 0 } else { width - 1 };
+            if width == 1 || player.x as usize == x {
+                return None;
+            }
+            if matches!(exit.side, Side::Top | Side::Bottom) {
+                if exit.pos == x {
+                    return None;
+                }
+                exit.pos -= usize::from(side == Side::Left);
+            }
+            for row in &mut board.tiles {
+                if matches!(row.remove(x), Tile::Teleport) {
+                    return None;
+                }
+            }
+            player.x -= isize::from(side == Side::Left);
+        }
+    }
+
+    Some((board, player))
+}
+
+/// Every puzzle one step simpler than this one, biggest steps first
+fn simpler(boards: &Boards) -> Vec<Boards> {
+    let mut found = Vec::new();
+
+    for i in 0..2 {
+        let mut with = |changed: (Board, Player)| {
+            let mut next = boards.clone();
+            next[i] = changed;
+            found.push(next);
+        };
+
+        for side in [Side::Top, Side::Bottom, Side::Left, Side::Right] {
+            if let Some(cropped) = crop(&boards[i], side) {
+                with(cropped);
+            }
+        }
+
+        let (board, player) = &boards[i];
+        for (y, row) in board.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let mut board = board.clone();
+                match tile {
+                    Tile::None | Tile::Exit => continue,
+                    // A teleport can't be left without its partner
+                    Tile::Teleport => {
+                        for tile in board.tiles.iter_mut().flatten() {
+                            if matches!(tile, Tile::Teleport) {
+                                *tile = Tile::None;
+                            }
+                        }
+                    }
+                    _ => board.tiles[y][x] = Tile::None,
+                }
+                with((board, *player));
+            }
+        }
+    }
+
+    found
+}
+
+/// Shrink the puzzle as far as it goes while keeping what `keep` asks for
+pub fn minimize(input: &str, keep: Keep) -> Result<String> {
+    let (metadata, _) = metadata::split(input);
+    let (mut b1, mut b2) = crate::parse_puzzle(input)?;
+    for (board, _) in [&mut b1, &mut b2] {
+        limits::check(board)?;
+        // Short rows end in walls, as far as moving goes
+        let width = board.tiles.iter().map(Vec::len).max().unwrap_or(0);
+        for row in &mut board.tiles {
+            row.resize(width, Tile::Wall);
+        }
+    }
+    let mut boards = [b1, b2];
+    let length = shortest(&boards).ok_or(Error::NoSolution)?;
+
+    let keeps = |boards: &Boards| match (keep, shortest(boards)) {
+        (Keep::Length, found) => found == Some(length),
+        (Keep::Solvable, found) => found.is_some(),
+    };
+    while let Some(next) = simpler(&boards).into_iter().find(keeps) {
+        boards = next;
+    }
+
+    Ok(fmt::write(metadata, [&boards[0], &boards[1]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Keep;
+
+    #[test]
+    fn minimize() {
+        let input = "title: Roomy\n\n  x\n....\n.I.P\n.R..\nWWWW\n\n  x\n...\nTPT\n.R.";
+
+        assert_eq!(
+            Ok("title: Roomy\n\n x\nR.\n\n  x\n...\nTPT\n.R.\n".to_string()),
+            super::minimize(input, Keep::Length)
+        );
+        assert_eq!(
+            Ok(" x\nR.\n\nx\nR\n".to_string()),
+            super::minimize(" x\nI.\nR.\n\n x\n..\n.R", Keep::Solvable)
+        );
+    }
+}
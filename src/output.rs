@@ -0,0 +1,179 @@
+//! Solution output formats besides the plain listing
+
+use std::time::Duration;
+
+use crate::json::Json;
+use crate::verify::{self, End, Step, Walk};
+use crate::{Dir, Error, Result, Stats};
+
+/// How `solve` prints the solution
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    /// One letter per move, like `UURDD`
+    Compact,
+    /// Runs of the same move counted, like `2U R 2D`
+    Rle,
+    Json,
+}
+
+impl Format {
+    /// Parse a format name from the command line
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "text" => Ok(Format::Text),
+            "compact" => Ok(Format::Compact),
+            "rle" => Ok(Format::Rle),
+            "json" => Ok(Format::Json),
+            _ => Err(Error::UnknownFormat),
+        }
+    }
+}
+
+/// Lay rows of cells out in left-aligned columns
+pub fn table<const N: usize>(rows: &[[String; N]]) -> String {
+    let widths: Vec<usize> = (0..N)
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    rows.iter()
+        .map(|row| {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ");
+            format!("{}\n", line.trim_end())
+        })
+        .collect()
+}
+
+/// Write the moves as a string of letters
+pub fn compact(moves: &[Dir]) -> String {
+    moves.iter().map(|d| d.to_char()).collect()
+}
+
+/// Write the moves as letters, with repeated moves counted
+pub fn rle(moves: &[Dir]) -> String {
+    moves
+        .chunk_by(|a, b| a == b)
+        .map(|run| match run.len() {
+            1 => run[0].to_char().to_string(),
+            n => format!("{}{}", n, run[0].to_char()),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// What a move does to a player besides walking a tile, if anything
+fn event(walk: &Walk) -> Option<&'static str> {
+    match walk.end {
+        _ if walk.slid > 0 => Some("slide"),
+        End::Blocked => Some("blocked"),
+        End::Teleported => Some("teleport"),
+        End::Exited => Some("exit"),
+        End::Walked | End::Fell => None,
+    }
+}
+
+/// Replay the moves, recording where both players end up after each
+fn steps(input: &str, moves: &[Dir]) -> Result<Vec<Json>> {
+    let ((b1, mut p1), (b2, mut p2)) = crate::parse_puzzle(input)?;
+    let mut steps = Vec::new();
+
+    for &dir in moves {
+        let events = [(1usize, &b1, p1), (2, &b2, p2)]
+            .into_iter()
+            .filter_map(|(player, board, from)| {
+                event(&Walk::new(dir, board, from))
+                    .map(|kind| Json::object([("player", player.into()), ("kind", kind.into())]))
+            })
+            .collect::<Vec<_>>();
+
+        (p1, p2) = match verify::step(dir, (&b1, p1), (&b2, p2)) {
+            Step::Moved(p1, p2) => (p1, p2),
+            _ => (b1.exit_cell(), b2.exit_cell()),
+        };
+
+        steps.push(Json::object([
+            ("move", format!("{:?}", dir).into()),
+            ("positions", vec![p1, p2].into()),
+            ("events", events.into()),
+        ]));
+    }
+
+    Ok(steps)
+}
+
+/// Describe the puzzle's solution, and what it took to find, as a single JSON
+/// document
+pub fn json(input: &str, moves: &[Dir], stats: &Stats, elapsed: Duration) -> Result<Json> {
+    Ok(Json::object([
+        (
+            "solution",
+            moves
+                .iter()
+                .map(|d| format!("{:?}", d))
+                .collect::<Vec<_>>()
+                .into(),
+        ),
+        ("steps", steps(input, moves)?.into()),
+        (
+            "stats",
+            Json::object([
+                ("moves", moves.len().into()),
+                ("paths", stats.paths.into()),
+                ("depth", stats.depth.into()),
+                ("elapsed_ms", (elapsed.as_secs_f64() * 1000.0).into()),
+                ("peak_alloc_bytes", stats.peak_alloc.into()),
+            ]),
+        ),
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::json::Json;
+    use crate::Dir;
+
+    #[test]
+    fn move_strings() {
+        let moves = Dir::parse_moves("UURDDLUUU").unwrap();
+
+        assert_eq!("UURDDLUUU", super::compact(&moves));
+        assert_eq!("2U R 2D L 3U", super::rle(&moves));
+        assert_eq!(Ok(moves), Dir::parse_moves("2U R 2D L 3U"));
+        assert!(Dir::parse_moves("UU2").is_err());
+        assert!(Dir::parse_moves("999999999999U").is_err());
+        assert!(Dir::parse_moves(&"60000U ".repeat(2)).is_err());
+        assert_eq!(Ok(60000), Dir::parse_moves("60000U").map(|m| m.len()));
+    }
+
+    #[test]
+    fn json() {
+        let input = " x\nTRT\n\n x\n.R.";
+        let (moves, stats) = crate::search(input).unwrap();
+        let doc = super::json(input, &moves, &stats, Duration::ZERO).unwrap();
+
+        assert_eq!(Some(&Json::from(vec!["Up"])), doc.get("solution"));
+        assert_eq!(
+            concat!(
+                r#"[{"move":"Up","positions":[{"x":1,"y":-1},{"x":1,"y":-1}],"#,
+                r#""events":[{"player":1,"kind":"exit"},{"player":2,"kind":"exit"}]}]"#
+            ),
+            doc.get("steps").unwrap().to_string()
+        );
+        assert_eq!(
+            Some(&Json::from(1usize)),
+            doc.get("stats").and_then(|s| s.get("moves"))
+        );
+    }
+}
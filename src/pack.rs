@@ -0,0 +1,241 @@
+//! Level packs
+//!
+//! A pack bundles many puzzles into one file. It starts with the pack's own
+//! metadata, followed by each level introduced by an `=== <id>` line. The
+//! body of a level is a regular puzzle, including its own metadata:
+//!
+//! ```text
+//! name: Starter pack
+//!
+//! === 1
+//! title: Warm-up
+//!  x
+//! .R.
+//!
+//!  x
+//! R..
+//! ```
+//!
+//! `pack build` makes a pack out of a directory of puzzles, stamping each
+//! level with the length of its shortest solution, checked by replaying it,
+//! and a checksum of its boards, so whoever gets the pack has something to
+//! check their solver and their copy against.
+
+use std::path::Path;
+
+use crate::algorithm::Algorithm;
+use crate::metadata::{self, Metadata};
+use crate::verify::{self, Verdict};
+use crate::{bench, fmt, metrics, Error, Result};
+
+/// The line prefix introducing a level
+const LEVEL_MARKER: &str = "===";
+
+/// The level metadata `pack build` fills in
+const OPTIMAL: &str = "optimal";
+const CHECKSUM: &str = "checksum";
+
+/// A single level in a pack
+#[derive(Debug)]
+pub struct Level {
+    pub id: String,
+    pub text: String,
+}
+
+impl Level {
+    /// The level's own metadata
+    pub fn metadata(&self) -> Metadata {
+        metadata::split(&self.text).0
+    }
+}
+
+/// A parsed level pack
+#[derive(Debug)]
+pub struct Pack {
+    pub metadata: Metadata,
+    pub levels: Vec<Level>,
+}
+
+impl Pack {
+    /// Parse a pack file
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut header = String::new();
+        let mut levels: Vec<Level> = Vec::new();
+
+        for line in input.lines() {
+            if let Some(id) = line.strip_prefix(LEVEL_MARKER) {
+                let id = id.trim().to_string();
+
+                if id.is_empty() || levels.iter().any(|l| l.id == id) {
+                    return Err(Error::InvalidPack);
+                }
+
+                levels.push(Level {
+                    id,
+                    text: String::new(),
+                });
+            } else {
+                let text = levels.last_mut().map_or(&mut header, |l| &mut l.text);
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+
+        let (metadata, rest) = metadata::split(&header);
+        if !rest.trim().is_empty() {
+            return Err(Error::InvalidPack);
+        }
+
+        for level in &mut levels {
+            level.text = level.text.trim_matches('\n').to_string();
+        }
+
+        Ok(Self { metadata, levels })
+    }
+
+    /// Find a level by its id
+    pub fn level(&self, id: &str) -> Result<&Level> {
+        self.levels
+            .iter()
+            .find(|l| l.id == id)
+            .ok_or(Error::NoSuchLevel)
+    }
+}
+
+/// Print the pack's metadata and an index of its levels
+pub fn list(pack: &Pack) {
+    for (key, value) in &pack.metadata {
+        println!("{}: {}", key, value);
+    }
+
+    for level in &pack.levels {
+        let metadata = level.metadata();
+        match metadata::get(&metadata, "title") {
+            Some(title) => println!("{}\t{}", level.id, title),
+            None => println!("{}", level.id),
+        }
+    }
+}
+
+/// A level for the pack, with its optimal length and checksum in its metadata
+fn stamp(text: &str) -> Result<String> {
+    let (mut metadata, _) = metadata::split(text);
+    let (board1, board2) = crate::parse_puzzle(text)?;
+
+    let (moves, _) = crate::search_with(text, Algorithm::Bfs)?;
+    if verify::verify(text, &moves)? != (Verdict::Solved { moves: moves.len() }) {
+        return Err(Error::NoSolution);
+    }
+
+    metadata.retain(|(key, _)| key != OPTIMAL && key != CHECKSUM);
+    metadata.push((OPTIMAL.to_string(), moves.len().to_string()));
+    let boards = fmt::write(vec![], [&board1, &board2]);
+    metadata.push((CHECKSUM.to_string(), metrics::hash(&boards)));
+
+    Ok(fmt::write(metadata, [&board1, &board2]))
+}
+
+/// Bundle the puzzles in the directory into a pack, each level named after
+/// its file
+pub fn build(dir: &str) -> Result<String> {
+    let name = Path::new(dir)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut out = match name.is_empty() {
+        true => String::new(),
+        false => format!("name: {}\n\n", name),
+    };
+
+    for file in bench::puzzles(dir)? {
+        let path = Path::new(dir).join(&file);
+        let text = crate::input::file(&path.to_string_lossy())?;
+        let id = path
+            .file_stem()
+            .map_or(file.clone(), |s| s.to_string_lossy().into_owned());
+        out.push_str(&format!("{} {}\n{}\n", LEVEL_MARKER, id, stamp(&text)?));
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pack;
+    use crate::metadata;
+    use crate::Dir::*;
+    use crate::Error;
+
+    const PACK: &str = "name: Test pack
+
+=== 1
+title: First
+ x
+...
+...
+.R.
+
+ x
+...
+...
+..R
+
+=== 2
+ x
+.R.
+
+ x
+R..
+";
+
+    #[test]
+    fn parse() {
+        let pack = Pack::parse(PACK).unwrap();
+
+        assert_eq!(Some("Test pack"), metadata::get(&pack.metadata, "name"));
+        assert_eq!(2, pack.levels.len());
+        assert_eq!(
+            Some("First"),
+            metadata::get(&pack.level("1").unwrap().metadata(), "title")
+        );
+        assert_eq!(
+            Ok(vec![Up, Up, Right, Left, Up]),
+            crate::solve_puzzle(&pack.level("1").unwrap().text)
+        );
+        assert_eq!(Err(Error::NoSuchLevel), pack.level("3").map(|_| ()));
+    }
+
+    #[test]
+    fn build() {
+        let dir = std::env::temp_dir().join("hive-mind-solver-pack-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("1.txt"), "title: First\n x\n.R.\n\n x\nR..").unwrap();
+        std::fs::write(dir.join("2.txt"), "optimal: 9\n x\n.R.\n\n x\n.R.").unwrap();
+
+        let built = super::build(&dir.to_string_lossy()).unwrap();
+        assert_eq!(
+            "name: hive-mind-solver-pack-test\n\
+             \n\
+             === 1\n\
+             title: First\n\
+             checksum: b4ae5ddc13148585\n\
+             optimal: 3\n\
+             \n x\n.R.\n\n x\nR..\n\
+             \n\
+             === 2\n\
+             checksum: da6765a535d81a3d\n\
+             optimal: 1\n\
+             \n x\n.R.\n\n x\n.R.\n\n",
+            built
+        );
+        assert_eq!(2, Pack::parse(&built).unwrap().levels.len());
+    }
+
+    #[test]
+    fn duplicate_ids() {
+        assert_eq!(
+            Err(Error::InvalidPack),
+            Pack::parse("=== 1\n x\n.R.\n\n x\n.R.\n=== 1\n x\n.R.\n\n x\n.R.").map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,71 @@
+//! Comparing solutions to the par a puzzle's metadata declares
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::{metadata, Error, Result};
+
+/// How a solution measures up to par
+#[derive(Debug, PartialEq)]
+pub struct Par {
+    pub par: usize,
+    pub moves: usize,
+}
+
+impl Par {
+    /// Compare a solution's length to the puzzle's `par`, if it declares one
+    pub fn check(input: &str, moves: usize) -> Result<Option<Self>> {
+        let (metadata, _) = metadata::split(input);
+
+        metadata::get(&metadata, "par")
+            .map(|par| {
+                let par = par.parse().map_err(|_| Error::InvalidPar)?;
+                Ok(Par { par, moves })
+            })
+            .transpose()
+    }
+
+    /// Whether the solution takes more moves than par
+    pub fn missed(&self) -> bool {
+        self.moves > self.par
+    }
+}
+
+impl fmt::Display for Par {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let by = self.moves.abs_diff(self.par);
+        let plural = if by == 1 { "" } else { "s" };
+
+        match self.moves.cmp(&self.par) {
+            Ordering::Less => write!(f, "Beats par ({}) by {} move{}", self.par, by, plural),
+            Ordering::Equal => write!(f, "Matches par ({})", self.par),
+            Ordering::Greater => write!(f, "Misses par ({}) by {} move{}", self.par, by, plural),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Par;
+    use crate::Error;
+
+    #[test]
+    fn check() {
+        let input = "par: 5\n\n  x\n.R.";
+
+        assert_eq!(Ok(None), Par::check("  x\n.R.", 3));
+        assert_eq!(
+            "Beats par (5) by 1 move",
+            Par::check(input, 4).unwrap().unwrap().to_string()
+        );
+        assert_eq!(
+            "Matches par (5)",
+            Par::check(input, 5).unwrap().unwrap().to_string()
+        );
+        assert!(Par::check(input, 7).unwrap().unwrap().missed());
+        assert_eq!(
+            Err(Error::InvalidPar),
+            Par::check("par: lots\n\n  x\n.R.", 5)
+        );
+    }
+}
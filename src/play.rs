@@ -0,0 +1,150 @@
+//! Playing a puzzle by hand
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::cli::Matches;
+use crate::session::{self, Session};
+use crate::verify::{self, Step};
+use crate::{report, save, Dir, Error, Result};
+
+/// Ask for the next moves, returning `None` when the player quits
+fn prompt() -> Result<Option<String>> {
+    print!("Move (u/d/l/r, undo, redo, save FILE, q to quit): ");
+    std::io::stdout().flush().map_err(|e| Error::Io(e.kind()))?;
+
+    let mut line = String::new();
+    let read = std::io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| Error::Io(e.kind()))?;
+
+    Ok((read > 0 && line.trim() != "q").then_some(line))
+}
+
+/// Play the puzzle interactively, reading moves from stdin
+///
+/// Every position along the way is kept, including the ones before a restart,
+/// so any number of moves can be undone and redone. The players' positions
+/// are the whole state of a game, so that's all there is to restore.
+///
+/// A game picks up where the `resumed` moves leave off. Once it's solved, the
+/// moves that did it are compared against the shortest solution, and the
+/// comparison also written to `report` if given.
+fn game(input: &str, resumed: &[Dir], report: Option<&str>, session: &mut Session) -> Result<()> {
+    let ((b1, start1), (b2, start2)) = crate::parse_puzzle(input)?;
+    // Both players' positions, and the moves it took to get there
+    let mut history = vec![(start1, start2, Vec::new())];
+    let mut undone = Vec::new();
+
+    // Saves only ever hold moves that keep both players on their boards
+    for &dir in resumed {
+        let (p1, p2, mut moves) = history.last().unwrap().clone();
+        if let Step::Moved(n1, n2) = verify::step(dir, (&b1, p1), (&b2, p2)) {
+            moves.push(dir);
+            history.push((n1, n2, moves));
+        }
+    }
+
+    loop {
+        let (p1, p2, _) = *history.last().unwrap();
+        print!("{}", crate::render::puzzle((&b1, p1), (&b2, p2)));
+
+        let asked = Instant::now();
+        let Some(line) = prompt()? else {
+            return Ok(());
+        };
+        // The thinking goes into the first of the moves, the rest follow on
+        let mut took = asked.elapsed();
+
+        match line.trim() {
+            "undo" => {
+                match history.len() {
+                    1 => println!("Nothing to undo"),
+                    _ => undone.push(history.pop().unwrap()),
+                }
+                continue;
+            }
+            "redo" => {
+                match undone.pop() {
+                    Some(state) => history.push(state),
+                    None => println!("Nothing to redo"),
+                }
+                continue;
+            }
+            line => {
+                if let Some(path) = line.strip_prefix("save ") {
+                    let (_, _, moves) = history.last().unwrap();
+                    save::write(path.trim(), input, moves, (p1, p2))?;
+                    println!("Saved to {}", path.trim());
+                    continue;
+                }
+            }
+        }
+        let Ok(dirs) = Dir::parse_moves(&line) else {
+            println!("Moves are u, d, l and r");
+            continue;
+        };
+        undone.clear();
+
+        for dir in dirs {
+            let (p1, p2, mut moves) = history.last().unwrap().clone();
+            moves.push(dir);
+            session.moved(dir, std::mem::take(&mut took));
+
+            let (restart, died) = match verify::step(dir, (&b1, p1), (&b2, p2)) {
+                Step::Solved => {
+                    println!("Solved in {} moves!\n", moves.len());
+                    session.solved(moves.len());
+
+                    let comparison = report::compare(input, &moves)?;
+                    print!("{}", comparison);
+                    if let Some(path) = report {
+                        std::fs::write(path, comparison).map_err(|e| Error::Io(e.kind()))?;
+                    }
+                    return Ok(());
+                }
+                Step::Died(player) => (format!("Player {} fell into a pit!", player), true),
+                Step::ExitedAlone(player) => {
+                    (format!("Only player {} made it out!", player), false)
+                }
+                Step::Moved(n1, n2) => {
+                    history.push((n1, n2, moves));
+                    continue;
+                }
+            };
+
+            println!("{} Starting over (undo to go back).", restart);
+            session.failed(died);
+            history.push((start1, start2, Vec::new()));
+            break;
+        }
+    }
+}
+
+/// Play the puzzle after showing the personal best on it, then sum up the
+/// session and add it to the level's stats in `--stats-file`
+pub fn play(input: &str, matches: &Matches) -> Result<()> {
+    let resumed = match matches.value("resume") {
+        Some(path) => save::read(path, input)?,
+        None => Vec::new(),
+    };
+
+    let path = matches
+        .value("stats-file")
+        .map(PathBuf::from)
+        .or_else(session::default_path);
+    if let Some(path) = &path {
+        print!("{}", session::leaderboard(path, input)?.unwrap_or_default());
+    }
+
+    let mut session = Session::start();
+    game(input, &resumed, matches.value("report"), &mut session)?;
+
+    print!("\n{}", session.summary());
+    if let Some(path) = path {
+        print!("{}", session.save(&path, input)?);
+    }
+
+    Ok(())
+}
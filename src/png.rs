@@ -0,0 +1,414 @@
+//! Decoding PNG screenshots
+//!
+//! Only as much PNG as screenshots use: 8-bit grayscale, RGB, palette and
+//! their alpha variants, without interlacing. The pixel data is inflated by
+//! hand, since zlib is just stored, fixed and dynamic Huffman blocks, and the
+//! alpha channel is dropped.
+
+use crate::{Error, Result};
+
+/// The first bytes of every PNG file
+pub const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// The most pixels a screenshot can have, well past any board's, so a
+/// crafted header can't ask for gigabytes
+const MAX_PIXELS: usize = 1 << 24;
+
+/// The first length of each length code, from 257 on, and its extra bits
+const LENGTH_BASE: [usize; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u32; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// The first distance of each distance code, and its extra bits
+const DISTANCE_BASE: [usize; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u32; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// The order the code length code lengths of a dynamic block come in
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Compressed data, read a bit at a time from the low bit of each byte up
+struct Bits<'a> {
+    bytes: &'a [u8],
+    /// How many bits have been read
+    pos: usize,
+}
+
+impl Bits<'_> {
+    fn bit(&mut self) -> Result<u32> {
+        let byte = self.bytes.get(self.pos / 8).ok_or(Error::InvalidImage)?;
+        let bit = (byte >> (self.pos % 8)) & 1;
+        self.pos += 1;
+        Ok(bit as u32)
+    }
+
+    /// Read a number `n` bits long, low bit first
+    fn bits(&mut self, n: u32) -> Result<u32> {
+        (0..n).try_fold(0, |value, i| Ok(value | self.bit()? << i))
+    }
+
+    /// Skip to the start of the next byte
+    fn align(&mut self) {
+        self.pos = self.pos.div_ceil(8) * 8;
+    }
+}
+
+/// A canonical Huffman code, as how many codes there are of each length and
+/// the symbols in code order
+struct Huffman {
+    counts: [usize; 16],
+    symbols: Vec<usize>,
+}
+
+impl Huffman {
+    /// Build the code from each symbol's code length, 0 for unused
+    fn new(lengths: &[usize]) -> Self {
+        let mut counts = [0; 16];
+        for &len in lengths {
+            counts[len] += 1;
+        }
+        counts[0] = 0;
+
+        let mut symbols = Vec::new();
+        for len in 1..16 {
+            symbols.extend((0..lengths.len()).filter(|&s| lengths[s] == len));
+        }
+
+        Huffman { counts, symbols }
+    }
+
+    /// Read one symbol
+    fn decode(&self, bits: &mut Bits) -> Result<usize> {
+        // The first code of each length, and where its symbols start
+        let (mut code, mut first, mut index) = (0, 0, 0);
+        for &count in &self.counts[1..] {
+            code |= bits.bit()? as usize;
+            if code < first + count {
+                return Ok(self.symbols[index + code - first]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(Error::InvalidImage)
+    }
+}
+
+/// The codes of fixed Huffman blocks
+fn fixed() -> (Huffman, Huffman) {
+    let lengths: Vec<usize> = (0..288)
+        .map(|s| match s {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        })
+        .collect();
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+/// Read the codes at the start of a dynamic Huffman block
+fn dynamic(bits: &mut Bits) -> Result<(Huffman, Huffman)> {
+    let literals = bits.bits(5)? as usize + 257;
+    let distances = bits.bits(5)? as usize + 1;
+    let code_lengths = bits.bits(4)? as usize + 4;
+
+    let mut lengths = [0; 19];
+    for &i in &CODE_LENGTH_ORDER[..code_lengths] {
+        lengths[i] = bits.bits(3)? as usize;
+    }
+    let code = Huffman::new(&lengths);
+
+    let mut lengths = Vec::new();
+    while lengths.len() < literals + distances {
+        let (len, repeat) = match code.decode(bits)? {
+            len @ 0..=15 => (len, 1),
+            16 => (
+                *lengths.last().ok_or(Error::InvalidImage)?,
+                3 + bits.bits(2)?,
+            ),
+            17 => (0, 3 + bits.bits(3)?),
+            _ => (0, 11 + bits.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(len, repeat as usize));
+    }
+    if lengths.len() != literals + distances {
+        return Err(Error::InvalidImage);
+    }
+
+    Ok((
+        Huffman::new(&lengths[..literals]),
+        Huffman::new(&lengths[literals..]),
+    ))
+}
+
+/// Inflate zlib data, refusing to make more than `limit` bytes
+fn inflate(data: &[u8], limit: usize) -> Result<Vec<u8>> {
+    match data {
+        [cmf, flg, ..]
+            if cmf & 0x0f == 8 && (*cmf as u16 * 256 + *flg as u16).is_multiple_of(31) => {}
+        _ => return Err(Error::InvalidImage),
+    }
+    let mut bits = Bits {
+        bytes: &data[2..],
+        pos: 0,
+    };
+    let mut out: Vec<u8> = Vec::new();
+
+    loop {
+        let last = bits.bit()? == 1;
+        let (literals, distances) = match bits.bits(2)? {
+            0 => {
+                bits.align();
+                let len = bits.bits(16)? as usize;
+                if bits.bits(16)? as usize != !len & 0xffff || out.len() + len > limit {
+                    return Err(Error::InvalidImage);
+                }
+                let start = bits.pos / 8;
+                let stored = bits
+                    .bytes
+                    .get(start..start + len)
+                    .ok_or(Error::InvalidImage)?;
+                out.extend_from_slice(stored);
+                bits.pos += len * 8;
+                if last {
+                    return Ok(out);
+                }
+                continue;
+            }
+            1 => fixed(),
+            2 => dynamic(&mut bits)?,
+            _ => return Err(Error::InvalidImage),
+        };
+
+        loop {
+            let symbol = literals.decode(&mut bits)?;
+            if symbol == 256 {
+                break;
+            }
+            if symbol < 256 {
+                out.push(symbol as u8);
+            } else {
+                let i = symbol - 257;
+                let len = LENGTH_BASE.get(i).ok_or(Error::InvalidImage)?
+                    + bits.bits(LENGTH_EXTRA[i])? as usize;
+                let i = distances.decode(&mut bits)?;
+                let distance = DISTANCE_BASE.get(i).ok_or(Error::InvalidImage)?
+                    + bits.bits(DISTANCE_EXTRA[i])? as usize;
+                if distance > out.len() {
+                    return Err(Error::InvalidImage);
+                }
+                for _ in 0..len {
+                    out.push(out[out.len() - distance]);
+                }
+            }
+            if out.len() > limit {
+                return Err(Error::InvalidImage);
+            }
+        }
+
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+/// The Paeth predictor: whichever neighbour is closest to their gradient
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Decode a PNG image into its width, height and RGB pixels
+pub fn decode(bytes: &[u8]) -> Result<(usize, usize, Vec<u8>)> {
+    let mut rest = bytes.strip_prefix(SIGNATURE).ok_or(Error::InvalidImage)?;
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut data = Vec::new();
+
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+        let kind = &rest[4..8];
+        let body = rest
+            .get(8..len.checked_add(8).ok_or(Error::InvalidImage)?)
+            .ok_or(Error::InvalidImage)?;
+        match kind {
+            b"IHDR" => header = Some(body),
+            b"PLTE" => palette = body,
+            b"IDAT" => data.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        rest = rest.get(len + 12..).ok_or(Error::InvalidImage)?;
+    }
+
+    let header = header
+        .filter(|h| h.len() == 13)
+        .ok_or(Error::InvalidImage)?;
+    let width = u32::from_be_bytes(header[0..4].try_into().unwrap()) as usize;
+    let height = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+    let channels = match (header[8], header[9], header[12]) {
+        (8, 0, 0) => 1,
+        (8, 2, 0) => 3,
+        (8, 3, 0) => 1,
+        (8, 4, 0) => 2,
+        (8, 6, 0) => 4,
+        _ => return Err(Error::InvalidImage),
+    };
+    let pixels = width
+        .checked_mul(height)
+        .filter(|&n| n <= MAX_PIXELS)
+        .ok_or(Error::InvalidImage)?;
+    let stride = width * channels;
+
+    let raw = inflate(&data, height * (stride + 1))?;
+    if raw.len() != height * (stride + 1) {
+        return Err(Error::InvalidImage);
+    }
+
+    let mut rows: Vec<u8> = Vec::with_capacity(height * stride);
+    for (y, line) in raw.chunks(stride + 1).enumerate() {
+        for (x, &byte) in line[1..].iter().enumerate() {
+            let at = y * stride + x;
+            let a = if x >= channels {
+                rows[at - channels]
+            } else {
+                0
+            };
+            let b = if y > 0 { rows[at - stride] } else { 0 };
+            let c = if x >= channels && y > 0 {
+                rows[at - stride - channels]
+            } else {
+                0
+            };
+            let predicted = match line[0] {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(Error::InvalidImage),
+            };
+            rows.push(byte.wrapping_add(predicted));
+        }
+    }
+
+    let mut rgb = Vec::with_capacity(pixels * 3);
+    for pixel in rows.chunks(channels) {
+        match header[9] {
+            0 | 4 => rgb.extend([pixel[0]; 3]),
+            3 => rgb.extend_from_slice(
+                palette
+                    .get(pixel[0] as usize * 3..pixel[0] as usize * 3 + 3)
+                    .ok_or(Error::InvalidImage)?,
+            ),
+            _ => rgb.extend_from_slice(&pixel[..3]),
+        }
+    }
+
+    Ok((width, height, rgb))
+}
+
+#[cfg(test)]
+mod tests {
+    /// Hex to bytes, for compressed data made by zlib
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// A PNG of the given chunks
+    fn png(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut bytes = super::SIGNATURE.to_vec();
+        for (kind, body) in chunks {
+            bytes.extend((body.len() as u32).to_be_bytes());
+            bytes.extend(*kind);
+            bytes.extend(*body);
+            // Checksums aren't checked
+            bytes.extend([0; 4]);
+        }
+        bytes
+    }
+
+    fn header(width: u32, height: u32, color: u8) -> Vec<u8> {
+        let mut header = [width.to_be_bytes(), height.to_be_bytes()].concat();
+        header.extend([8, color, 0, 0, 0]);
+        header
+    }
+
+    #[test]
+    fn inflate() {
+        // A dynamic Huffman block
+        assert_eq!(
+            Ok(b"W.WWWxWx..WWR.W..WW.x..Wx.xWRx.Wx.R.xW.W".to_vec()),
+            super::inflate(
+                &hex("78da15c7310100000c83304788e98395cadf7a0544ade50d0e36a5a6335f78fc100c54"),
+                100
+            )
+        );
+        // Stored, and too long
+        assert_eq!(
+            Ok(b"abc".to_vec()),
+            super::inflate(&[0x78, 0x01, 1, 3, 0, 0xfc, 0xff, b'a', b'b', b'c'], 3)
+        );
+        assert!(super::inflate(&[0x78, 0x01, 1, 3, 0, 0xfc, 0xff, b'a', b'b', b'c'], 2).is_err());
+    }
+
+    #[test]
+    fn decode() {
+        // RGB rows filtered by their left and Paeth neighbours, in a fixed
+        // Huffman block
+        let data = hex("78da633c71e2848585454a4a0a4b56491d88e3e403005a6408ac");
+        let image = png(&[
+            (b"IHDR", &header(3, 2, 2)),
+            (b"IDAT", &data[..10]),
+            (b"IDAT", &data[10..]),
+            (b"IEND", &[]),
+        ]);
+        assert_eq!(
+            Ok((
+                3,
+                2,
+                vec![200, 200, 200, 0, 0, 0, 100, 100, 100, 50, 60, 70, 200, 200, 200, 0, 10, 20]
+            )),
+            super::decode(&image)
+        );
+
+        // A palette, stored
+        let image = png(&[
+            (b"IHDR", &header(2, 1, 3)),
+            (b"PLTE", &[1, 2, 3, 4, 5, 6]),
+            (b"IDAT", &[0x78, 0x01, 1, 3, 0, 0xfc, 0xff, 0, 1, 0]),
+            (b"IEND", &[]),
+        ]);
+        assert_eq!(Ok((2, 1, vec![4, 5, 6, 1, 2, 3])), super::decode(&image));
+
+        let huge = png(&[(b"IHDR", &header(u32::MAX, u32::MAX, 2)), (b"IEND", &[])]);
+        assert!(super::decode(&huge).is_err());
+        assert!(super::decode(b"P6\n1 1\n255\n...").is_err());
+    }
+}
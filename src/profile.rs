@@ -0,0 +1,226 @@
+//! Sampling where a solve spends its time, with the `profile` feature
+//!
+//! The solver marks out its stages with spans as it goes. While
+//! `--profile FILE` is on, a thread looks at which spans are open every
+//! millisecond and counts each stack it sees, and when the solve is done the
+//! counts are drawn as a flamegraph: one bar per stage, as wide as the share
+//! of samples it was in, stacked on whatever it was called from.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+/// How often the open spans get looked at
+const INTERVAL: Duration = Duration::from_millis(1);
+/// The width of the flamegraph, in pixels
+const WIDTH: f64 = 1200.0;
+/// The height of each bar
+const ROW: usize = 16;
+
+/// Whether anyone's sampling, so spans cost nothing otherwise
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// The spans open right now, outermost first
+static STACK: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+/// How many samples saw each stack of spans
+type Counts = BTreeMap<Vec<&'static str>, usize>;
+
+/// A stage of the solve, open until it's dropped
+pub struct Span(bool);
+
+/// Open a span with the name, if sampling's on
+pub fn span(name: &'static str) -> Span {
+    let enabled = ENABLED.load(Ordering::Relaxed);
+    if enabled {
+        STACK.lock().unwrap().push(name);
+    }
+    Span(enabled)
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if self.0 {
+            STACK.lock().unwrap().pop();
+        }
+    }
+}
+
+/// Sampling under way, to be drawn to a file
+pub struct Profiler {
+    path: String,
+    running: Arc<AtomicBool>,
+    sampler: JoinHandle<Counts>,
+}
+
+impl Profiler {
+    /// Start sampling, for a flamegraph to be written to the path
+    pub fn start(path: &str) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        ENABLED.store(true, Ordering::Relaxed);
+
+        let sampler = {
+            let running = Arc::clone(&running);
+            std::thread::spawn(move || {
+                let mut counts = Counts::new();
+                while running.load(Ordering::Relaxed) {
+                    let stack = STACK.lock().unwrap().clone();
+                    if !stack.is_empty() {
+                        *counts.entry(stack).or_default() += 1;
+                    }
+                    std::thread::sleep(INTERVAL);
+                }
+                counts
+            })
+        };
+
+        Profiler {
+            path: path.to_string(),
+            running,
+            sampler,
+        }
+    }
+
+    /// Stop sampling and write the flamegraph
+    pub fn finish(self) -> Result<()> {
+        self.running.store(false, Ordering::Relaxed);
+        ENABLED.store(false, Ordering::Relaxed);
+        let counts = self.sampler.join().unwrap_or_default();
+
+        std::fs::write(&self.path, flamegraph(&counts)).map_err(|e| Error::Io(e.kind()))
+    }
+}
+
+/// A span and everything sampled inside it
+#[derive(Default)]
+struct Node {
+    samples: usize,
+    children: BTreeMap<&'static str, Node>,
+}
+
+/// The stacks as a tree, with a root holding every sample
+fn tree(counts: &Counts) -> Node {
+    let mut root = Node::default();
+    for (stack, &n) in counts {
+        root.samples += n;
+        let mut node = &mut root;
+        for name in stack {
+            node = node.children.entry(name).or_default();
+            node.samples += n;
+        }
+    }
+    root
+}
+
+/// A warm color for the span, the same every time for the same name
+fn color(name: &str) -> String {
+    let hash = name.bytes().fold(0x811c9dc5u32, |h, b| {
+        (h ^ b as u32).wrapping_mul(0x01000193)
+    });
+    let [a, b, c, _] = hash.to_le_bytes().map(|x| x as f64 / 255.0);
+    format!(
+        "rgb({},{},{})",
+        205 + (50.0 * a) as u8,
+        (230.0 * b) as u8,
+        (55.0 * c) as u8
+    )
+}
+
+/// Draw the node's children side by side from `x`, a row above `depth`
+fn bars(out: &mut String, node: &Node, total: usize, x: f64, depth: usize, height: usize) {
+    let mut x = x;
+    for (name, child) in &node.children {
+        let width = child.samples as f64 / total as f64 * WIDTH;
+        let y = height - (depth + 1) * ROW;
+        let _ = writeln!(
+            out,
+            r#"  <g><title>{} ({} samples, {:.1}%)</title><rect x="{:.1}" y="{}" width="{:.1}" height="{}" fill="{}"/>"#,
+            name,
+            child.samples,
+            child.samples as f64 / total as f64 * 100.0,
+            x,
+            y,
+            width,
+            ROW - 1,
+            color(name)
+        );
+        // Only label bars with room for the name
+        if width > name.len() as f64 * 7.0 + 6.0 {
+            let _ = writeln!(
+                out,
+                r#"  <text x="{:.1}" y="{}">{}</text>"#,
+                x + 3.0,
+                y + ROW - 4,
+                name
+            );
+        }
+        out.push_str("  </g>\n");
+        bars(out, child, total, x, depth + 1, height);
+        x += width;
+    }
+}
+
+/// How many spans deep the tree goes
+fn depth(node: &Node) -> usize {
+    node.children
+        .values()
+        .map(|c| depth(c) + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// The samples as a flamegraph, outermost spans along the bottom
+fn flamegraph(counts: &Counts) -> String {
+    let root = tree(counts);
+    let height = (depth(&root) + 1) * ROW;
+
+    let mut out = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="monospace" font-size="11">"#,
+        WIDTH, height
+    );
+    out.push('\n');
+    if root.samples == 0 {
+        let _ = writeln!(
+            out,
+            r#"  <text x="3" y="{}">The solve was over before a sample was taken</text>"#,
+            ROW - 4
+        );
+    } else {
+        bars(&mut out, &root, root.samples, 0.0, 0, height);
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn flamegraph() {
+        let counts = super::Counts::from([
+            (vec!["solve", "bfs", "apply"], 2),
+            (vec!["solve", "bfs"], 1),
+            (vec!["solve", "parse"], 1),
+        ]);
+        let svg = super::flamegraph(&counts);
+
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="1200" height="64""#)
+        );
+        assert!(svg.contains(
+            r#"<title>solve (4 samples, 100.0%)</title><rect x="0.0" y="48" width="1200.0""#
+        ));
+        assert!(svg.contains(
+            r#"<title>bfs (3 samples, 75.0%)</title><rect x="0.0" y="32" width="900.0""#
+        ));
+        assert!(svg.contains(
+            r#"<title>parse (1 samples, 25.0%)</title><rect x="900.0" y="32" width="300.0""#
+        ));
+        assert!(svg.contains(
+            r#"<title>apply (2 samples, 50.0%)</title><rect x="0.0" y="16" width="600.0""#
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+//! A status line for long searches, so they don't look hung
+//!
+//! It's drawn on stderr, and only when that's a terminal and nothing else is
+//! being narrated, so piped output and `-v` logs stay clean.
+
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use crate::{verbosity, Stats};
+
+/// The shortest time between redraws
+const REDRAW: Duration = Duration::from_millis(100);
+
+/// Frames of the spinner at the start of the line
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// The status line, cleared away when dropped
+pub struct Bar {
+    start: Instant,
+    /// When the line was last drawn, if it has been
+    drawn: Option<Instant>,
+    frame: usize,
+    enabled: bool,
+}
+
+impl Bar {
+    /// Start timing a search
+    pub fn start() -> Self {
+        Bar {
+            start: Instant::now(),
+            drawn: None,
+            frame: 0,
+            enabled: verbosity() == 0 && std::io::stderr().is_terminal(),
+        }
+    }
+
+    /// Redraw the line with the latest stats, if it's been long enough
+    pub fn update(&mut self, stats: &Stats) {
+        if !self.enabled || self.drawn.is_some_and(|d| d.elapsed() < REDRAW) {
+            return;
+        }
+
+        self.frame = (self.frame + 1) % SPINNER.len();
+        let line = line(stats, self.start.elapsed());
+        #[cfg(feature = "alloc-stats")]
+        let line = format!("{} · {}", line, crate::alloc::bytes(crate::alloc::peak()));
+        let _ = write!(
+            std::io::stderr(),
+            "\r\x1b[K{} {}",
+            SPINNER[self.frame],
+            line
+        );
+        self.drawn = Some(Instant::now());
+    }
+}
+
+impl Drop for Bar {
+    fn drop(&mut self) {
+        if self.drawn.is_some() {
+            let _ = write!(std::io::stderr(), "\r\x1b[K");
+        }
+    }
+}
+
+/// Describe how far the search has got
+fn line(stats: &Stats, elapsed: Duration) -> String {
+    let secs = elapsed.as_secs_f64();
+    let rate = if secs > 0.0 {
+        (stats.paths as f64 / secs) as usize
+    } else {
+        0
+    };
+
+    format!(
+        "depth {} · {} states · {}/s · {:.1}s",
+        stats.depth, stats.paths, rate, secs
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::Stats;
+
+    #[test]
+    fn line() {
+        let stats = Stats {
+            paths: 3000,
+            depth: 12,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            "depth 12 · 3000 states · 1500/s · 2.0s",
+            super::line(&stats, Duration::from_secs(2))
+        );
+    }
+}
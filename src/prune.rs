@@ -0,0 +1,77 @@
+//! Saying why the search gave up on branches, with `--why-pruned K`
+//!
+//! Only the first K are described, since a search prunes far more branches
+//! than anyone could read. They're usually enough to see why a puzzle is hard,
+//! or why there's no solution at all.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::cli::Matches;
+use crate::{Dir, Error, Player, Result};
+
+/// How many more pruned branches to describe
+static LEFT: AtomicUsize = AtomicUsize::new(0);
+
+/// Why a branch of the search was dropped
+#[derive(Debug, PartialEq)]
+pub enum Prune {
+    /// The given player fell into a pit
+    Fell(usize),
+    /// Only the given player reached the exit
+    ExitedAlone(usize),
+    /// Both players ended up where an earlier move of the branch had them
+    Revisited(Player, Player),
+}
+
+impl fmt::Display for Prune {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Prune::Fell(player) => write!(f, "player {} falls into a pit", player),
+            Prune::ExitedAlone(player) => write!(f, "only player {} reaches the exit", player),
+            Prune::Revisited(p1, p2) => write!(
+                f,
+                "the players are back at ({}, {}) and ({}, {}), where they've already been",
+                p1.x, p1.y, p2.x, p2.y
+            ),
+        }
+    }
+}
+
+/// Set how many pruned branches to describe from `--why-pruned`
+pub fn configure(matches: &Matches) -> Result<()> {
+    if let Some(value) = matches.value("why-pruned") {
+        let count = value
+            .parse()
+            .map_err(|_| Error::Usage("--why-pruned takes a number".to_string()))?;
+        LEFT.store(count, Ordering::Relaxed);
+    }
+
+    Ok(())
+}
+
+/// Describe a dropped branch, if there are any descriptions left to give
+pub fn pruned(history: &[Dir], why: Prune) {
+    let left = LEFT.load(Ordering::Relaxed);
+    if left == 0 {
+        return;
+    }
+    LEFT.store(left - 1, Ordering::Relaxed);
+
+    println!("Pruned {}: {}", crate::output::compact(history), why);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Prune;
+    use crate::Player;
+
+    #[test]
+    fn describe() {
+        assert_eq!("player 2 falls into a pit", Prune::Fell(2).to_string());
+        assert_eq!(
+            "the players are back at (1, 2) and (0, 0), where they've already been",
+            Prune::Revisited(Player { x: 1, y: 2 }, Player { x: 0, y: 0 }).to_string()
+        );
+    }
+}
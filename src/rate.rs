@@ -0,0 +1,155 @@
+//! Scoring how hard puzzles are, to put a pack in order
+//!
+//! The score adds up things that can be measured over every position the
+//! puzzle can reach:
+//!
+//! - the shortest solution's length
+//! - twice the log2 of how many positions there are, for how lost a player
+//!   can get
+//! - twice the branching factor, the moves from a position that go
+//!   somewhere new without losing
+//! - three times the fatal moves next to the shortest solution: moves that
+//!   lose, or lead somewhere the puzzle can't be solved from
+//!
+//! The weights are rough, but puzzles with a higher score are harder.
+
+use crate::graph::{Edge, Graph};
+use crate::output;
+use crate::pack::Pack;
+use crate::{input, Result};
+
+/// What the score is made of
+#[derive(Debug, PartialEq)]
+pub struct Rating {
+    /// The shortest solution's length, if there is one
+    pub moves: Option<usize>,
+    /// How many positions can be reached
+    pub states: usize,
+    /// How many moves from a position go somewhere new without losing, on
+    /// average
+    pub branching: f64,
+    /// How many moves from positions on the shortest solution lose the game
+    pub fatal: usize,
+}
+
+impl Rating {
+    /// Measure the puzzle
+    pub fn of(input: &str) -> Result<Self> {
+        let graph = Graph::of(input)?;
+        let to_goal = graph.to_goal();
+
+        let onward = |(from, edges): (usize, &[Edge; 4])| {
+            edges
+                .iter()
+                .filter(|e| matches!(e, Edge::To(to) if *to != from) || **e == Edge::Solved)
+                .count()
+        };
+        let branching = graph.edges.iter().enumerate().map(onward).sum::<usize>() as f64
+            / graph.states.len() as f64;
+
+        // Follow the shortest solution, counting the moves off it that lose
+        let mut fatal = 0;
+        let mut state = Some(0).filter(|_| to_goal[0].is_some());
+        while let Some(at) = state {
+            let edges = &graph.edges[at];
+            fatal += edges
+                .iter()
+                .filter(|e| match e {
+                    Edge::To(to) => to_goal[*to].is_none(),
+                    Edge::Solved => false,
+                    Edge::Lost => true,
+                })
+                .count();
+            state = edges.iter().find_map(|e| match e {
+                Edge::To(to) if to_goal[*to] < to_goal[at] && to_goal[*to].is_some() => Some(*to),
+                _ => None,
+            });
+        }
+
+        Ok(Rating {
+            moves: to_goal[0],
+            states: graph.states.len(),
+            branching,
+            fatal,
+        })
+    }
+
+    /// The difficulty score, if the puzzle can be solved at all
+    pub fn score(&self) -> Option<f64> {
+        self.moves.map(|moves| {
+            moves as f64
+                + 2.0 * (self.states as f64).log2()
+                + 2.0 * self.branching
+                + 3.0 * self.fatal as f64
+        })
+    }
+}
+
+/// The puzzles in a file: every level of a pack, or the puzzle itself
+pub fn puzzles(path: &str) -> Result<Vec<(String, String)>> {
+    let text = input::file(path)?;
+    match Pack::parse(&text) {
+        Ok(pack) if !pack.levels.is_empty() => Ok(pack
+            .levels
+            .into_iter()
+            .map(|level| (format!("{}:{}", path, level.id), level.text))
+            .collect()),
+        _ => Ok(vec![(path.to_string(), text)]),
+    }
+}
+
+/// Lay the ratings out in a table, easiest first and unsolvable last
+fn table(mut ratings: Vec<(String, Rating)>) -> String {
+    ratings.sort_by(|(_, a), (_, b)| match (a.score(), b.score()) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    });
+
+    let mut rows =
+        vec![["puzzle", "score", "moves", "states", "branching", "fatal"].map(String::from)];
+    for (puzzle, rating) in ratings {
+        let or_dash = |n: Option<String>| n.unwrap_or_else(|| "-".to_string());
+        rows.push([
+            puzzle,
+            or_dash(rating.score().map(|s| format!("{:.1}", s))),
+            or_dash(rating.moves.map(|m| m.to_string())),
+            rating.states.to_string(),
+            format!("{:.2}", rating.branching),
+            rating.fatal.to_string(),
+        ]);
+    }
+
+    output::table(&rows)
+}
+
+/// Rate every puzzle in the files, and every level of the packs among them
+pub fn rate(paths: &[String]) -> Result<String> {
+    let mut ratings = Vec::new();
+    for path in paths {
+        for (name, puzzle) in puzzles(path)? {
+            ratings.push((name, Rating::of(&puzzle)?));
+        }
+    }
+
+    Ok(table(ratings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rating;
+
+    #[test]
+    fn rate() {
+        let easy = Rating::of("  x\n...\n.R.\n\n  x\n...\n.R.").unwrap();
+        let hard = Rating::of("  x\nP..\n.R.\n\n x\n..\n.R").unwrap();
+
+        assert_eq!(Some(3), easy.moves);
+        assert_eq!(0, easy.fatal);
+        assert_eq!(Some(3), hard.moves);
+        assert!(hard.fatal > 0);
+        assert!(hard.score() > easy.score());
+
+        let table = super::table(vec![("hard".to_string(), hard), ("easy".to_string(), easy)]);
+        assert!(table.lines().nth(1).unwrap().starts_with("easy"));
+    }
+}
@@ -0,0 +1,188 @@
+//! Tiles a puzzle could do without
+//!
+//! A tile earns its place by being in the way of a solution: stood on, slid
+//! over, teleported through or walked into. Tiles that no solution touches,
+//! or that only longer solutions do, are clutter, and so is any tile the
+//! shortest solution stays as short without. Each tile is turned to floor in
+//! turn to find out, the pair of teleports going together.
+
+use std::collections::HashSet;
+
+use crate::graph::{Edge, Graph, DIRS};
+use crate::{animate, limits, Board, Dir, Error, Player, Result, Tile};
+
+/// How far the solutions get to a tile
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Use {
+    Never,
+    LongerOnly,
+    Shortest,
+}
+
+/// The name of a tile in the report
+fn name(tile: Tile) -> &'static str {
+    match tile {
+        Tile::Wall => "wall",
+        Tile::Teleport => "teleport",
+        Tile::Pit => "pit",
+        Tile::Ice => "ice",
+        Tile::None | Tile::Exit => "floor",
+    }
+}
+
+/// The cells of the board a move looks at: those the player passes through,
+/// and the wall that stops it
+fn touched(dir: Dir, board: &Board, from: Player) -> Vec<Player> {
+    let mut cells = animate::path(dir, board, from);
+    let last = *cells.last().unwrap();
+    if cells.len() == 1 || matches!(board.get_tile(last), Tile::Ice) {
+        cells.push(last.hop(dir));
+    }
+    cells
+}
+
+/// The cells on each board that solutions touch: every solution that doesn't
+/// stand still, and the shortest ones
+fn used(graph: &Graph, boards: [&Board; 2]) -> [[HashSet<Player>; 2]; 2] {
+    let to_goal = graph.to_goal();
+    let from_start: Vec<usize> = graph.paths().iter().map(Vec::len).collect();
+    let length = to_goal[0];
+    let mut used: [[HashSet<Player>; 2]; 2] = Default::default();
+
+    for (state, edges) in graph.edges.iter().enumerate() {
+        let Some(left) = to_goal[state] else {
+            continue;
+        };
+        let on_shortest = Some(from_start[state] + left) == length;
+
+        for (dir, edge) in DIRS.into_iter().zip(edges) {
+            let shortest = match *edge {
+                Edge::Solved => on_shortest && left == 1,
+                Edge::To(to) if to != state && to_goal[to].is_some() => {
+                    on_shortest && to_goal[to] == Some(left - 1)
+                }
+                _ => continue,
+            };
+            let (p1, p2) = graph.states[state];
+            for (i, (board, player)) in boards.into_iter().zip([p1, p2]).enumerate() {
+                let cells = touched(dir, board, player);
+                if shortest {
+                    used[1][i].extend(cells.iter().copied());
+                }
+                used[0][i].extend(cells);
+            }
+        }
+    }
+
+    used
+}
+
+/// The shortest solution's length with the tile at the given cell turned to
+/// floor, and both teleports if it's one of them
+fn without(boards: &[(Board, Player); 2], board: usize, at: Player) -> Result<Option<usize>> {
+    let mut boards = boards.clone();
+    let tiles = &mut boards[board].0.tiles;
+    match tiles[at.y as usize][at.x as usize] {
+        Tile::Teleport => {
+            for tile in tiles.iter_mut().flatten() {
+                if matches!(tile, Tile::Teleport) {
+                    *tile = Tile::None;
+                }
+            }
+        }
+        _ => tiles[at.y as usize][at.x as usize] = Tile::None,
+    }
+
+    let [(b1, p1), (b2, p2)] = &boards;
+    let graph = Graph::explore((b1, *p1), (b2, *p2), limits::deadline())?;
+    Ok(graph.to_goal()[0])
+}
+
+/// List the tiles no solution needs, and those the shortest solution is as
+/// short without
+pub fn report(input: &str) -> Result<String> {
+    let ((b1, p1), (b2, p2)) = crate::parse_puzzle(input)?;
+    limits::check(&b1)?;
+    limits::check(&b2)?;
+    let graph = Graph::explore((&b1, p1), (&b2, p2), limits::deadline())?;
+    let length = graph.to_goal()[0].ok_or(Error::NoSolution)?;
+    let used = used(&graph, [&b1, &b2]);
+    let boards = [(b1, p1), (b2, p2)];
+
+    let mut total = 0;
+    let mut rows = vec![["board", "tile", "at", "used by", "without it"].map(String::from)];
+    for (i, (board, _)) in boards.iter().enumerate() {
+        for (y, row) in board.tiles.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                if matches!(tile, Tile::None) {
+                    continue;
+                }
+                total += 1;
+
+                let at = Player {
+                    x: x as isize,
+                    y: y as isize,
+                };
+                let usage = match (used[1][i].contains(&at), used[0][i].contains(&at)) {
+                    (true, _) => Use::Shortest,
+                    (false, true) => Use::LongerOnly,
+                    (false, false) => Use::Never,
+                };
+                let after = without(&boards, i, at)?;
+                if usage == Use::Shortest && after != Some(length) {
+                    continue;
+                }
+
+                rows.push([
+                    (i + 1).to_string(),
+                    name(tile).to_string(),
+                    format!("({}, {})", x, y),
+                    match usage {
+                        Use::Never => "no solution",
+                        Use::LongerOnly => "longer solutions",
+                        Use::Shortest => "the shortest solution",
+                    }
+                    .to_string(),
+                    match after {
+                        Some(moves) if moves == length => "same length".to_string(),
+                        Some(moves) => format!("{} moves", moves),
+                        None => "unsolvable".to_string(),
+                    },
+                ]);
+            }
+        }
+    }
+
+    if rows.len() == 1 {
+        return Ok(format!("All {} tiles are needed\n", total));
+    }
+    Ok(format!(
+        "{} of {} tiles could go ({} moves as it is)\n\n{}",
+        rows.len() - 1,
+        total,
+        length,
+        crate::output::table(&rows)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn report() {
+        // Player 2 needs the wall to hold still while player 1 steps left, and
+        // the pit is only ever fallen into
+        let puzzle = "x\n.R\nP.\n\n x\nWR";
+
+        assert_eq!(
+            "1 of 2 tiles could go (2 moves as it is)\n\
+             \n\
+             board  tile  at      used by      without it\n\
+             1      pit   (0, 1)  no solution  same length\n",
+            super::report(puzzle).unwrap()
+        );
+        assert_eq!(
+            "All 1 tiles are needed\n",
+            super::report("x\n.R\n\n x\nWR").unwrap()
+        );
+    }
+}
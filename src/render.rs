@@ -0,0 +1,413 @@
+//! Printing boards for people to look at
+
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::algorithm;
+use crate::cli::Matches;
+use crate::{Board, Dir, Error, Player, Result, Side, Tile};
+
+/// The space between boards printed side by side
+pub const GUTTER: usize = 4;
+
+/// Whether boards are printed with colors
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Whether drawings that need one come with a legend
+static LEGEND_SHOWN: AtomicBool = AtomicBool::new(true);
+
+/// Turn colors on or off with `--color`, defaulting to on for terminals
+/// unless `NO_COLOR` is set, and legends with `--legend`
+pub fn configure(matches: &Matches) -> Result<()> {
+    let color = match matches.value("color").unwrap_or("auto") {
+        "always" => true,
+        "never" => false,
+        "auto" => std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+        _ => {
+            return Err(Error::Usage(
+                "--color must be auto, always or never".to_string(),
+            ))
+        }
+    };
+    COLOR.store(color, Ordering::Relaxed);
+
+    let legend = match matches.value("legend").unwrap_or("on") {
+        "on" => true,
+        "off" => false,
+        _ => return Err(Error::Usage("--legend must be on or off".to_string())),
+    };
+    LEGEND_SHOWN.store(legend, Ordering::Relaxed);
+
+    Ok(())
+}
+
+/// The legend, or nothing with `--legend off`
+fn legend(text: String) -> String {
+    match LEGEND_SHOWN.load(Ordering::Relaxed) {
+        true => text,
+        false => String::new(),
+    }
+}
+
+/// The ANSI style for a character of a board
+fn style(c: char) -> Option<&'static str> {
+    match c {
+        // The players stand out the most, since they're what you're following
+        'R' => Some("1;30;43"),
+        '.' => Some("2"),
+        'W' => Some("90"),
+        'T' => Some("35"),
+        'P' => Some("31"),
+        'I' => Some("36"),
+        'x' | '^' | 'v' | '<' | '>' => Some("1;32"),
+        '↑' | '↓' | '←' | '→' => Some("1;33"),
+        '░' => Some("34"),
+        '▒' => Some("36"),
+        '▓' => Some("33"),
+        '█' => Some("31"),
+        '●' => Some("1;33"),
+        '·' => Some("2"),
+        '■' => Some("90"),
+        '◆' => Some("35"),
+        '○' => Some("31"),
+        '≈' => Some("36"),
+        '⇧' | '⇩' | '⇦' | '⇨' => Some("1;32"),
+        '+' => Some("32"),
+        '!' => Some("1;31"),
+        '?' => Some("33"),
+        _ => None,
+    }
+}
+
+/// Color each tile of boards that have already been laid out
+fn paint(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match style(c) {
+            Some(style) => out.push_str(&format!("\x1b[{}m{}\x1b[0m", style, c)),
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+/// Print two blocks of text next to each other
+pub fn side_by_side(left: &str, right: &str) -> String {
+    let width = left.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+    let (mut left, mut right) = (left.lines(), right.lines());
+    let mut out = String::new();
+
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => break,
+            (l, r) => {
+                let line = format!(
+                    "{:<width$}{}",
+                    l.unwrap_or_default(),
+                    r.unwrap_or_default(),
+                    width = width + GUTTER
+                );
+                out.push_str(line.trim_end());
+                out.push('\n');
+            }
+        }
+    }
+
+    out
+}
+
+/// Color the text if colors are on
+fn finish(text: String) -> String {
+    if COLOR.load(Ordering::Relaxed) {
+        paint(&text)
+    } else {
+        text
+    }
+}
+
+/// Print both boards of a puzzle next to each other
+pub fn puzzle((b1, p1): (&Board, Player), (b2, p2): (&Board, Player)) -> String {
+    finish(side_by_side(&b1.to_text(p1), &b2.to_text(p2)))
+}
+
+/// The arrow drawn on a cell the player leaves in this direction
+fn arrow(dir: Dir) -> char {
+    match dir {
+        Dir::Up => '↑',
+        Dir::Down => '↓',
+        Dir::Left => '←',
+        Dir::Right => '→',
+    }
+}
+
+/// Print a board with an arrow on every cell the player leaves along the way,
+/// showing the last move made from cells visited more than once
+fn route(board: &Board, start: Player, moves: &[Dir]) -> String {
+    let mut lines = board
+        .to_text(start)
+        .lines()
+        .map(|l| l.chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut p = start;
+
+    for &dir in moves {
+        let cells = crate::animate::path(dir, board, p);
+
+        for &cell in &cells[..cells.len() - 1] {
+            // Teleports stay visible, and the start stays marked
+            if cell != start && matches!(board.get_tile(cell), Tile::None | Tile::Ice) {
+                // The first line is the exit
+                lines[cell.y as usize + 1][cell.x as usize] = arrow(dir);
+            }
+        }
+        p = *cells.last().unwrap();
+    }
+
+    lines
+        .into_iter()
+        .map(|l| l.into_iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Print both boards of a puzzle next to each other with the route the
+/// players take drawn on
+pub fn solution((b1, p1): (&Board, Player), (b2, p2): (&Board, Player), moves: &[Dir]) -> String {
+    finish(side_by_side(&route(b1, p1, moves), &route(b2, p2, moves)))
+}
+
+/// Shades for cells visited more and more often
+const SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+
+/// Print a board with each cell shaded by how often it was visited, relative
+/// to the busiest cell
+fn shade(board: &Board, visits: &HashMap<Player, usize>, busiest: usize) -> String {
+    let mut lines = vec![board.exit.to_text()];
+
+    lines.extend(board.tiles.iter().enumerate().map(|(y, row)| {
+        row.iter()
+            .enumerate()
+            .map(|(x, tile)| {
+                let cell = Player {
+                    x: x as isize,
+                    y: y as isize,
+                };
+                match visits.get(&cell) {
+                    Some(&n) => SHADES[(n * SHADES.len()).div_ceil(busiest) - 1],
+                    None => tile.to_char(),
+                }
+            })
+            .collect::<String>()
+    }));
+
+    lines.join("\n")
+}
+
+/// Print both boards with each cell shaded by its count, like how often the
+/// search visited it, which the legend calls `what`
+pub fn heatmap(boards: [&Board; 2], visits: &[HashMap<Player, usize>; 2], what: &str) -> String {
+    let busiest = visits
+        .iter()
+        .flat_map(HashMap::values)
+        .copied()
+        .max()
+        .unwrap_or(1);
+
+    finish(format!(
+        "{}{}",
+        side_by_side(
+            &shade(boards[0], &visits[0], busiest),
+            &shade(boards[1], &visits[1], busiest)
+        ),
+        legend(format!(
+            "{} fewest to {} most {} ({})\n",
+            SHADES[0],
+            SHADES[SHADES.len() - 1],
+            what,
+            busiest
+        ))
+    ))
+}
+
+/// Print a board with each floor cell marked by whether the player can stop
+/// there and whether the exit can be reached from there, on their own board
+fn reach(board: &Board, player: Player) -> String {
+    let reached = algorithm::reachable(board, player);
+    let leads_out = algorithm::distances(board);
+    let mut lines = vec![board.exit.to_text()];
+
+    lines.extend(board.tiles.iter().enumerate().map(|(y, row)| {
+        row.iter()
+            .enumerate()
+            .map(|(x, tile)| {
+                let cell = Player {
+                    x: x as isize,
+                    y: y as isize,
+                };
+                if cell == player {
+                    return 'R';
+                }
+                match (tile, reached.contains(&cell), leads_out.contains_key(&cell)) {
+                    (Tile::None, true, true) => '+',
+                    (Tile::None, true, false) => '!',
+                    (Tile::None, false, true) => '?',
+                    _ => tile.to_char(),
+                }
+            })
+            .collect::<String>()
+    }));
+
+    lines.join("\n")
+}
+
+/// Print both boards marked with where each player can get to and get out
+/// from, each on their own board
+pub fn reachability((b1, p1): (&Board, Player), (b2, p2): (&Board, Player)) -> String {
+    finish(format!(
+        "{}{}",
+        side_by_side(&reach(b1, p1), &reach(b2, p2)),
+        legend(
+            "+ reachable, leads out  ! reachable, no way out  ? leads out, never reached\n"
+                .to_string()
+        )
+    ))
+}
+
+/// The glyph for a tile on box-drawn boards
+fn glyph(tile: Tile) -> char {
+    match tile {
+        Tile::None => '·',
+        Tile::Wall => '■',
+        Tile::Teleport => '◆',
+        Tile::Pit => '○',
+        Tile::Ice => '≈',
+        Tile::Exit => '⇧',
+    }
+}
+
+/// What the glyphs of box-drawn boards mean
+const LEGEND: &str = "● player  · floor  ■ wall  ≈ ice  ○ pit  ◆ teleport  ⇧⇩⇦⇨ exit\n";
+
+/// Draw a board inside a box, with the exit as an arrow in its border
+fn boxed(board: &Board, player: Player) -> String {
+    let width = board.tiles.iter().map(Vec::len).max().unwrap_or(0);
+    let height = board.tiles.len();
+    let exit = |side, pos| board.exit.side == side && board.exit.pos == pos;
+    let edge = |side, arrow| -> String {
+        (0..width)
+            .map(|x| if exit(side, x) { arrow } else { '─' })
+            .collect()
+    };
+
+    let mut lines = vec![format!("┌{}┐", edge(Side::Top, '⇧'))];
+    for y in 0..height {
+        let row = (0..width)
+            .map(|x| {
+                let cell = Player {
+                    x: x as isize,
+                    y: y as isize,
+                };
+                match cell == player {
+                    true => '●',
+                    false => glyph(board.get_tile(cell)),
+                }
+            })
+            .collect::<String>();
+        let left = if exit(Side::Left, y) { '⇦' } else { '│' };
+        let right = if exit(Side::Right, y) { '⇨' } else { '│' };
+        lines.push(format!("{}{}{}", left, row, right));
+    }
+    lines.push(format!("└{}┘", edge(Side::Bottom, '⇩')));
+
+    lines.join("\n")
+}
+
+/// Print both boards of a puzzle in boxes, with a glyph for each kind of tile
+/// and a legend
+pub fn pretty((b1, p1): (&Board, Player), (b2, p2): (&Board, Player)) -> String {
+    finish(format!(
+        "{}{}",
+        side_by_side(&boxed(b1, p1), &boxed(b2, p2)),
+        legend(format!("\n{}", LEGEND))
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Dir::*;
+
+    #[test]
+    fn side_by_side() {
+        assert_eq!(
+            " x      x\n...    R.\n.R.\n",
+            super::side_by_side(" x\n...\n.R.", " x\nR.")
+        );
+    }
+
+    #[test]
+    fn solution() {
+        let ((b1, p1), (b2, p2)) = crate::parse_puzzle(" x\nTRT\n\n x\nR.I.").unwrap();
+
+        assert_eq!(
+            " x      x\nTRT    R↑I.\n",
+            super::solution((&b1, p1), (&b2, p2), &[Right, Left, Right, Up])
+        );
+
+        let ice = crate::Board::parse(" x\nRI.").unwrap();
+        assert_eq!(" x\nR→.", super::route(&ice, p2, &[Right]));
+    }
+
+    #[test]
+    fn reachability() {
+        let ((b1, p1), (b2, p2)) =
+            crate::parse_puzzle(" x\n...\n.R.\nIIW\n.W.\n\nx\n.W\nWR\n..").unwrap();
+
+        assert_eq!(
+            " x     x\n\
+             +++    ?W\n\
+             +R+    WR\n\
+             IIW    !!\n\
+             +W.\n\
+             + reachable, leads out  ! reachable, no way out  ? leads out, never reached\n",
+            super::reachability((&b1, p1), (&b2, p2))
+        );
+    }
+
+    #[test]
+    fn heatmap() {
+        let board = crate::Board::parse(" x\nW..").unwrap();
+        let visits = [(1, 1), (2, 4)]
+            .map(|(x, n)| (crate::Player { x, y: 0 }, n))
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            " x      x\nW░█    W..\n░ fewest to █ most visits (4)\n",
+            super::heatmap([&board, &board], &[visits, Default::default()], "visits")
+        );
+    }
+
+    #[test]
+    fn pretty() {
+        let ((b1, p1), (b2, p2)) = crate::parse_puzzle(" x\nTRT\nPI.\n\n<1\nW..\n.R.").unwrap();
+
+        assert_eq!(
+            "┌─⇧─┐    ┌───┐\n\
+             │◆●◆│    │■··│\n\
+             │○≈·│    ⇦·●·│\n\
+             └───┘    └───┘\n\
+             \n\
+             ● player  · floor  ■ wall  ≈ ice  ○ pit  ◆ teleport  ⇧⇩⇦⇨ exit\n",
+            super::pretty((&b1, p1), (&b2, p2))
+        );
+    }
+
+    #[test]
+    fn paint() {
+        assert_eq!(
+            " \x1b[1;32mx\x1b[0m\n\x1b[1;30;43mR\x1b[0m\x1b[35mT\x1b[0m\n",
+            super::paint(" x\nRT\n")
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Stepping back and forth through a solution
+
+use crate::term::{Key, Screen};
+use crate::verify::{self, Step};
+use crate::{Board, Dir, Player, Result};
+
+/// The key bindings, shown at the bottom of the screen
+const HELP: &str = "←/→ step · 0-9 then enter jump to move · q quit";
+
+struct Replay {
+    b1: Board,
+    b2: Board,
+    moves: Vec<Dir>,
+    /// Both players' positions before the first move and after each one
+    states: Vec<(Player, Player)>,
+    /// How the moves end, when they don't solve the puzzle
+    ending: Option<String>,
+    /// The move being shown, with 0 for the start
+    at: usize,
+    /// The move number being typed in
+    jump: String,
+}
+
+impl Replay {
+    /// Play the moves through ahead of time, stopping where the game ends
+    fn new(input: &str, moves: Vec<Dir>) -> Result<Self> {
+        let ((b1, p1), (b2, p2)) = crate::parse_puzzle(input)?;
+        let mut states = vec![(p1, p2)];
+        let mut ending = None;
+
+        for (i, &dir) in moves.iter().enumerate() {
+            let &(p1, p2) = states.last().unwrap();
+
+            match verify::step(dir, (&b1, p1), (&b2, p2)) {
+                Step::Moved(n1, n2) => states.push((n1, n2)),
+                Step::Solved => {
+                    states.push((b1.exit_cell(), b2.exit_cell()));
+                    break;
+                }
+                Step::Died(player) => {
+                    ending = Some(format!(
+                        "Move {}: player {} falls into a pit",
+                        i + 1,
+                        player
+                    ));
+                    break;
+                }
+                Step::ExitedAlone(player) => {
+                    ending = Some(format!("Move {}: only player {} gets out", i + 1, player));
+                    break;
+                }
+            }
+        }
+
+        Ok(Replay {
+            b1,
+            b2,
+            moves,
+            states,
+            ending,
+            at: 0,
+            jump: String::new(),
+        })
+    }
+
+    /// Handle a key press, returning false to quit
+    fn key(&mut self, key: Key) -> bool {
+        let last = self.states.len() - 1;
+
+        match key {
+            Key::Right | Key::Char('l' | ' ') => self.at = (self.at + 1).min(last),
+            Key::Left | Key::Char('h') => self.at = self.at.saturating_sub(1),
+            Key::Char(c) if c.is_ascii_digit() => self.jump.push(c),
+            Key::Backspace => {
+                self.jump.pop();
+            }
+            Key::Enter => {
+                if let Ok(n) = self.jump.parse::<usize>() {
+                    self.at = n.min(last);
+                }
+                self.jump.clear();
+            }
+            Key::Char('q') | Key::Esc => return false,
+            _ => {}
+        }
+
+        true
+    }
+
+    /// Lay out the whole screen
+    fn screen(&self) -> String {
+        let (p1, p2) = self.states[self.at];
+        let heading = match self.at {
+            0 => format!("Start ({} moves)", self.moves.len()),
+            n => format!("Move {}/{}: {:?}", n, self.moves.len(), self.moves[n - 1]),
+        };
+
+        let moves = self
+            .moves
+            .iter()
+            .enumerate()
+            .map(|(i, d)| {
+                if i + 1 == self.at {
+                    format!("[{}]", d.to_char())
+                } else {
+                    d.to_char().to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let ending = match &self.ending {
+            Some(ending) if self.at == self.states.len() - 1 => ending.as_str(),
+            _ => "",
+        };
+
+        format!(
+            "{}\n\n{}\n{}\n{}\n\nJump to: {}\n{}\n",
+            heading,
+            crate::render::puzzle((&self.b1, p1), (&self.b2, p2)),
+            moves,
+            ending,
+            self.jump,
+            HELP
+        )
+    }
+}
+
+/// Open the replay viewer on the moves
+pub fn run(input: &str, moves: Vec<Dir>) -> Result<()> {
+    let mut replay = Replay::new(input, moves)?;
+    let mut screen = Screen::enter()?;
+
+    loop {
+        screen.draw(&replay.screen())?;
+
+        match screen.key()? {
+            Some(key) if replay.key(key) => {}
+            _ => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Replay;
+    use crate::term::Key;
+    use crate::Dir::*;
+    use crate::Player;
+
+    #[test]
+    fn scrub() {
+        let moves = vec![Right, Left, Right, Up];
+        let mut replay = Replay::new(" x\nTRT\n\n x\nR.I.", moves).unwrap();
+        let cell = |x, y| Player { x, y };
+
+        replay.key(Key::Left);
+        assert_eq!(0, replay.at);
+
+        replay.key(Key::Right);
+        replay.key(Key::Right);
+        assert_eq!((cell(0, 0), cell(0, 0)), replay.states[replay.at]);
+
+        for key in [Key::Char('9'), Key::Enter] {
+            replay.key(key);
+        }
+        assert_eq!(4, replay.at);
+        assert!(replay.screen().starts_with("Move 4/4: Up"));
+    }
+
+    #[test]
+    fn ending() {
+        let replay = Replay::new(" x\nR\n\n x\nRP", vec![Right, Up]).unwrap();
+
+        assert_eq!(1, replay.states.len());
+        assert_eq!(
+            Some("Move 1: player 2 falls into a pit"),
+            replay.ending.as_deref()
+        );
+    }
+}
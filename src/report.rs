@@ -0,0 +1,83 @@
+//! Comparing a playthrough against the shortest solution
+
+use crate::hint::{self, Position};
+use crate::{output, Dir, Result};
+
+/// How many moves are left to go after each of the moves, counting the one
+/// that solves the puzzle as leaving none
+fn remaining(input: &str, moves: &[Dir]) -> Result<Vec<usize>> {
+    (1..=moves.len())
+        .map(|i| match i {
+            _ if i == moves.len() => Ok(0),
+            _ => hint::hint(input, &Position::After(moves[..i].to_vec()), 0).map(|(_, n)| n),
+        })
+        .collect()
+}
+
+/// Compare the moves that solved the puzzle with the shortest solution: how
+/// many extra moves they took, and which moves didn't bring the end closer
+pub fn compare(input: &str, moves: &[Dir]) -> Result<String> {
+    let (optimal, shortest) = hint::hint(input, &Position::After(Vec::new()), usize::MAX)?;
+
+    let mut lines = vec![
+        format!(
+            "You solved it in {} moves: {}",
+            moves.len(),
+            output::compact(moves)
+        ),
+        format!(
+            "The shortest solution takes {}: {}",
+            shortest,
+            output::compact(&optimal)
+        ),
+    ];
+
+    match moves.len() - shortest {
+        0 => lines.push("That's as short as it gets!".to_string()),
+        1 => lines.push("1 extra move".to_string()),
+        n => lines.push(format!("{} extra moves", n)),
+    }
+
+    let mut before = shortest;
+    for (i, after) in remaining(input, moves)?.into_iter().enumerate() {
+        if after + 1 != before {
+            lines.push(format!(
+                "Move {} ({:?}) didn't get you closer: {} to go before, {} after",
+                i + 1,
+                moves[i],
+                before,
+                after
+            ));
+        }
+        before = after;
+    }
+
+    Ok(lines.join("\n") + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Dir;
+
+    const PUZZLE: &str = " x\n...\n...\n.R.\n\n x\n...\n...\n..R";
+
+    #[test]
+    fn compare() {
+        let moves = Dir::parse_moves("UURLU").unwrap();
+        assert_eq!(
+            "You solved it in 5 moves: UURLU\n\
+             The shortest solution takes 5: UURLU\n\
+             That's as short as it gets!\n",
+            super::compare(PUZZLE, &moves).unwrap()
+        );
+
+        let moves = Dir::parse_moves("DUURLU").unwrap();
+        assert_eq!(
+            "You solved it in 6 moves: DUURLU\n\
+             The shortest solution takes 5: UURLU\n\
+             1 extra move\n\
+             Move 1 (Down) didn't get you closer: 5 to go before, 5 after\n",
+            super::compare(PUZZLE, &moves).unwrap()
+        );
+    }
+}
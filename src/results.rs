@@ -0,0 +1,184 @@
+//! Keeping every solve in a SQLite database, for experiments that run over
+//! many puzzles and many versions
+//!
+//! Each solve adds a row to the `solves` table: the puzzle's hash, the
+//! algorithm, how long it took, how many moves the solution has (empty when
+//! there's no solution) and when it happened. `stats` sums the table up, or
+//! runs any query on it. The database is worked through the `sqlite3` shell,
+//! so there's nothing to link against, but it has to be installed.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::cli::Matches;
+use crate::{algorithm, metrics, output, Dir, Error, Result, Stats};
+
+/// The table every solve goes in, made the first time one is recorded
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS solves (
+    puzzle TEXT NOT NULL,
+    algorithm TEXT NOT NULL,
+    elapsed_ms REAL NOT NULL,
+    moves INTEGER,
+    solved_at TEXT NOT NULL DEFAULT (datetime('now'))
+);";
+
+/// Each puzzle and algorithm's solves, summed up
+const SUMMARY: &str = "SELECT puzzle, algorithm, COUNT(*), ifnull(MIN(moves), '-'),
+    printf('%.1f', MIN(elapsed_ms)), printf('%.1f', AVG(elapsed_ms)), MAX(solved_at)
+FROM solves GROUP BY puzzle, algorithm ORDER BY puzzle, algorithm;";
+
+/// Where the database is kept when `--results-db` doesn't say
+fn default_path() -> Option<PathBuf> {
+    let dir = match std::env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".local/share"),
+    };
+    Some(dir.join(env!("CARGO_PKG_NAME")).join("results.db"))
+}
+
+/// The database from `--results-db`, or the default one
+fn path(matches: &Matches) -> Option<PathBuf> {
+    matches
+        .value("results-db")
+        .map(PathBuf::from)
+        .or_else(default_path)
+}
+
+/// Run SQL on the database, returning what it printed, a row per line with
+/// tabs between the columns
+fn sqlite(path: &Path, sql: &str, header: bool) -> Result<String> {
+    let mut child = Command::new("sqlite3")
+        .args(["-batch", "-separator", "\t"])
+        .arg(if header { "-header" } else { "-noheader" })
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::Io(e.kind()))?;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(sql.as_bytes())
+        .map_err(|e| Error::Io(e.kind()))?;
+
+    let output = child.wait_with_output().map_err(|e| Error::Io(e.kind()))?;
+    match output.status.success() {
+        true => Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+        false => Err(Error::Database(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )),
+    }
+}
+
+/// Add a solve to the database at the given path
+fn insert(path: &Path, input: &str, moves: Option<usize>, elapsed: Duration) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| Error::Io(e.kind()))?;
+    }
+
+    let sql = format!(
+        "{}\nINSERT INTO solves (puzzle, algorithm, elapsed_ms, moves) VALUES ('{}', '{}', {}, {});",
+        SCHEMA,
+        metrics::hash(input),
+        algorithm::configured().name(),
+        elapsed.as_secs_f64() * 1000.0,
+        moves.map_or("NULL".to_string(), |m| m.to_string())
+    );
+    sqlite(path, &sql, false).map(|_| ())
+}
+
+/// Record the search in the results database, if it came to an answer
+pub fn record(
+    matches: &Matches,
+    input: &str,
+    result: &Result<(Vec<Dir>, Stats)>,
+    elapsed: Duration,
+) -> Result<()> {
+    let moves = match result {
+        Ok((moves, _)) => Some(moves.len()),
+        Err(Error::NoSolution) => None,
+        // Timeouts and the like say nothing about the puzzle
+        Err(_) => return Ok(()),
+    };
+
+    match path(matches) {
+        Some(path) => insert(&path, input, moves, elapsed),
+        None => Ok(()),
+    }
+}
+
+/// Sum up the solves in the database at the given path, or run the query
+fn query(path: &Path, sql: Option<&str>) -> Result<String> {
+    if let Some(sql) = sql {
+        return sqlite(path, sql, true);
+    }
+
+    let mut rows = vec![[
+        "puzzle",
+        "algorithm",
+        "solves",
+        "moves",
+        "best ms",
+        "mean ms",
+        "last",
+    ]
+    .map(String::from)];
+    let found = sqlite(path, &format!("{}\n{}", SCHEMA, SUMMARY), false)?;
+    for line in found.lines() {
+        let mut columns = line.split('\t').map(String::from);
+        rows.push(std::array::from_fn(|_| columns.next().unwrap_or_default()));
+    }
+
+    match rows.len() {
+        1 => Ok("No solves recorded yet\n".to_string()),
+        _ => Ok(output::table(&rows)),
+    }
+}
+
+/// The `stats` command: sum up the results database, or run `--sql` on it
+pub fn stats(matches: &Matches) -> Result<String> {
+    let path = path(matches).ok_or(Error::Usage("--results-db needs a FILE".to_string()))?;
+    query(&path, matches.value("sql"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    #[test]
+    fn record() {
+        let path = std::env::temp_dir().join("hive-mind-solver-results-test.db");
+        let _ = std::fs::remove_file(&path);
+        let puzzle = " x\n.R.\n\n x\n.R.";
+
+        assert_eq!(
+            "No solves recorded yet\n",
+            super::query(&path, None).unwrap()
+        );
+        super::insert(&path, puzzle, Some(1), Duration::from_millis(2)).unwrap();
+        super::insert(&path, puzzle, Some(1), Duration::from_millis(4)).unwrap();
+
+        let summary = super::query(&path, None).unwrap();
+        let row: Vec<&str> = summary.lines().nth(1).unwrap().split_whitespace().collect();
+        assert_eq!(
+            [
+                crate::metrics::hash(puzzle).as_str(),
+                "bfs",
+                "2",
+                "1",
+                "2.0",
+                "3.0"
+            ],
+            row[..6]
+        );
+        assert_eq!(
+            "COUNT(*)\n2\n",
+            super::query(&path, Some("SELECT COUNT(*) FROM solves;")).unwrap()
+        );
+        assert!(super::query(&path, Some("SELECT nothing FROM nowhere;")).is_err());
+    }
+}
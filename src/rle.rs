@@ -0,0 +1,190 @@
+//! Run-length-encoded board notation
+//!
+//! Each board row is written as a series of runs, where a run is an optional
+//! count followed by a tile character (`3.` is three empty tiles, `W` is a
+//! single wall). Rows are separated by `/`, starting with the exit row, and
+//! the two boards of a puzzle are separated by `,`. Exits on the other sides
+//! of the board keep their plain notation (`<2`) in the exit row:
+//!
+//! ```text
+//! .x/3./3./.R.,.x/3./3./2.R
+//! ```
+
+use std::borrow::Cow;
+
+use crate::{Error, Result, Side};
+
+/// The most tiles a puzzle expands to, well past any board the solver would
+/// take, so mistyped counts can't ask for gigabytes of floor, whether in one
+/// run or spread over many
+const MAX_TILES: usize = 1 << 16;
+
+/// Whether the input looks like RLE rather than a plain board definition
+fn is_rle(input: &str) -> bool {
+    let input = input.trim();
+
+    !input.contains('\n') && input.contains('/')
+}
+
+/// Expand a single RLE row into its plain characters, out of the tiles `left`
+/// for the rest of the puzzle
+fn expand_row(row: &str, left: &mut usize) -> Result<String> {
+    let mut out = String::new();
+    let mut count = None::<usize>;
+
+    for c in row.chars() {
+        if let Some(d) = c.to_digit(10) {
+            count = count
+                .unwrap_or(0)
+                .checked_mul(10)
+                .map(|n| n + d as usize)
+                .filter(|&n| n <= *left);
+            if count.is_none() {
+                return Err(Error::InvalidRle);
+            }
+        } else if matches!(c, '.' | 'x' | 'R' | 'T' | 'P' | 'I' | 'W') {
+            match count.take().unwrap_or(1) {
+                0 => return Err(Error::InvalidRle),
+                n if n > *left => return Err(Error::InvalidRle),
+                n => {
+                    *left -= n;
+                    out.extend(std::iter::repeat_n(c, n));
+                }
+            }
+        } else {
+            return Err(Error::InvalidRle);
+        }
+    }
+
+    if count.is_some() {
+        return Err(Error::InvalidRle);
+    }
+
+    Ok(out)
+}
+
+/// Compress a single plain row into RLE runs
+fn compress_row(row: &str) -> String {
+    let mut out = String::new();
+    let mut chars = row
+        .chars()
+        .map(|c| if c == ' ' { '.' } else { c })
+        .peekable();
+
+    while let Some(c) = chars.next() {
+        let mut n = 1;
+        while chars.next_if_eq(&c).is_some() {
+            n += 1;
+        }
+
+        if n > 1 {
+            out.push_str(&n.to_string());
+        }
+        out.push(c);
+    }
+
+    out
+}
+
+/// Expand an RLE puzzle into the plain board format, passing plain input through untouched
+pub fn expand(input: &str) -> Result<Cow<'_, str>> {
+    if !is_rle(input) {
+        return Ok(Cow::Borrowed(input));
+    }
+
+    let mut left = MAX_TILES;
+    let boards = input
+        .trim()
+        .split(',')
+        .map(|board| {
+            board
+                .split('/')
+                .enumerate()
+                .map(|(y, row)| {
+                    if y == 0 && row.starts_with(['^', 'v', '<', '>']) {
+                        return Ok(row.to_string());
+                    }
+
+                    let row = expand_row(row, &mut left)?;
+                    // The exit row is conventionally written with spaces
+                    Ok(if y == 0 { row.replace('.', " ") } else { row })
+                })
+                .collect::<Result<Vec<_>>>()
+                .map(|rows| rows.join("\n"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Cow::Owned(boards.join("\n\n")))
+}
+
+/// Serialize a puzzle into RLE notation
+pub fn compress(input: &str) -> Result<String> {
+    let (board1, board2) = crate::parse_puzzle(input)?;
+
+    Ok([board1, board2]
+        .iter()
+        .map(|(board, player)| {
+            let text = board.to_text(*player);
+            let mut rows = text.lines();
+            let exit = match board.exit.side {
+                Side::Top => compress_row(rows.next().unwrap_or_default()),
+                _ => rows.next().unwrap_or_default().to_string(),
+            };
+
+            std::iter::once(exit)
+                .chain(rows.map(compress_row))
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Dir::*;
+    use crate::Error;
+
+    const PUZZLE: &str = " x\n...\n...\n.R.\n\n x\n...\n...\n..R";
+
+    #[test]
+    fn round_trip() {
+        let rle = super::compress(PUZZLE).unwrap();
+        assert_eq!(".x/3./3./.R.,.x/3./3./2.R", rle);
+        assert_eq!(PUZZLE, super::expand(&rle).unwrap());
+    }
+
+    #[test]
+    fn side_exit() {
+        let rle = super::compress(">1\n...\n.R.\n\nv0\n..R\n...").unwrap();
+        assert_eq!(">1/3./.R.,v0/2.R/3.", rle);
+        assert_eq!(">1\n...\n.R.\n\nv0\n..R\n...", super::expand(&rle).unwrap());
+    }
+
+    #[test]
+    fn solve_rle() {
+        assert_eq!(
+            Ok(vec![Up, Up, Right, Left, Up]),
+            crate::solve_puzzle(".x/3./3./.R.,.x/3./3./2.R")
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(Err(Error::InvalidRle), super::expand("x/0./.R"));
+        assert_eq!(Err(Error::InvalidRle), super::expand("x/3?/.R"));
+        assert_eq!(Err(Error::InvalidRle), super::expand("x/3/.R"));
+        assert_eq!(
+            Err(Error::InvalidRle),
+            super::expand("x/99999999999999999999./.R")
+        );
+
+        // Short runs add up too
+        let many = ["60000."; 10].join("/");
+        assert_eq!(
+            Err(Error::InvalidRle),
+            super::expand(&format!("x/{}/.R", many))
+        );
+        assert!(super::expand("x/60000./.R").is_ok());
+    }
+}
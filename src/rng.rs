@@ -0,0 +1,105 @@
+//! Reproducible randomness, seeded with `--seed`
+//!
+//! Everything random draws from a generator seeded here, so the same seed
+//! gives the same results on every run and every platform. Without `--seed`
+//! the seed is 0, not the clock, except where every run should differ, like
+//! generating puzzles.
+//!
+//! The rest of the output is held to the same standard: the same puzzle, flags
+//! and seed print the same thing every time. Hash maps are seeded afresh on
+//! every run, so they're only ever looked things up in, never walked through
+//! in whatever order they keep.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::cli::Matches;
+use crate::{Error, Result};
+
+/// The seed from `--seed`
+static SEED: AtomicU64 = AtomicU64::new(0);
+/// Whether `--seed` was given
+static SEEDED: AtomicBool = AtomicBool::new(false);
+
+/// Set the seed from `--seed`
+pub fn configure(matches: &Matches) -> Result<()> {
+    if let Some(value) = matches.value("seed") {
+        let seed = value
+            .parse()
+            .map_err(|_| Error::Usage("--seed takes a number".to_string()))?;
+        SEED.store(seed, Ordering::Relaxed);
+        SEEDED.store(true, Ordering::Relaxed);
+    }
+
+    Ok(())
+}
+
+/// The seed from `--seed`, or one from the clock when there isn't one
+pub fn seed_or_clock() -> u64 {
+    match SEEDED.load(Ordering::Relaxed) {
+        true => SEED.load(Ordering::Relaxed),
+        false => std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64),
+    }
+}
+
+/// A SplitMix64 generator, which only uses 64-bit integer arithmetic and so
+/// behaves the same everywhere
+pub struct Rng(u64);
+
+impl Rng {
+    /// A generator starting from the configured seed
+    pub fn new() -> Self {
+        Rng(SEED.load(Ordering::Relaxed))
+    }
+
+    /// A generator starting from the given seed
+    pub fn from_seed(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    /// The next number in the sequence
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number below `n`
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// True with the given probability
+    pub fn chance(&mut self, p: f64) -> bool {
+        // The top 53 bits make an evenly spread float in [0, 1)
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    /// Put the items in a random order
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rng;
+
+    #[test]
+    fn reproducible() {
+        // The first outputs of SplitMix64 seeded with 0
+        let mut rng = Rng(0);
+        assert_eq!(0xe220_a839_7b1d_cdaf, rng.next_u64());
+        assert_eq!(0x6e78_9e6a_a1b9_65f4, rng.next_u64());
+
+        let (mut a, mut b) = ([1, 2, 3, 4, 5], [1, 2, 3, 4, 5]);
+        Rng(7).shuffle(&mut a);
+        Rng(7).shuffle(&mut b);
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,98 @@
+//! Saving a game in play mode to pick it up again later
+//!
+//! A save holds the moves of the attempt in progress and where they left both
+//! players, tied to the puzzle by its hash. Boards don't change as they're
+//! played, so that's the whole state of a game.
+
+use crate::json::Json;
+use crate::verify::{self, Step};
+use crate::{metrics, output, Dir, Error, Player, Result};
+
+/// A player's position as JSON
+fn position(p: Player) -> Json {
+    Json::object([("x", p.x.into()), ("y", p.y.into())])
+}
+
+/// Describe the game so far
+fn save(input: &str, moves: &[Dir], (p1, p2): (Player, Player)) -> Json {
+    Json::object([
+        ("puzzle", metrics::hash(input).into()),
+        ("moves", output::compact(moves).into()),
+        ("players", Json::Array(vec![position(p1), position(p2)])),
+    ])
+}
+
+/// Write the game so far to the file
+pub fn write(path: &str, input: &str, moves: &[Dir], players: (Player, Player)) -> Result<()> {
+    std::fs::write(path, format!("{}\n", save(input, moves, players)))
+        .map_err(|e| Error::Io(e.kind()))
+}
+
+/// Read back the moves of a saved game, checking that they're for this puzzle
+/// and still lead where they did
+fn load(input: &str, save: &Json) -> Result<Vec<Dir>> {
+    if save.get("puzzle").and_then(Json::as_str) != Some(&metrics::hash(input)) {
+        return Err(Error::Usage(
+            "the save is from a different puzzle".to_string(),
+        ));
+    }
+    let moves = Dir::parse_moves(
+        save.get("moves")
+            .and_then(Json::as_str)
+            .ok_or(Error::InvalidSave)?,
+    )
+    .map_err(|_| Error::InvalidSave)?;
+
+    let ((b1, mut p1), (b2, mut p2)) = crate::parse_puzzle(input)?;
+    for &dir in &moves {
+        match verify::step(dir, (&b1, p1), (&b2, p2)) {
+            Step::Moved(n1, n2) => (p1, p2) = (n1, n2),
+            _ => return Err(Error::InvalidSave),
+        }
+    }
+
+    if save.get("players") == Some(&Json::Array(vec![position(p1), position(p2)])) {
+        Ok(moves)
+    } else {
+        Err(Error::InvalidSave)
+    }
+}
+
+/// Read the moves of the game saved in the file
+pub fn read(path: &str, input: &str) -> Result<Vec<Dir>> {
+    let text = std::fs::read_to_string(path).map_err(|e| Error::Io(e.kind()))?;
+    load(input, &Json::parse(&text).map_err(|_| Error::InvalidSave)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::Json;
+    use crate::Dir::*;
+    use crate::{Error, Player};
+
+    const PUZZLE: &str = " x\n...\n...\n.R.\n\n x\n...\n...\n..R";
+
+    #[test]
+    fn round_trip() {
+        let moves = vec![Up, Right];
+        let players = (Player { x: 2, y: 1 }, Player { x: 2, y: 1 });
+        let save = super::save(PUZZLE, &moves, players);
+
+        assert_eq!(
+            r#"{"puzzle":"9bd394cbe1755d7d","moves":"UR","players":[{"x":2,"y":1},{"x":2,"y":1}]}"#,
+            save.to_string()
+        );
+        assert_eq!(Ok(moves), super::load(PUZZLE, &save));
+    }
+
+    #[test]
+    fn mismatches() {
+        let save =
+            Json::parse(r#"{"puzzle":"9bd394cbe1755d7d","moves":"U","players":[]}"#).unwrap();
+        assert_eq!(Err(Error::InvalidSave), super::load(PUZZLE, &save));
+
+        let other = PUZZLE.replace('R', ".").replacen("...", ".R.", 1);
+        let save = super::save(&other, &[], (Player { x: 1, y: 0 }, Player { x: 1, y: 0 }));
+        assert!(matches!(super::load(PUZZLE, &save), Err(Error::Usage(_))));
+    }
+}
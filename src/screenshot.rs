@@ -0,0 +1,221 @@
+//! Reconstruct a board from a screenshot of the game
+//!
+//! Screenshots and sprites can be PNG or binary PPM (`P6`) images. The sprite
+//! pack is a directory holding one image per tile, named after the tile's
+//! character in the board format (`W.png`, `I.png`, `R.png`, `x.png`, ...,
+//! with `_.png` for an empty tile). The screenshot is cut into cells the size
+//! of the sprites, starting with the exit row at the top, and every cell is
+//! matched to the closest sprite.
+
+use std::path::Path;
+
+use crate::{png, Error, Result};
+
+/// An RGB image
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Parse a PNG or binary PPM image
+    fn parse(bytes: &[u8]) -> Result<Self> {
+        if bytes.starts_with(png::SIGNATURE) {
+            let (width, height, pixels) = png::decode(bytes)?;
+            return Ok(Self {
+                width,
+                height,
+                pixels,
+            });
+        }
+
+        let mut pos = 0;
+        let mut fields = Vec::new();
+
+        while fields.len() < 4 {
+            while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+                pos += 1;
+            }
+            if bytes.get(pos) == Some(&b'#') {
+                while bytes.get(pos).is_some_and(|b| *b != b'\n') {
+                    pos += 1;
+                }
+                continue;
+            }
+
+            let start = pos;
+            while bytes.get(pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+                pos += 1;
+            }
+            if start == pos {
+                return Err(Error::InvalidImage);
+            }
+            let field = std::str::from_utf8(&bytes[start..pos]);
+            fields.push(field.map_err(|_| Error::InvalidImage)?);
+        }
+
+        let number = |s: &str| s.parse::<usize>().map_err(|_| Error::InvalidImage);
+        let (width, height) = (number(fields[1])?, number(fields[2])?);
+
+        if fields[0] != "P6" || number(fields[3])? > 255 {
+            return Err(Error::InvalidImage);
+        }
+
+        let end = width
+            .checked_mul(height)
+            .and_then(|n| n.checked_mul(3))
+            .and_then(|n| n.checked_add(pos + 1))
+            .ok_or(Error::InvalidImage)?;
+        let pixels = bytes.get(pos + 1..end).ok_or(Error::InvalidImage)?.to_vec();
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Read an image from disk
+    fn load(path: &Path) -> Result<Self> {
+        Self::parse(&std::fs::read(path).map_err(|e| Error::Io(e.kind()))?)
+    }
+
+    /// Sum of squared differences between a sprite and the cell at the given offset
+    fn distance(&self, sprite: &Image, x0: usize, y0: usize) -> u64 {
+        (0..sprite.height)
+            .flat_map(|y| (0..sprite.width * 3).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let a = self.pixels[(y0 + y) * self.width * 3 + x0 * 3 + x];
+                let b = sprite.pixels[y * sprite.width * 3 + x];
+                (a as i64 - b as i64).pow(2) as u64
+            })
+            .sum()
+    }
+}
+
+/// Load every sprite in the pack, keyed by its tile character
+fn load_sprites(dir: &Path) -> Result<Vec<(char, Image)>> {
+    let mut sprites = Vec::new();
+
+    for entry in std::fs::read_dir(dir).map_err(|e| Error::Io(e.kind()))? {
+        let path = entry.map_err(|e| Error::Io(e.kind()))?.path();
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+
+        let tile = match stem {
+            "_" => '.',
+            "x" | "R" | "T" | "P" | "I" | "W" => stem.chars().next().unwrap(),
+            _ => continue,
+        };
+
+        sprites.push((tile, Image::load(&path)?));
+    }
+
+    // Cells are cut the size of the sprites, so they can't be empty
+    let size = sprites.first().map(|(_, s)| (s.width, s.height));
+    if size.is_none_or(|(w, h)| w == 0 || h == 0)
+        || sprites
+            .iter()
+            .any(|(_, s)| Some((s.width, s.height)) != size)
+    {
+        return Err(Error::InvalidImage);
+    }
+
+    Ok(sprites)
+}
+
+/// Transcribe a screenshot into the plain board format
+fn transcribe(image: &Image, sprites: &[(char, Image)]) -> String {
+    let (cw, ch) = (sprites[0].1.width, sprites[0].1.height);
+
+    (0..image.height / ch)
+        .map(|row| {
+            let line = (0..image.width / cw)
+                .map(|col| {
+                    let tile = sprites
+                        .iter()
+                        .min_by_key(|(_, s)| image.distance(s, col * cw, row * ch))
+                        .map(|(c, _)| *c)
+                        .unwrap();
+
+                    match (row, tile) {
+                        (0, 'x') => 'x',
+                        (0, _) => ' ',
+                        (_, tile) => tile,
+                    }
+                })
+                .collect::<String>();
+
+            if row == 0 {
+                line.trim_end().to_string()
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Read each screenshot and print the transcribed boards as a puzzle
+pub fn read_boards(screenshots: &[&str], sprite_dir: &str) -> Result<String> {
+    let sprites = load_sprites(Path::new(sprite_dir))?;
+
+    screenshots
+        .iter()
+        .map(|path| Image::load(Path::new(path)).map(|image| transcribe(&image, &sprites)))
+        .collect::<Result<Vec<_>>>()
+        .map(|boards| boards.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Image;
+
+    /// Build a PPM with one flat color per cell of the given size
+    fn ppm(cells: &[&[u8]], size: usize) -> Vec<u8> {
+        let (w, h) = (cells[0].len() * size, cells.len() * size);
+        let mut bytes = format!("P6\n# test\n{} {}\n255\n", w, h).into_bytes();
+
+        for y in 0..h {
+            for x in 0..w {
+                bytes.extend([cells[y / size][x / size]; 3]);
+            }
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn invalid() {
+        let huge = format!("P6\n{} 2\n255\n", usize::MAX / 2);
+        assert!(Image::parse(huge.as_bytes()).is_err());
+        assert!(Image::parse(b"P6\n2 2\n255\n...").is_err());
+    }
+
+    #[test]
+    fn empty_sprites() {
+        let dir = std::env::temp_dir().join("hive-mind-solver-empty-sprites");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("W.ppm"), b"P6\n0 4\n255\n").unwrap();
+
+        assert!(super::load_sprites(&dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn transcribe() {
+        let sprites: Vec<_> = [('.', 200), ('W', 0), ('x', 100), ('R', 50)]
+            .into_iter()
+            .map(|(c, v)| (c, Image::parse(&ppm(&[&[v]], 2)).unwrap()))
+            .collect();
+
+        let shot = ppm(&[&[200, 100, 200], &[200, 0, 200], &[50, 200, 200]], 2);
+
+        assert_eq!(
+            " x\n.W.\nR..",
+            super::transcribe(&Image::parse(&shot).unwrap(), &sprites)
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! Checking the searches against each other over a directory of puzzles
+//!
+//! Every algorithm is held to the state graph, which walks the whole space of
+//! positions and so doesn't share any of their shortcuts: where the graph
+//! finds a solution, every search but `beam` has to find one too, every
+//! solution has to replay, and the searches that promise a shortest solution
+//! have to find one as short as the graph's. Anything else is a bug in one of
+//! them.
+
+use std::path::Path;
+
+use crate::algorithm::Algorithm;
+use crate::cli::Matches;
+use crate::graph::Graph;
+use crate::verify::{self, Verdict};
+use crate::{bench, compare, output, rate, Error, Result};
+
+/// Whether the algorithm always finds a shortest solution
+fn shortest(algorithm: Algorithm) -> bool {
+    matches!(
+        algorithm,
+        Algorithm::Bfs | Algorithm::Astar | Algorithm::Ida
+    )
+}
+
+/// What's wrong with the algorithm's answer to the puzzle, if anything,
+/// given how many moves the graph says it takes
+fn check(puzzle: &str, algorithm: Algorithm, expected: Option<usize>) -> Result<Option<String>> {
+    let moves = match (crate::search_with(puzzle, algorithm), expected) {
+        (Ok((moves, _)), _) => moves,
+        (Err(Error::NoSolution), None) => return Ok(None),
+        // Beam search can miss
+        (Err(Error::NoSolution), Some(_)) if algorithm == Algorithm::Beam => return Ok(None),
+        (Err(Error::NoSolution), Some(n)) => {
+            return Ok(Some(format!("found no solution, but there's one in {}", n)))
+        }
+        (Err(Error::Interrupted), _) => return Err(Error::Interrupted),
+        (Err(error), _) => return Ok(Some(format!("failed: {:?}", error))),
+    };
+
+    let found = output::compact(&moves);
+    Ok(match (verify::verify(puzzle, &moves)?, expected) {
+        (Verdict::Solved { .. }, None) => Some(format!(
+            "{} solves it, but the graph says nothing does",
+            found
+        )),
+        (Verdict::Solved { moves }, Some(n)) if shortest(algorithm) && moves != n => Some(format!(
+            "found {} in {} moves, but there's one in {}",
+            found, moves, n
+        )),
+        (Verdict::Solved { .. }, Some(_)) => None,
+        (verdict, _) => Some(format!("{} doesn't solve it: {}", found, verdict)),
+    })
+}
+
+/// Run every algorithm in `--algorithms` (all of them by default) on the
+/// puzzles in the directory, listing where they go wrong. Returns whether
+/// they all got it right too.
+pub fn selftest(matches: &Matches) -> Result<(String, bool)> {
+    let dir = matches.arg(0).unwrap();
+    let algorithms = compare::algorithms(matches)?;
+
+    let mut rows = vec![["puzzle", "algorithm", "problem"].map(String::from)];
+    let (mut total, mut skipped) = (0, 0);
+    for name in bench::puzzles(dir)? {
+        let path = Path::new(dir).join(&name).to_string_lossy().into_owned();
+        let Ok(puzzles) = rate::puzzles(&path) else {
+            skipped += 1;
+            continue;
+        };
+        for (name, puzzle) in puzzles {
+            let graph = match Graph::of(&puzzle) {
+                Ok(graph) => graph,
+                Err(Error::Interrupted) => return Err(Error::Interrupted),
+                Err(_) => {
+                    skipped += 1;
+                    continue;
+                }
+            };
+            let expected = graph.to_goal()[0];
+            total += 1;
+
+            for &algorithm in &algorithms {
+                if let Some(problem) = check(&puzzle, algorithm, expected)? {
+                    rows.push([name.clone(), algorithm.name().to_string(), problem]);
+                }
+            }
+        }
+    }
+
+    let mut out = match rows.len() - 1 {
+        0 => format!(
+            "All {} algorithms agree on all {} puzzles\n",
+            algorithms.len(),
+            total
+        ),
+        n => format!("{} problems among {} puzzles\n", n, total),
+    };
+    if skipped > 0 {
+        out.push_str(&format!(
+            "{} files or levels couldn't be checked\n",
+            skipped
+        ));
+    }
+    let agreed = rows.len() == 1;
+    if !agreed {
+        out.push('\n');
+        out.push_str(&output::table(&rows));
+    }
+
+    Ok((out, agreed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Algorithm;
+
+    #[test]
+    fn check() {
+        let puzzle = "  x\n...\n.I.\n.R.\n\n  x\n...\nTPT\n.R.";
+
+        for algorithm in crate::algorithm::ALL {
+            assert_eq!(Ok(None), super::check(puzzle, algorithm, Some(5)));
+        }
+        assert_eq!(
+            Ok(Some("found no solution, but there's one in 1".to_string())),
+            super::check("x\nR\n\nx\nP\nR", Algorithm::Bfs, Some(1))
+        );
+        assert_eq!(
+            Ok(Some(
+                "found LURUU in 5 moves, but there's one in 4".to_string()
+            )),
+            super::check(puzzle, Algorithm::Astar, Some(4))
+        );
+    }
+}
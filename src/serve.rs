@@ -0,0 +1,95 @@
+//! Line-delimited JSON co-process protocol
+//!
+//! Each line on stdin is a request like `{"puzzle": "...", "algorithm": "bfs"}`,
+//! answered by a single line of JSON on stdout. An `id` field in the request is
+//! echoed back so callers can match up responses.
+
+use std::io::{BufRead, Write};
+
+use crate::algorithm::{self, Algorithm};
+use crate::json::Json;
+use crate::{Dir, Error, Result, Stats};
+
+/// Solve the puzzle in a single request
+pub fn solve(request: &Json) -> Result<(Vec<Dir>, Stats)> {
+    let algorithm = match request.get("algorithm").map(Json::as_str) {
+        None => algorithm::configured(),
+        Some(Some(name)) => Algorithm::parse(name)?,
+        Some(None) => return Err(Error::UnknownAlgorithm),
+    };
+
+    let puzzle = request
+        .get("puzzle")
+        .and_then(Json::as_str)
+        .ok_or(Error::InvalidJson)?;
+
+    crate::search_with(puzzle, algorithm)
+}
+
+/// Answer a single request line
+fn handle(line: &str) -> Json {
+    let request = Json::parse(line);
+    let id = request
+        .as_ref()
+        .ok()
+        .and_then(|r| r.get("id"))
+        .cloned()
+        .unwrap_or(Json::Null);
+
+    match request.and_then(|r| solve(&r)) {
+        Ok((moves, _)) => Json::object([
+            ("id", id),
+            ("ok", true.into()),
+            ("moves", moves.len().into()),
+            (
+                "solution",
+                moves
+                    .iter()
+                    .map(|d| format!("{:?}", d))
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+        ]),
+        Err(err) => Json::object([
+            ("id", id),
+            ("ok", false.into()),
+            ("error", format!("{:?}", err).into()),
+        ]),
+    }
+}
+
+/// Answer requests from stdin until it closes
+pub fn serve() -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+
+    for line in std::io::stdin().lock().lines() {
+        let line = line.map_err(|e| Error::Io(e.kind()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        writeln!(stdout, "{}", handle(&line)).map_err(|e| Error::Io(e.kind()))?;
+        stdout.flush().map_err(|e| Error::Io(e.kind()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn handle() {
+        assert_eq!(
+            r#"{"id":7,"ok":true,"moves":1,"solution":["Up"]}"#,
+            super::handle(r#"{"id": 7, "puzzle": " x\n.R.\n\n x\n.R."}"#).to_string()
+        );
+        assert_eq!(
+            r#"{"id":null,"ok":false,"error":"UnknownAlgorithm"}"#,
+            super::handle(r#"{"puzzle": "", "algorithm": "bogo"}"#).to_string()
+        );
+        assert_eq!(
+            r#"{"id":null,"ok":false,"error":"InvalidJson"}"#,
+            super::handle("puzzle").to_string()
+        );
+    }
+}
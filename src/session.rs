@@ -0,0 +1,302 @@
+//! Timing play sessions and keeping score across them
+//!
+//! Each level's totals live in a JSON stats file, under the puzzle's hash,
+//! so improvement can be tracked from one session to the next. The fewest
+//! moves and the quickest time a level has been solved in make a personal
+//! leaderboard, shown against the shortest solution when the level comes up
+//! again.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::json::Json;
+use crate::{metadata, metrics, Dir, Error, Result};
+
+/// A single sitting at a puzzle in play mode
+pub struct Session {
+    start: Instant,
+    /// Tries at the puzzle, counting the one in progress
+    attempts: usize,
+    deaths: usize,
+    /// Every move made, with how long it took to decide on
+    moves: Vec<(Dir, Duration)>,
+    /// How many moves the solution took, once there is one
+    solved: Option<usize>,
+    /// How long into the session the puzzle was solved
+    solved_after: Option<Duration>,
+}
+
+impl Session {
+    /// Start timing a session
+    pub fn start() -> Self {
+        Session {
+            start: Instant::now(),
+            attempts: 1,
+            deaths: 0,
+            moves: Vec::new(),
+            solved: None,
+            solved_after: None,
+        }
+    }
+
+    /// Note a move and the time it took
+    pub fn moved(&mut self, dir: Dir, took: Duration) {
+        self.moves.push((dir, took));
+    }
+
+    /// Note a failed attempt, and whether it was a fall into a pit
+    pub fn failed(&mut self, died: bool) {
+        self.attempts += 1;
+        if died {
+            self.deaths += 1;
+        }
+    }
+
+    /// Note that the puzzle was solved in so many moves
+    pub fn solved(&mut self, moves: usize) {
+        self.solved = Some(moves);
+        self.solved_after = Some(self.start.elapsed());
+    }
+
+    /// How the session went, in a few lines
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!(
+            "{} attempt{}, {} death{}, {} moves in {:.0}s",
+            self.attempts,
+            if self.attempts == 1 { "" } else { "s" },
+            self.deaths,
+            if self.deaths == 1 { "" } else { "s" },
+            self.moves.len(),
+            self.start.elapsed().as_secs_f64()
+        )];
+
+        if !self.moves.is_empty() {
+            let total: Duration = self.moves.iter().map(|(_, took)| *took).sum();
+            let (i, (dir, slowest)) = self
+                .moves
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, (_, took))| *took)
+                .unwrap();
+            lines.push(format!(
+                "{:.1}s per move on average, the longest being move {} ({:?}) at {:.1}s",
+                total.as_secs_f64() / self.moves.len() as f64,
+                i + 1,
+                dir,
+                slowest.as_secs_f64()
+            ));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    /// Add the session to the level's totals in the stats, which have to be
+    /// laid out the way they're written
+    fn record(&self, stats: &mut Json, input: &str) -> Result<()> {
+        let (metadata, _) = metadata::split(input);
+        let hash = metrics::hash(input);
+
+        if stats.get("levels").is_none() {
+            stats.set("levels", Json::Object(Vec::new()));
+        }
+        let Some(levels @ Json::Object(_)) = stats.get_mut("levels") else {
+            return Err(Error::InvalidSave);
+        };
+        if levels.get(&hash).is_none() {
+            levels.set(&hash, Json::Object(Vec::new()));
+        }
+        let Some(level @ Json::Object(_)) = levels.get_mut(&hash) else {
+            return Err(Error::InvalidSave);
+        };
+
+        let count = |key| level.get(key).and_then(Json::as_f64).unwrap_or(0.0);
+        let lowest = |key, now: Option<f64>| match (level.get(key).and_then(Json::as_f64), now) {
+            (Some(best), Some(now)) => Some(best.min(now)),
+            (best, now) => best.or(now),
+        };
+        let best = lowest("best", self.solved.map(|m| m as f64));
+        let best_seconds = lowest(
+            "best_seconds",
+            self.solved_after.map(|t| t.as_secs_f64().round()),
+        );
+        let totals = [
+            ("sessions", count("sessions") + 1.0),
+            ("attempts", count("attempts") + self.attempts as f64),
+            ("deaths", count("deaths") + self.deaths as f64),
+            ("solves", count("solves") + self.solved.map_or(0.0, |_| 1.0)),
+            (
+                "seconds",
+                count("seconds") + self.start.elapsed().as_secs_f64().round(),
+            ),
+        ];
+
+        if let Some(title) = metadata::get(&metadata, "title") {
+            level.set("title", title.into());
+        }
+        for (key, value) in totals {
+            level.set(key, value.into());
+        }
+        level.set("best", best.into());
+        level.set("best_seconds", best_seconds.into());
+
+        Ok(())
+    }
+
+    /// Read the stats file, which is empty until the first session is saved
+    fn load(path: &Path) -> Result<Json> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Json::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Json::Object(Vec::new())),
+            Err(e) => Err(Error::Io(e.kind())),
+        }
+    }
+
+    /// Add the session to the stats file, making it if need be, and describe
+    /// the level's totals so far
+    pub fn save(&self, path: &Path, input: &str) -> Result<String> {
+        let mut stats = Session::load(path)?;
+        self.record(&mut stats, input)?;
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| Error::Io(e.kind()))?;
+        }
+        std::fs::write(path, format!("{}\n", stats)).map_err(|e| Error::Io(e.kind()))?;
+
+        let level = stats
+            .get("levels")
+            .and_then(|l| l.get(&metrics::hash(input)))
+            .unwrap();
+        let count = |key| level.get(key).and_then(Json::as_f64).unwrap_or(0.0);
+        let best = match level.get("best").and_then(Json::as_f64) {
+            Some(best) => format!("best solution {} moves", best),
+            None => "not solved yet".to_string(),
+        };
+        Ok(format!(
+            "This level: {} sessions, {} attempts, {} deaths, {}\n",
+            count("sessions"),
+            count("attempts"),
+            count("deaths"),
+            best
+        ))
+    }
+}
+
+/// The best the level has been solved in, set against the shortest solution,
+/// or `None` if it hasn't been solved yet
+pub fn personal_best(stats: &Json, input: &str, optimal: Option<usize>) -> Option<String> {
+    let level = stats.get("levels")?.get(&metrics::hash(input))?;
+    let best = level.get("best").and_then(Json::as_f64)?;
+
+    let mut line = format!("Personal best: {} moves", best);
+    if let Some(seconds) = level.get("best_seconds").and_then(Json::as_f64) {
+        line.push_str(&format!(", fastest solve {}s", seconds));
+    }
+    match optimal {
+        Some(optimal) if best as usize == optimal => line.push_str(" (as short as it gets)"),
+        Some(optimal) => line.push_str(&format!(" (shortest is {})", optimal)),
+        None => {}
+    }
+    Some(line + "\n")
+}
+
+/// The personal best in the stats file, for a level being played again
+pub fn leaderboard(path: &Path, input: &str) -> Result<Option<String>> {
+    let stats = Session::load(path)?;
+    if stats
+        .get("levels")
+        .and_then(|l| l.get(&metrics::hash(input)))
+        .is_none()
+    {
+        return Ok(None);
+    }
+    let optimal = crate::solve_puzzle(input).ok().map(|moves| moves.len());
+    Ok(personal_best(&stats, input, optimal))
+}
+
+/// Where the stats are kept when `--stats-file` doesn't say
+pub fn default_path() -> Option<PathBuf> {
+    let dir = match std::env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".local/share"),
+    };
+    Some(dir.join(env!("CARGO_PKG_NAME")).join("stats.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Session;
+    use crate::json::Json;
+    use crate::Dir::*;
+    use crate::Error;
+
+    const PUZZLE: &str = "title: Two steps\n\n x\nTRT\n\n x\n.R.";
+
+    #[test]
+    fn record() {
+        let mut stats = Json::Object(Vec::new());
+
+        let mut session = Session::start();
+        session.moved(Down, Duration::from_secs(3));
+        session.failed(true);
+        session.record(&mut stats, PUZZLE).unwrap();
+
+        let mut session = Session::start();
+        session.moved(Up, Duration::from_secs(1));
+        session.solved(1);
+        session.record(&mut stats, PUZZLE).unwrap();
+
+        let level = stats
+            .get("levels")
+            .and_then(|l| l.get(&crate::metrics::hash(PUZZLE)))
+            .unwrap();
+        assert_eq!(Some(&Json::from("Two steps")), level.get("title"));
+        assert_eq!(Some(&Json::from(2usize)), level.get("sessions"));
+        assert_eq!(Some(&Json::from(3usize)), level.get("attempts"));
+        assert_eq!(Some(&Json::from(1usize)), level.get("deaths"));
+        assert_eq!(Some(&Json::from(1usize)), level.get("solves"));
+        assert_eq!(Some(&Json::from(1usize)), level.get("best"));
+        assert_eq!(Some(&Json::from(0usize)), level.get("best_seconds"));
+
+        assert_eq!(
+            Some("Personal best: 1 moves, fastest solve 0s (as short as it gets)\n".to_string()),
+            super::personal_best(&stats, PUZZLE, Some(1))
+        );
+        assert_eq!(
+            None,
+            super::personal_best(&stats, " x\n.R.\n\n x\nR..", Some(1))
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let session = Session::start();
+        let hash = crate::metrics::hash(PUZZLE);
+
+        for text in [
+            "[]".to_string(),
+            r#"{"levels":3}"#.to_string(),
+            format!(r#"{{"levels":{{"{}":[]}}}}"#, hash),
+        ] {
+            let mut stats = Json::parse(&text).unwrap();
+            assert_eq!(
+                Err(Error::InvalidSave),
+                session.record(&mut stats, PUZZLE),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn summary() {
+        let mut session = Session::start();
+        session.moved(Up, Duration::from_secs(1));
+        session.moved(Left, Duration::from_secs(5));
+
+        assert!(session
+            .summary()
+            .contains("3.0s per move on average, the longest being move 2 (Left) at 5.0s"));
+    }
+}
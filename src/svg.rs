@@ -0,0 +1,129 @@
+//! Vector images of puzzles, for wiki pages and guides
+
+use std::fmt::Write;
+
+use crate::{Board, Dir, Player, Tile};
+
+/// The size of a tile, in pixels
+const CELL: isize = 32;
+
+/// The fill color for each kind of tile
+fn fill(tile: Tile) -> &'static str {
+    match tile {
+        Tile::None => "#eeeeee",
+        Tile::Wall => "#555555",
+        Tile::Teleport => "#aa44cc",
+        Tile::Pit => "#111111",
+        Tile::Ice => "#bbddff",
+        Tile::Exit => "#33aa33",
+    }
+}
+
+/// The widest row of the board
+fn width(board: &Board) -> isize {
+    board.tiles.iter().map(Vec::len).max().unwrap_or(0) as isize
+}
+
+/// The pixel coordinates of the center of a cell on a board drawn at `left`,
+/// leaving a cell of margin all round for the exit
+fn center(left: isize, cell: Player) -> (isize, isize) {
+    (
+        left + (cell.x + 1) * CELL + CELL / 2,
+        (cell.y + 1) * CELL + CELL / 2,
+    )
+}
+
+/// Draw a tile-sized square centered on the point
+fn square(out: &mut String, (x, y): (isize, isize), fill: &str) {
+    let _ = writeln!(
+        out,
+        r##"  <rect x="{}" y="{}" width="{size}" height="{size}" fill="{}" stroke="#ffffff"/>"##,
+        x - CELL / 2,
+        y - CELL / 2,
+        fill,
+        size = CELL
+    );
+}
+
+/// Draw a board at `left`, with the player's route if there is one
+fn board(out: &mut String, left: isize, board: &Board, start: Player, moves: &[Dir]) {
+    for (y, row) in board.tiles.iter().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            let cell = Player {
+                x: x as isize,
+                y: y as isize,
+            };
+            square(out, center(left, cell), fill(tile));
+        }
+    }
+
+    square(out, center(left, board.exit_cell()), fill(Tile::Exit));
+
+    let mut p = start;
+    for &dir in moves {
+        let cells = crate::animate::path(dir, board, p);
+
+        for pair in cells.windows(2) {
+            let ((x1, y1), (x2, y2)) = (center(left, pair[0]), center(left, pair[1]));
+            // Teleporting jumps across the board rather than walking
+            let dash = match (pair[0].x - pair[1].x).abs() + (pair[0].y - pair[1].y).abs() {
+                1 => "",
+                _ => r#" stroke-dasharray="4 4""#,
+            };
+            let _ = writeln!(
+                out,
+                r##"  <line x1="{}" y1="{}" x2="{}" y2="{}" stroke="#ee8800" stroke-width="4"{}/>"##,
+                x1, y1, x2, y2, dash
+            );
+        }
+        p = *cells.last().unwrap();
+    }
+
+    let (sx, sy) = center(left, start);
+    let _ = writeln!(
+        out,
+        r##"  <circle cx="{}" cy="{}" r="{}" fill="#ffcc00" stroke="#000000"/>"##,
+        sx,
+        sy,
+        CELL / 3
+    );
+}
+
+/// Draw both boards side by side, with the solution's route if there is one
+pub fn puzzle((b1, p1): (&Board, Player), (b2, p2): (&Board, Player), moves: &[Dir]) -> String {
+    // Each board gets a cell of margin on both sides
+    let left2 = (width(b1) + 2) * CELL + CELL;
+    let total_width = left2 + (width(b2) + 2) * CELL;
+    let height = (b1.tiles.len().max(b2.tiles.len()) as isize + 2) * CELL;
+
+    let mut out = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = total_width,
+        h = height
+    );
+    out.push('\n');
+    board(&mut out, 0, b1, p1, moves);
+    board(&mut out, left2, b2, p2, moves);
+    out.push_str("</svg>\n");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Dir::*;
+
+    #[test]
+    fn puzzle() {
+        let ((b1, p1), (b2, p2)) = crate::parse_puzzle(" x\nTRT\n\n x\n.R.").unwrap();
+        let svg = super::puzzle((&b1, p1), (&b2, p2), &[Up]);
+
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="352" height="96""#)
+        );
+        assert_eq!(8, svg.matches("<rect").count());
+        assert_eq!(2, svg.matches("<line").count());
+        assert!(svg.contains(r#"<line x1="80" y1="48" x2="80" y2="16""#));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
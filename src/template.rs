@@ -0,0 +1,216 @@
+//! Parameterized puzzle templates
+//!
+//! A template is a puzzle with variables, declared with defaults as
+//! `param-<name>` metadata. `$N` is replaced by the value of `N`, and
+//! `{text*count}` repeats `text` (which may span lines) `count` times, where
+//! the count is a sum of numbers and variables like `N`, `N+1` or `2*N-1`:
+//!
+//! ```text
+//! param-N: 3
+//! title: Corridor $N
+//!
+//!  x
+//! {.*N}
+//! R{.*N-1}
+//! ```
+
+use crate::cli::Matches;
+use crate::metadata;
+use crate::{Error, Result};
+
+/// The prefix of metadata keys declaring variables
+const PARAM_PREFIX: &str = "param-";
+
+/// The most times a repeat is expanded, well past any board the solver would
+/// take, so a mistyped count can't ask for gigabytes of floor
+const MAX_REPEAT: usize = 1 << 16;
+/// The most a template expands to, in bytes, for the same reason
+const MAX_OUTPUT: usize = 1 << 20;
+
+/// Look up a variable
+fn var(vars: &[(String, i64)], name: &str) -> Result<i64> {
+    vars.iter()
+        .rev()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| *v)
+        .ok_or(Error::InvalidTemplate)
+}
+
+/// Evaluate a repeat count like `2*N-1`
+fn eval(expr: &str, vars: &[(String, i64)]) -> Result<i64> {
+    let mut total: i64 = 0;
+    let mut sign = 1;
+    let mut term = String::new();
+    for c in expr.chars().chain(['+']) {
+        if c == '+' || c == '-' {
+            let value = term
+                .split('*')
+                .map(|factor| match factor.trim() {
+                    f if f.chars().all(|c| c.is_ascii_digit()) && !f.is_empty() => {
+                        f.parse().map_err(|_| Error::InvalidTemplate)
+                    }
+                    f => var(vars, f),
+                })
+                .try_fold(1i64, |product, factor| {
+                    product.checked_mul(factor?).ok_or(Error::InvalidTemplate)
+                })?;
+
+            total = total
+                .checked_add(value.checked_mul(sign).ok_or(Error::InvalidTemplate)?)
+                .ok_or(Error::InvalidTemplate)?;
+            sign = if c == '+' { 1 } else { -1 };
+            term.clear();
+        } else {
+            term.push(c);
+        }
+    }
+
+    Ok(total)
+}
+
+/// Expand the `{text*count}` repeats
+fn expand_repeats(body: &str, vars: &[(String, i64)]) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = body;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+
+        let end = rest[start..].find('}').ok_or(Error::InvalidTemplate)? + start;
+        let inner = &rest[start + 1..end];
+        let (text, count) = inner.split_once('*').ok_or(Error::InvalidTemplate)?;
+        if text.contains('{') {
+            return Err(Error::InvalidTemplate);
+        }
+
+        let count = usize::try_from(eval(count, vars)?)
+            .ok()
+            .filter(|&n| n <= MAX_REPEAT)
+            .ok_or(Error::InvalidTemplate)?;
+        if out.len() + text.len() * count > MAX_OUTPUT {
+            return Err(Error::InvalidTemplate);
+        }
+        out.push_str(&text.repeat(count));
+
+        rest = &rest[end + 1..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Replace each `$name` with the variable's value
+fn substitute(text: &str, vars: &[(String, i64)]) -> Result<String> {
+    let mut out = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+            name.push(c);
+        }
+        out.push_str(&var(vars, &name)?.to_string());
+    }
+
+    Ok(out)
+}
+
+/// Instantiate a template, with the given values overriding the defaults
+pub fn render(input: &str, overrides: &[(String, i64)]) -> Result<String> {
+    let (metadata, body) = metadata::split(input);
+
+    let mut vars = Vec::new();
+    for (key, value) in &metadata {
+        if let Some(name) = key.strip_prefix(PARAM_PREFIX) {
+            let value = value.parse().map_err(|_| Error::InvalidTemplate)?;
+            vars.push((name.to_string(), value));
+        }
+    }
+    vars.extend_from_slice(overrides);
+
+    let mut out = String::new();
+    for (key, value) in &metadata {
+        if !key.starts_with(PARAM_PREFIX) {
+            out.push_str(&format!("{}: {}\n", key, substitute(value, &vars)?));
+        }
+    }
+    if !out.is_empty() {
+        out.push('\n');
+    }
+
+    out.push_str(&substitute(&expand_repeats(body, &vars)?, &vars)?);
+    Ok(out)
+}
+
+/// Parse the `--set NAME=VALUE` overrides from the command line
+pub fn parse_overrides(matches: &Matches) -> Result<Vec<(String, i64)>> {
+    matches
+        .values("set")
+        .map(|set| {
+            let (name, value) = set.split_once('=').ok_or(Error::InvalidTemplate)?;
+            let value = value.parse().map_err(|_| Error::InvalidTemplate)?;
+            Ok((name.to_string(), value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    const CORRIDOR: &str = "param-N: 3
+title: Corridor $N
+
+ x
+{.*N}
+R{.*N-1}
+{W*2*N-N}
+
+ x
+...
+R..";
+
+    #[test]
+    fn render() {
+        assert_eq!(
+            Ok("title: Corridor 3\n\n x\n...\nR..\nWWW\n\n x\n...\nR..".to_string()),
+            super::render(CORRIDOR, &[])
+        );
+        assert_eq!(
+            Ok("title: Corridor 5\n\n x\n.....\nR....\nWWWWW\n\n x\n...\nR..".to_string()),
+            super::render(CORRIDOR, &[("N".to_string(), 5)])
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Err(Error::InvalidTemplate),
+            super::render(CORRIDOR, &[("N".to_string(), 0)])
+        );
+        assert_eq!(Err(Error::InvalidTemplate), super::render(" x\n{.*M}", &[]));
+    }
+
+    #[test]
+    fn too_big() {
+        let n = [("N".to_string(), 100_000)];
+
+        assert_eq!(
+            Err(Error::InvalidTemplate),
+            super::render(" x\n{.*N*N*N*N*N}", &n)
+        );
+        assert_eq!(
+            Err(Error::InvalidTemplate),
+            super::render(" x\n{.*N+9223372036854775807}", &n)
+        );
+        assert_eq!(Err(Error::InvalidTemplate), super::render(" x\n{.*N}", &n));
+        // Each repeat is small enough, but not all of them together
+        let many = format!(" x\n{}", "{.......*60000}".repeat(3));
+        assert_eq!(Err(Error::InvalidTemplate), super::render(&many, &[]));
+    }
+}
@@ -0,0 +1,130 @@
+//! Full-screen terminal drawing and key input
+//!
+//! Raw mode goes through `stty`, the same way the clipboard goes through the
+//! platform's own tools, so there's nothing to link against.
+
+use std::io::{IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+use crate::{Error, Result};
+
+/// A key press
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Tab,
+    Backspace,
+    Esc,
+    Char(char),
+}
+
+/// Make sense of the bytes from a single read of a raw terminal
+fn decode(bytes: &[u8]) -> Option<Key> {
+    match bytes {
+        [0x1b, b'[', b'A'] | [0x1b, b'O', b'A'] => Some(Key::Up),
+        [0x1b, b'[', b'B'] | [0x1b, b'O', b'B'] => Some(Key::Down),
+        [0x1b, b'[', b'C'] | [0x1b, b'O', b'C'] => Some(Key::Right),
+        [0x1b, b'[', b'D'] | [0x1b, b'O', b'D'] => Some(Key::Left),
+        [0x1b] => Some(Key::Esc),
+        [b'\r'] | [b'\n'] => Some(Key::Enter),
+        [b'\t'] => Some(Key::Tab),
+        [0x7f] | [0x08] => Some(Key::Backspace),
+        // Ctrl-C doesn't interrupt in raw mode, so it has to quit by hand
+        [0x03] => Some(Key::Char('q')),
+        _ => std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|s| s.chars().next())
+            .filter(|c| !c.is_control())
+            .map(Key::Char),
+    }
+}
+
+/// Run `stty` on the terminal
+fn stty(args: &[&str]) -> Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .map_err(|_| Error::NotATerminal)?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(Error::NotATerminal)
+    }
+}
+
+/// The terminal in raw mode on the alternate screen, restored when dropped
+pub struct Screen {
+    /// The terminal settings to go back to
+    saved: String,
+}
+
+impl Screen {
+    /// Take over the terminal
+    pub fn enter() -> Result<Self> {
+        if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+            return Err(Error::NotATerminal);
+        }
+
+        let saved = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+
+        let screen = Screen { saved };
+        print!("\x1b[?1049h\x1b[?25l");
+        Ok(screen)
+    }
+
+    /// Replace everything on the screen with the text
+    pub fn draw(&mut self, text: &str) -> Result<()> {
+        let mut stdout = std::io::stdout().lock();
+
+        // Raw mode doesn't return the cursor to the start of the line
+        write!(stdout, "\x1b[2J\x1b[H{}", text.replace('\n', "\r\n"))
+            .and_then(|_| stdout.flush())
+            .map_err(|e| Error::Io(e.kind()))
+    }
+
+    /// Wait for the next key press, returning `None` when input closes
+    pub fn key(&mut self) -> Result<Option<Key>> {
+        let mut buf = [0; 8];
+
+        loop {
+            let read = std::io::stdin()
+                .read(&mut buf)
+                .map_err(|e| Error::Io(e.kind()))?;
+            if read == 0 {
+                return Ok(None);
+            }
+            if let Some(key) = decode(&buf[..read]) {
+                return Ok(Some(key));
+            }
+        }
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = std::io::stdout().flush();
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Key;
+
+    #[test]
+    fn decode() {
+        assert_eq!(Some(Key::Up), super::decode(b"\x1b[A"));
+        assert_eq!(Some(Key::Left), super::decode(b"\x1bOD"));
+        assert_eq!(Some(Key::Char('n')), super::decode(b"n"));
+        assert_eq!(Some(Key::Enter), super::decode(b"\r"));
+        assert_eq!(None, super::decode(b"\x1b[15~"));
+    }
+}
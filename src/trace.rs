@@ -0,0 +1,549 @@
+//! Recording every move the search tries, with `--trace-out FILE`
+//!
+//! Narration is too slow and too bulky for a search that expands millions of
+//! positions, so the trace is binary: an 8-byte magic, then one 26-byte
+//! record per move tried, every number little-endian:
+//!
+//! | bytes | what                                             |
+//! | ----- | ------------------------------------------------ |
+//! | 8     | microseconds since the search started            |
+//! | 8     | where the players were, as x1, y1, x2, y2 (i16s) |
+//! | 1     | the move: 0 up, 1 down, 2 right, 3 left          |
+//! | 1     | what happened, see [`Outcome`]                   |
+//! | 8     | where the players ended up, the same way         |
+//!
+//! A move that wins or loses the game records where the players were instead.
+//!
+//! Only breadth-first search knows when a position was seen before; the
+//! other algorithms record every move that stays on the boards as `Moved`.
+//!
+//! `trace view` browses a trace as a tree, each position under the move that
+//! first reached it, with a timeline of how fast the search went along the
+//! top. `trace timeline` prints just the timeline and the totals.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::cli::Matches;
+use crate::term::{Key, Screen};
+use crate::{Dir, Error, Player, Result};
+
+/// The first bytes of every trace
+pub const MAGIC: &[u8; 8] = b"HMTRACE1";
+
+/// The size of a record
+pub const RECORD: usize = 26;
+
+/// The trace being written, and when the search started
+static FILE: Mutex<Option<(BufWriter<File>, Instant)>> = Mutex::new(None);
+/// Whether [`FILE`] is open, checked without taking the lock
+static TRACING: AtomicBool = AtomicBool::new(false);
+
+/// What a move did
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    /// The players moved to a position not seen before
+    Moved = 0,
+    /// The players moved back to a position already seen
+    Revisited = 1,
+    Solved = 2,
+    Fell = 3,
+    ExitedAlone = 4,
+}
+
+impl Outcome {
+    fn name(self) -> &'static str {
+        match self {
+            Outcome::Moved => "moved",
+            Outcome::Revisited => "revisited",
+            Outcome::Solved => "solved",
+            Outcome::Fell => "fell",
+            Outcome::ExitedAlone => "exited alone",
+        }
+    }
+}
+
+/// One move the search tried
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Record {
+    pub micros: u64,
+    pub from: (Player, Player),
+    pub dir: Dir,
+    pub outcome: Outcome,
+    /// Where the players ended up, or where they were for moves that ended
+    /// the game
+    pub to: (Player, Player),
+}
+
+impl Record {
+    /// The record's bytes in the trace
+    pub fn encode(&self) -> [u8; RECORD] {
+        let mut bytes = [0; RECORD];
+        bytes[..8].copy_from_slice(&self.micros.to_le_bytes());
+        bytes[8..16].copy_from_slice(&position(self.from));
+        bytes[16] = match self.dir {
+            Dir::Up => 0,
+            Dir::Down => 1,
+            Dir::Right => 2,
+            Dir::Left => 3,
+        };
+        bytes[17] = self.outcome as u8;
+        bytes[18..].copy_from_slice(&position(self.to));
+        bytes
+    }
+}
+
+impl Record {
+    /// Read a record back from its bytes
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        let number = |i: usize| i16::from_le_bytes([bytes[i], bytes[i + 1]]) as isize;
+        let position = |i| {
+            (
+                Player {
+                    x: number(i),
+                    y: number(i + 2),
+                },
+                Player {
+                    x: number(i + 4),
+                    y: number(i + 6),
+                },
+            )
+        };
+
+        Ok(Record {
+            micros: u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            from: position(8),
+            dir: match bytes[16] {
+                0 => Dir::Up,
+                1 => Dir::Down,
+                2 => Dir::Right,
+                3 => Dir::Left,
+                _ => return Err(Error::InvalidTrace),
+            },
+            outcome: match bytes[17] {
+                0 => Outcome::Moved,
+                1 => Outcome::Revisited,
+                2 => Outcome::Solved,
+                3 => Outcome::Fell,
+                4 => Outcome::ExitedAlone,
+                _ => return Err(Error::InvalidTrace),
+            },
+            to: position(18),
+        })
+    }
+}
+
+/// Read every record out of a trace
+pub fn read(bytes: &[u8]) -> Result<Vec<Record>> {
+    let records = bytes.strip_prefix(MAGIC).ok_or(Error::InvalidTrace)?;
+    if records.len() % RECORD != 0 {
+        return Err(Error::InvalidTrace);
+    }
+    records.chunks(RECORD).map(Record::decode).collect()
+}
+
+/// Both players' coordinates as 16-bit numbers
+fn position((p1, p2): (Player, Player)) -> [u8; 8] {
+    let mut bytes = [0; 8];
+    for (i, n) in [p1.x, p1.y, p2.x, p2.y].into_iter().enumerate() {
+        bytes[i * 2..i * 2 + 2].copy_from_slice(&(n as i16).to_le_bytes());
+    }
+    bytes
+}
+
+/// Start the trace from `--trace-out`, replacing anything already there
+pub fn configure(matches: &Matches) -> Result<()> {
+    if let Some(path) = matches.value("trace-out") {
+        let mut file = BufWriter::new(File::create(path).map_err(|e| Error::Io(e.kind()))?);
+        file.write_all(MAGIC).map_err(|e| Error::Io(e.kind()))?;
+        *FILE.lock().unwrap() = Some((file, Instant::now()));
+        TRACING.store(true, Ordering::Relaxed);
+    }
+
+    Ok(())
+}
+
+/// Record a move the search tried, if there's a trace
+pub fn record(from: (Player, Player), dir: Dir, outcome: Outcome, to: (Player, Player)) {
+    if !TRACING.load(Ordering::Relaxed) {
+        return;
+    }
+
+    if let Some((file, start)) = FILE.lock().unwrap().as_mut() {
+        let record = Record {
+            micros: start.elapsed().as_micros() as u64,
+            from,
+            dir,
+            outcome,
+            to,
+        };
+        // A trace that can't be written shouldn't stop the search
+        let _ = file.write_all(&record.encode());
+    }
+}
+
+/// Write out whatever of the trace is still buffered
+pub fn flush() -> Result<()> {
+    if let Some((file, _)) = FILE.lock().unwrap().as_mut() {
+        file.flush().map_err(|e| Error::Io(e.kind()))?;
+    }
+    Ok(())
+}
+
+/// A move in the tree, under the position it was made from
+struct Node {
+    record: Record,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// How many moves there are in the subtree, this one included
+    size: usize,
+}
+
+/// The moves of a trace as a tree. A position's moves go under the move
+/// that first reached it, and the start's moves have no parent.
+struct Tree {
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+}
+
+impl Tree {
+    fn build(records: Vec<Record>) -> Self {
+        let mut reached_by: HashMap<(Player, Player), usize> = HashMap::new();
+        let mut tree = Tree {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        };
+
+        for record in records {
+            let i = tree.nodes.len();
+            let parent = reached_by.get(&record.from).copied();
+            match parent {
+                Some(parent) => tree.nodes[parent].children.push(i),
+                None => tree.roots.push(i),
+            }
+            if record.outcome == Outcome::Moved && record.to != record.from {
+                reached_by.entry(record.to).or_insert(i);
+            }
+            tree.nodes.push(Node {
+                record,
+                parent,
+                children: Vec::new(),
+                size: 1,
+            });
+        }
+
+        // Children always come after their parents
+        for i in (0..tree.nodes.len()).rev() {
+            if let Some(parent) = tree.nodes[i].parent {
+                tree.nodes[parent].size += tree.nodes[i].size;
+            }
+        }
+
+        tree
+    }
+}
+
+/// The levels of the timeline's bars
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// How many columns the timeline takes
+const TIMELINE_WIDTH: usize = 60;
+
+/// A bar per slice of the search's time, as high as the moves tried in it,
+/// with the time at each end
+fn timeline(records: &[Record]) -> String {
+    // A hand-edited trace needn't be in order, or in any sane range
+    let end = records.iter().map(|r| r.micros).max().unwrap_or(0);
+    let mut counts = [0usize; TIMELINE_WIDTH];
+    for record in records {
+        let slice = record.micros as u128 * TIMELINE_WIDTH as u128 / (end as u128 + 1);
+        counts[(slice as usize).min(TIMELINE_WIDTH - 1)] += 1;
+    }
+    let busiest = counts.iter().copied().max().unwrap_or(0).max(1);
+
+    let bars: String = counts
+        .iter()
+        .map(|&n| match n {
+            0 => ' ',
+            n => BARS[(n * BARS.len()).div_ceil(busiest) - 1],
+        })
+        .collect();
+    format!("0ms {} {:.1}ms", bars, end as f64 / 1000.0)
+}
+
+/// How many moves ended each way
+fn totals(records: &[Record]) -> String {
+    let outcomes = [
+        Outcome::Moved,
+        Outcome::Revisited,
+        Outcome::Solved,
+        Outcome::Fell,
+        Outcome::ExitedAlone,
+    ];
+    let parts: Vec<String> = outcomes
+        .into_iter()
+        .map(|o| {
+            let n = records.iter().filter(|r| r.outcome == o).count();
+            format!("{} {}", n, o.name())
+        })
+        .collect();
+    format!("{} moves: {}", records.len(), parts.join(", "))
+}
+
+/// The tree being browsed
+struct Viewer {
+    tree: Tree,
+    header: String,
+    expanded: Vec<bool>,
+    /// The node under the cursor
+    selected: usize,
+}
+
+/// How many rows of the tree fit on the screen
+const ROWS: usize = 30;
+
+/// The key bindings, shown at the bottom of the screen
+const HELP: &str = "up/down move · right/enter open · left close · q quit";
+
+impl Viewer {
+    fn new(records: Vec<Record>) -> Result<Self> {
+        if records.is_empty() {
+            return Err(Error::InvalidTrace);
+        }
+        let header = format!("{}\n{}", timeline(&records), totals(&records));
+        let tree = Tree::build(records);
+
+        Ok(Viewer {
+            expanded: vec![false; tree.nodes.len()],
+            selected: tree.roots[0],
+            tree,
+            header,
+        })
+    }
+
+    /// The nodes showing, with how deep each one is, in order
+    fn visible(&self) -> Vec<(usize, usize)> {
+        let mut rows = Vec::new();
+        let mut stack: Vec<(usize, usize)> =
+            self.tree.roots.iter().rev().map(|&i| (i, 0)).collect();
+        while let Some((i, depth)) = stack.pop() {
+            rows.push((i, depth));
+            if self.expanded[i] {
+                stack.extend(
+                    self.tree.nodes[i]
+                        .children
+                        .iter()
+                        .rev()
+                        .map(|&c| (c, depth + 1)),
+                );
+            }
+        }
+        rows
+    }
+
+    /// A line for a move in the tree
+    fn row(&self, i: usize, depth: usize) -> String {
+        let node = &self.tree.nodes[i];
+        let record = &node.record;
+        let marker = match (node.children.is_empty(), self.expanded[i]) {
+            (true, _) => '·',
+            (false, true) => '▾',
+            (false, false) => '▸',
+        };
+        let ((x1, y1), (x2, y2)) = (
+            (record.to.0.x, record.to.0.y),
+            (record.to.1.x, record.to.1.y),
+        );
+        let below = match node.size {
+            1 => String::new(),
+            n => format!(", {} below", n - 1),
+        };
+
+        format!(
+            "{}{} {} → ({}, {}) ({}, {})  {} at {:.1}ms{}",
+            "  ".repeat(depth),
+            marker,
+            record.dir.to_char(),
+            x1,
+            y1,
+            x2,
+            y2,
+            record.outcome.name(),
+            record.micros as f64 / 1000.0,
+            below
+        )
+    }
+
+    fn screen(&self) -> String {
+        let rows = self.visible();
+        let at = rows
+            .iter()
+            .position(|&(i, _)| i == self.selected)
+            .unwrap_or(0);
+        let first = at
+            .saturating_sub(ROWS / 2)
+            .min(rows.len().saturating_sub(ROWS));
+
+        let lines: Vec<String> = rows
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(ROWS)
+            .map(|(n, &(i, depth))| match n == at {
+                true => format!("\x1b[7m{}\x1b[0m", self.row(i, depth)),
+                false => self.row(i, depth),
+            })
+            .collect();
+
+        format!("{}\n\n{}\n\n{}\n", self.header, lines.join("\n"), HELP)
+    }
+
+    /// Handle a key press, returning whether to carry on
+    fn key(&mut self, key: Key) -> bool {
+        let rows = self.visible();
+        let at = rows
+            .iter()
+            .position(|&(i, _)| i == self.selected)
+            .unwrap_or(0);
+
+        match key {
+            Key::Up if at > 0 => self.selected = rows[at - 1].0,
+            Key::Down if at + 1 < rows.len() => self.selected = rows[at + 1].0,
+            Key::Right | Key::Enter => self.expanded[self.selected] = true,
+            Key::Left if self.expanded[self.selected] => self.expanded[self.selected] = false,
+            // Closing a closed move goes up to the one it came from
+            Key::Left => {
+                if let Some(parent) = self.tree.nodes[self.selected].parent {
+                    self.selected = parent;
+                }
+            }
+            Key::Char('q') | Key::Esc => return false,
+            _ => {}
+        }
+        true
+    }
+}
+
+/// Browse or summarize a trace, as `trace <ACTION> <FILE>` asks
+pub fn run(action: &str, path: &str) -> Result<()> {
+    let bytes = std::fs::read(path).map_err(|e| Error::Io(e.kind()))?;
+    let records = read(&bytes)?;
+
+    match action {
+        "timeline" => {
+            println!("{}\n{}", timeline(&records), totals(&records));
+            Ok(())
+        }
+        "view" => {
+            let mut viewer = Viewer::new(records)?;
+            let mut screen = Screen::enter()?;
+            loop {
+                screen.draw(&viewer.screen())?;
+                match screen.key()? {
+                    Some(key) if viewer.key(key) => {}
+                    _ => return Ok(()),
+                }
+            }
+        }
+        _ => Err(Error::Usage("expected view or timeline".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Outcome, Record, Viewer};
+    use crate::term::Key;
+    use crate::{Dir, Player};
+
+    #[test]
+    fn encode() {
+        let at = |x, y| Player { x, y };
+        let record = Record {
+            micros: 300,
+            from: (at(1, 2), at(0, 0)),
+            dir: Dir::Left,
+            outcome: Outcome::Moved,
+            to: (at(0, 2), at(0, 0)),
+        };
+
+        assert_eq!(
+            [
+                44, 1, 0, 0, 0, 0, 0, 0, // 300µs
+                1, 0, 2, 0, 0, 0, 0, 0, // (1, 2) and (0, 0)
+                3, 0, // left, moved
+                0, 0, 2, 0, 0, 0, 0, 0, // (0, 2) and (0, 0)
+            ],
+            record.encode()
+        );
+    }
+
+    #[test]
+    fn view() {
+        let at = |x| (Player { x, y: 0 }, Player { x, y: 0 });
+        let record = |micros, from, dir, outcome, to| Record {
+            micros,
+            from: at(from),
+            dir,
+            outcome,
+            to: at(to),
+        };
+        let records = vec![
+            record(0, 1, Dir::Left, Outcome::Moved, 0),
+            record(10, 1, Dir::Right, Outcome::Moved, 2),
+            record(20, 0, Dir::Left, Outcome::Fell, 0),
+            record(30, 2, Dir::Left, Outcome::Revisited, 1),
+            record(1500, 2, Dir::Up, Outcome::Solved, 2),
+        ];
+        let bytes: Vec<u8> = super::MAGIC
+            .iter()
+            .copied()
+            .chain(records.iter().flat_map(Record::encode))
+            .collect();
+        assert_eq!(records, super::read(&bytes).unwrap());
+        assert!(super::read(&bytes[..30]).is_err());
+
+        let mut viewer = Viewer::new(records).unwrap();
+        assert_eq!(
+            "0ms █▃                                                         ▃ 1.5ms",
+            viewer.header.lines().next().unwrap()
+        );
+        viewer.key(Key::Down);
+        viewer.key(Key::Right);
+        let rows: Vec<String> = viewer
+            .visible()
+            .into_iter()
+            .map(|(i, d)| viewer.row(i, d))
+            .collect();
+        assert_eq!(
+            vec![
+                "▸ L → (0, 0) (0, 0)  moved at 0.0ms, 1 below",
+                "▾ R → (2, 0) (2, 0)  moved at 0.0ms, 2 below",
+                "  · L → (1, 0) (1, 0)  revisited at 0.0ms",
+                "  · U → (2, 0) (2, 0)  solved at 1.5ms",
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn out_of_order() {
+        let at = (Player { x: 0, y: 0 }, Player { x: 0, y: 0 });
+        let record = |micros| Record {
+            micros,
+            from: at,
+            dir: Dir::Up,
+            outcome: Outcome::Fell,
+            to: at,
+        };
+
+        let viewer = Viewer::new(vec![record(u64::MAX), record(2000), record(0)]).unwrap();
+        assert_eq!(
+            format!("0ms █{}▄ {:.1}ms", " ".repeat(58), u64::MAX as f64 / 1000.0),
+            viewer.header.lines().next().unwrap()
+        );
+    }
+}
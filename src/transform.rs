@@ -0,0 +1,183 @@
+//! Rotating and mirroring boards
+
+use crate::cli::Matches;
+use crate::{Board, Error, Exit, Player, Result, Side, Tile};
+
+/// A single geometric transformation of a board
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transform {
+    /// Rotate a quarter turn clockwise
+    Rotate,
+    /// Mirror left to right
+    MirrorH,
+    /// Mirror top to bottom
+    MirrorV,
+    /// Swap rows and columns
+    Transpose,
+}
+
+impl Transform {
+    /// Parse the transformations given on the command line, in order
+    pub fn parse_args(matches: &Matches) -> Result<Vec<Self>> {
+        let mut transforms = Vec::new();
+
+        for (flag, value) in &matches.flags {
+            match *flag {
+                "rotate" => {
+                    let turns = match value.as_deref() {
+                        Some("90") => 1,
+                        Some("180") => 2,
+                        Some("270") => 3,
+                        _ => return Err(Error::InvalidTransform),
+                    };
+                    transforms.extend(std::iter::repeat_n(Self::Rotate, turns));
+                }
+                "mirror-h" => transforms.push(Self::MirrorH),
+                "mirror-v" => transforms.push(Self::MirrorV),
+                "transpose" => transforms.push(Self::Transpose),
+                _ => {}
+            }
+        }
+
+        Ok(transforms)
+    }
+
+    /// Where a position on a board of the given size ends up
+    fn map(self, (x, y): (isize, isize), (w, h): (isize, isize)) -> (isize, isize) {
+        match self {
+            Self::Rotate => (h - 1 - y, x),
+            Self::MirrorH => (w - 1 - x, y),
+            Self::MirrorV => (x, h - 1 - y),
+            Self::Transpose => (y, x),
+        }
+    }
+
+    /// The size of a board of the given size after the transformation
+    fn size(self, (w, h): (isize, isize)) -> (isize, isize) {
+        match self {
+            Self::Rotate | Self::Transpose => (h, w),
+            Self::MirrorH | Self::MirrorV => (w, h),
+        }
+    }
+
+    /// Transform a board along with the player on it
+    ///
+    /// Ragged rows are padded with walls first, which is how the ends of short
+    /// rows behave anyway. An exit on the right of a short row sits inside the
+    /// padded board, and can only be written down again if it ends up at the
+    /// end of a row with nothing but walls after it, so the row can be cut
+    /// short there. Otherwise the board can't be transformed.
+    pub fn apply(self, board: &Board, player: Player) -> Result<(Board, Player)> {
+        let w = board.tiles.iter().map(Vec::len).max().unwrap_or(0) as isize;
+        let h = board.tiles.len() as isize;
+        let (new_w, new_h) = self.size((w, h));
+
+        let mut tiles = vec![vec![Tile::Wall; new_w as usize]; new_h as usize];
+        for (y, row) in board.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let (nx, ny) = self.map((x as isize, y as isize), (w, h));
+                tiles[ny as usize][nx as usize] = *tile;
+            }
+        }
+
+        let cell = board.exit_cell();
+        let (ex, ey) = self.map((cell.x, cell.y), (w, h));
+        // An exit that isn't next to the board lands somewhere it can't be
+        // written down, or indexed
+        let on_x = (0..new_w).contains(&ex);
+        let on_y = (0..new_h).contains(&ey);
+        let (side, pos) = if on_x && ey == -1 {
+            (Side::Top, ex)
+        } else if on_x && ey == new_h {
+            (Side::Bottom, ex)
+        } else if on_y && ex == -1 {
+            (Side::Left, ey)
+        } else if on_y && ex == new_w {
+            (Side::Right, ey)
+        } else if on_x && on_y {
+            let row = &mut tiles[ey as usize];
+            if !row[ex as usize..].iter().all(|t| matches!(t, Tile::Wall)) {
+                return Err(Error::InvalidTransform);
+            }
+            row.truncate(ex as usize);
+            (Side::Right, ey)
+        } else {
+            return Err(Error::InvalidTransform);
+        };
+        let exit = Exit {
+            side,
+            pos: pos as usize,
+        };
+
+        let (x, y) = self.map((player.x, player.y), (w, h));
+
+        Ok((Board { tiles, exit }, Player { x, y }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transform;
+    use crate::Dir::*;
+
+    const PUZZLE: &str = " x\n...\n...\n.R.\n\n x\n...\n...\n..R";
+
+    /// Transform both boards of the test puzzle and write it back out
+    fn transform(transforms: &[Transform]) -> String {
+        let (mut board1, mut board2) = crate::parse_puzzle(PUZZLE).unwrap();
+
+        for t in transforms {
+            board1 = t.apply(&board1.0, board1.1).unwrap();
+            board2 = t.apply(&board2.0, board2.1).unwrap();
+        }
+
+        crate::fmt::write(vec![], [&board1, &board2])
+    }
+
+    #[test]
+    fn rotate() {
+        let rotated = transform(&[Transform::Rotate]);
+
+        assert_eq!(">1\n...\nR..\n...\n\n>1\n...\n...\nR..\n", rotated);
+        assert_eq!(
+            Ok(vec![Down, Up, Right, Right, Right]),
+            crate::solve_puzzle(&rotated)
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        use Transform::*;
+
+        assert_eq!(transform(&[]), transform(&[Rotate; 4]));
+        assert_eq!(transform(&[]), transform(&[MirrorH, MirrorH]));
+        assert_eq!(transform(&[Transpose]), transform(&[Rotate, MirrorH]));
+        assert_eq!(transform(&[MirrorV]), transform(&[Rotate, Rotate, MirrorH]));
+    }
+
+    #[test]
+    fn ragged_right_exit() {
+        use Transform::*;
+
+        let ((board, player), _) = crate::parse_puzzle(">1\n...\n.R\n\n>1\n...\n.R").unwrap();
+        let transposed = Transpose.apply(&board, player).unwrap();
+        let puzzle = crate::fmt::write(vec![], [&transposed, &transposed]);
+
+        assert_eq!(">2\n..\n.R\n.\n\n>2\n..\n.R\n.\n", puzzle);
+        assert_eq!(Ok(vec![Down]), crate::solve_puzzle(&puzzle));
+        // The exit would be in the middle of a row of floor
+        assert!(MirrorH.apply(&board, player).is_err());
+        assert!(Rotate.apply(&board, player).is_err());
+    }
+
+    #[test]
+    fn exit_off_board() {
+        use Transform::*;
+
+        let ((board, player), _) = crate::parse_puzzle(">5\n...\n.R.\n\n x\n.R.").unwrap();
+        assert!(Rotate.apply(&board, player).is_err());
+
+        let ((board, player), _) = crate::parse_puzzle("          x\n...\n.R.\n\n x\n.R.").unwrap();
+        assert!(MirrorH.apply(&board, player).is_err());
+    }
+}
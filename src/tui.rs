@@ -0,0 +1,192 @@
+//! A full-screen dashboard for working through a puzzle
+//!
+//! Shows both boards, the moves made so far, and the solver's take on the
+//! current position, updated after every key press.
+
+use std::time::{Duration, Instant};
+
+use crate::term::{Key, Screen};
+use crate::verify::{self, Step};
+use crate::{metadata, Board, Dir, Player, Result, Stats, Turn};
+
+/// The key bindings, shown at the bottom of the screen
+const HELP: &str = "arrows/wasd move · n next move · z undo · r restart · q quit";
+
+/// What the solver says about the current position
+type Analysis = Result<(Vec<Dir>, Stats, Duration)>;
+
+struct Dashboard {
+    title: String,
+    b1: Board,
+    b2: Board,
+    /// Both players' positions after each move, starting with the start
+    history: Vec<(Player, Player)>,
+    moves: Vec<Dir>,
+    analysis: Analysis,
+    message: String,
+}
+
+impl Dashboard {
+    /// Start at the beginning of the puzzle
+    fn new(input: &str) -> Result<Self> {
+        let (metadata, _) = metadata::split(input);
+        let ((b1, p1), (b2, p2)) = crate::parse_puzzle(input)?;
+        crate::limits::check(&b1)?;
+        crate::limits::check(&b2)?;
+
+        let mut dashboard = Dashboard {
+            title: metadata::get(&metadata, "title")
+                .unwrap_or("Untitled puzzle")
+                .to_string(),
+            b1,
+            b2,
+            history: vec![(p1, p2)],
+            moves: Vec::new(),
+            analysis: Ok(Default::default()),
+            message: String::new(),
+        };
+        dashboard.analyze();
+        Ok(dashboard)
+    }
+
+    /// Solve the puzzle from the current position
+    fn analyze(&mut self) {
+        let &(p1, p2) = self.history.last().unwrap();
+        let mut stats = Stats::default();
+        let start = Instant::now();
+
+        self.analysis = crate::solve(
+            vec![Turn::new(&self.b1, p1, &self.b2, p2)],
+            &mut stats,
+            crate::limits::deadline(),
+            &mut |_| {},
+        )
+        .map(|solution| (solution, stats, start.elapsed()));
+    }
+
+    /// Make a move, unless it ends the game
+    fn go(&mut self, dir: Dir) {
+        let &(p1, p2) = self.history.last().unwrap();
+
+        self.message = match verify::step(dir, (&self.b1, p1), (&self.b2, p2)) {
+            Step::Solved => format!("Solved in {} moves!", self.moves.len() + 1),
+            Step::Died(player) => format!("{:?} would drop player {} into a pit", dir, player),
+            Step::ExitedAlone(player) => {
+                format!("{:?} would take only player {} out", dir, player)
+            }
+            Step::Moved(n1, n2) => {
+                self.history.push((n1, n2));
+                self.moves.push(dir);
+                self.analyze();
+                String::new()
+            }
+        };
+    }
+
+    /// Handle a key press, returning false to quit
+    fn key(&mut self, key: Key) -> bool {
+        match key {
+            Key::Up | Key::Char('w') => self.go(Dir::Up),
+            Key::Down | Key::Char('s') => self.go(Dir::Down),
+            Key::Left | Key::Char('a') => self.go(Dir::Left),
+            Key::Right | Key::Char('d') => self.go(Dir::Right),
+            Key::Char('n') => match &self.analysis {
+                Ok((solution, _, _)) => self.go(solution[0]),
+                Err(_) => self.message = "There's no way out from here".to_string(),
+            },
+            Key::Char('z') if self.moves.is_empty() => {
+                self.message = "Nothing to undo".to_string();
+            }
+            Key::Char('z') => {
+                self.history.pop();
+                self.moves.pop();
+                self.message.clear();
+                self.analyze();
+            }
+            Key::Char('r') => {
+                self.history.truncate(1);
+                self.moves.clear();
+                self.message.clear();
+                self.analyze();
+            }
+            Key::Char('q') | Key::Esc => return false,
+            _ => {}
+        }
+
+        true
+    }
+
+    /// Lay out the whole screen
+    fn screen(&self) -> String {
+        let &(p1, p2) = self.history.last().unwrap();
+        let moves = self
+            .moves
+            .iter()
+            .map(|d| format!("{:?}", d))
+            .collect::<Vec<_>>();
+
+        let analysis = match &self.analysis {
+            Ok((solution, stats, elapsed)) => format!(
+                "Solver: {} paths, depth {}, {:.1} ms\nSolution from here ({} moves):\n{}",
+                stats.paths,
+                stats.depth,
+                elapsed.as_secs_f64() * 1000.0,
+                solution.len(),
+                solution
+                    .iter()
+                    .enumerate()
+                    .map(|(i, d)| format!("  {:>3}. {:?}", self.moves.len() + i + 1, d))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            Err(err) => format!("Solver: {:?}", err),
+        };
+
+        format!(
+            "{}\n\n{}\nMoves ({}): {}\n\n{}\n\n{}\n{}\n",
+            self.title,
+            crate::render::puzzle((&self.b1, p1), (&self.b2, p2)),
+            moves.len(),
+            moves.join(" "),
+            analysis,
+            self.message,
+            HELP
+        )
+    }
+}
+
+/// Open the dashboard on a puzzle
+pub fn run(input: &str) -> Result<()> {
+    let mut dashboard = Dashboard::new(input)?;
+    let mut screen = Screen::enter()?;
+    loop {
+        screen.draw(&dashboard.screen())?;
+
+        match screen.key()? {
+            Some(key) if dashboard.key(key) => {}
+            _ => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dashboard;
+    use crate::term::Key;
+
+    #[test]
+    fn keys() {
+        let mut dashboard = Dashboard::new(" x\nTRT\n\n x\nR.I.").unwrap();
+
+        assert!(dashboard.key(Key::Char('n')));
+        assert!(dashboard.key(Key::Left));
+        assert_eq!(2, dashboard.moves.len());
+        assert!(dashboard
+            .screen()
+            .contains("Solution from here (2 moves):\n    3. Right\n    4. Up"));
+
+        dashboard.key(Key::Char('z'));
+        assert_eq!(1, dashboard.moves.len());
+        assert!(!dashboard.key(Key::Char('q')));
+    }
+}
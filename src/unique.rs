@@ -0,0 +1,151 @@
+//! Checking that a puzzle has only the one shortest solution
+//!
+//! A shortcut the designer didn't mean to leave is the most common mistake
+//! in a level. Every shortest solution is counted, a few of them listed, and
+//! the intended solution, from `--intended` or the puzzle's `solution`
+//! metadata, is checked against them.
+
+use crate::graph::{Edge, Graph, DIRS};
+use crate::verify::{self, Verdict};
+use crate::{metadata, output, Dir, Error, Result};
+
+/// How many shortest solutions are listed when there are several
+const LISTED: usize = 10;
+
+/// The shortest solutions from a state, up to `limit` of them, in the order
+/// of [`DIRS`]
+fn shortest(graph: &Graph, to_goal: &[Option<usize>], state: usize, limit: usize) -> Vec<Vec<Dir>> {
+    let mut found = Vec::new();
+    for (dir, edge) in DIRS.into_iter().zip(&graph.edges[state]) {
+        if found.len() == limit {
+            break;
+        }
+        match *edge {
+            Edge::Solved if to_goal[state] == Some(1) => found.push(vec![dir]),
+            Edge::To(to) if to_goal[to].is_some() && to_goal[to] < to_goal[state] => {
+                for rest in shortest(graph, to_goal, to, limit - found.len()) {
+                    found.push([&[dir][..], &rest].concat());
+                }
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+/// How many shortest solutions there are from each state
+fn counts(graph: &Graph, to_goal: &[Option<usize>]) -> Vec<u64> {
+    // Nearer states first, so every state's successors are counted before it
+    let mut order: Vec<usize> = (0..graph.states.len())
+        .filter(|&i| to_goal[i].is_some())
+        .collect();
+    order.sort_by_key(|&i| to_goal[i]);
+
+    let mut counts = vec![0u64; graph.states.len()];
+    for state in order {
+        counts[state] = graph.edges[state]
+            .iter()
+            .map(|edge| match *edge {
+                Edge::Solved if to_goal[state] == Some(1) => 1,
+                Edge::To(to) if to_goal[to].is_some() && to_goal[to] < to_goal[state] => counts[to],
+                _ => 0,
+            })
+            .fold(0, u64::saturating_add);
+    }
+    counts
+}
+
+/// Check the puzzle's shortest solution is unique, and is the intended one
+/// if there's one given, returning what was found and whether it is
+pub fn check(input: &str, intended: Option<&str>) -> Result<(String, bool)> {
+    let (metadata, _) = metadata::split(input);
+    let intended = match intended.or(metadata::get(&metadata, "solution")) {
+        Some(moves) => Some(Dir::parse_moves(moves)?),
+        None => None,
+    };
+
+    let graph = Graph::of(input)?;
+    let to_goal = graph.to_goal();
+    let length = to_goal[0].ok_or(Error::NoSolution)?;
+    let count = counts(&graph, &to_goal)[0];
+    let solutions = shortest(&graph, &to_goal, 0, LISTED);
+
+    let mut out = String::new();
+    let mut unique = count == 1;
+    if let Some(intended) = &intended {
+        match verify::verify(input, intended)? {
+            Verdict::Solved { moves } if moves == length => {}
+            Verdict::Solved { moves } => {
+                out.push_str(&format!(
+                    "The intended solution takes {} moves, but {} is enough\n",
+                    moves, length
+                ));
+                unique = false;
+            }
+            verdict => {
+                out.push_str(&format!(
+                    "The intended solution doesn't work: {}\n",
+                    verdict
+                ));
+                unique = false;
+            }
+        }
+    }
+
+    if count == 1 {
+        out.push_str(&format!(
+            "The shortest solution is unique: {} ({} moves)\n",
+            output::compact(&solutions[0]),
+            length
+        ));
+    } else {
+        out.push_str(&format!(
+            "There are {} shortest solutions ({} moves):\n",
+            count, length
+        ));
+        for solution in &solutions {
+            let mark = match intended.as_ref().is_some_and(|i| i.starts_with(solution)) {
+                true => " (intended)",
+                false => "",
+            };
+            out.push_str(&format!("  {}{}\n", output::compact(solution), mark));
+        }
+        if count > solutions.len() as u64 {
+            out.push_str(&format!("  and {} more\n", count - solutions.len() as u64));
+        }
+    }
+
+    Ok((out, unique))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn check() {
+        assert_eq!(
+            (
+                "The shortest solution is unique: RUU (3 moves)\n".to_string(),
+                true
+            ),
+            super::check("  x\nW..\n.R.\n\n x\n...\n.RW", None).unwrap()
+        );
+
+        // Up and right can go either way round when nothing's in the way
+        let open = "  x\n...\n.R.\n\n  x\n...\n.R.";
+        assert_eq!(
+            (
+                "The intended solution takes 5 moves, but 3 is enough\n\
+                 There are 2 shortest solutions (3 moves):\n  \
+                   URU\n  \
+                   RUU\n"
+                    .to_string(),
+                false
+            ),
+            super::check(open, Some("LURRU")).unwrap()
+        );
+        assert!(super::check(open, Some("URU"))
+            .unwrap()
+            .0
+            .contains("URU (intended)"));
+    }
+}
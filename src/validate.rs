@@ -0,0 +1,156 @@
+//! Checking puzzles for mistakes without solving them
+
+use crate::{render, Board, Player, Result, Side, Tile};
+
+/// Problems with a single board that parse fine but can't be what was meant
+fn check_board(board: &Board) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let teleports = board
+        .tiles
+        .iter()
+        .flatten()
+        .filter(|t| matches!(t, Tile::Teleport))
+        .count();
+    match teleports {
+        1 => problems.push("has a teleport with nowhere to go".to_string()),
+        n if n > 2 => problems.push(format!("has {} teleports, but they only work in pairs", n)),
+        _ => {}
+    }
+
+    match exit_inside(board).map(|p| board.get_tile(p)) {
+        None => problems.push("has an exit that isn't next to the board".to_string()),
+        Some(Tile::Wall) => problems.push("has an exit blocked by a wall".to_string()),
+        _ => {}
+    }
+
+    problems
+}
+
+/// The cell on the board next to the exit, if there is one
+pub(crate) fn exit_inside(board: &Board) -> Option<Player> {
+    let exit = board.exit_cell();
+    [(0, 1), (0, -1), (1, 0), (-1, 0)]
+        .into_iter()
+        .map(|(dx, dy)| Player {
+            x: exit.x + dx,
+            y: exit.y + dy,
+        })
+        .find(|p| {
+            usize::try_from(p.y)
+                .ok()
+                .zip(usize::try_from(p.x).ok())
+                .is_some_and(|(y, x)| board.tiles.get(y).and_then(|r| r.get(x)).is_some())
+        })
+}
+
+/// Parse a puzzle and list anything wrong with it
+pub fn validate(input: &str) -> Result<Vec<String>> {
+    let ((b1, _), (b2, _)) = crate::parse_puzzle(input)?;
+    crate::limits::check(&b1)?;
+    crate::limits::check(&b2)?;
+
+    Ok(check_board(&b1)
+        .into_iter()
+        .map(|p| format!("Board 1 {}", p))
+        .chain(
+            check_board(&b2)
+                .into_iter()
+                .map(|p| format!("Board 2 {}", p)),
+        )
+        .collect())
+}
+
+/// Where a player is, for people
+fn cell(p: Player) -> String {
+    format!("({}, {})", p.x, p.y)
+}
+
+/// Describe how a board was read: its size, the player, the exit and the
+/// teleports
+fn describe(board: &Board, player: Player) -> String {
+    let width = board.tiles.iter().map(Vec::len).max().unwrap_or(0);
+    let exit = match board.exit.side {
+        Side::Top => format!("above column {}", board.exit.pos),
+        Side::Bottom => format!("below column {}", board.exit.pos),
+        Side::Left => format!("left of row {}", board.exit.pos),
+        Side::Right => format!("right of row {}", board.exit.pos),
+    };
+    let teleports = board
+        .tiles
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.iter().enumerate().filter_map(move |(x, tile)| {
+                matches!(tile, Tile::Teleport).then_some(Player {
+                    x: x as isize,
+                    y: y as isize,
+                })
+            })
+        })
+        .map(cell)
+        .collect::<Vec<_>>();
+    let teleports = match teleports.len() {
+        0 => "no teleports".to_string(),
+        2 => format!("teleports {} <-> {}", teleports[0], teleports[1]),
+        _ => format!("teleports at {}", teleports.join(", ")),
+    };
+
+    format!(
+        "{}x{}, player at {}, exit {}, {}",
+        width,
+        board.tiles.len(),
+        cell(player),
+        exit,
+        teleports
+    )
+}
+
+/// Print the boards the way the solver reads them, what it found on each,
+/// and anything wrong with them, for `--check`
+pub fn check(input: &str) -> Result<String> {
+    let problems = validate(input)?;
+    let ((b1, p1), (b2, p2)) = crate::parse_puzzle(input)?;
+
+    let mut out = render::puzzle((&b1, p1), (&b2, p2));
+    out.push_str(&format!("\nBoard 1: {}\n", describe(&b1, p1)));
+    out.push_str(&format!("Board 2: {}\n", describe(&b2, p2)));
+    if problems.is_empty() {
+        out.push_str("Puzzle looks good\n");
+    }
+    for problem in problems {
+        out.push_str(&format!("{}\n", problem));
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn problems() {
+        assert_eq!(Ok(vec![]), super::validate(" x\n.R.\n\n x\n.R."));
+        assert_eq!(
+            Ok(vec![
+                "Board 1 has a teleport with nowhere to go".to_string(),
+                "Board 2 has an exit blocked by a wall".to_string(),
+            ]),
+            super::validate(" x\nTR.\n\n x\nWWW\n.R.")
+        );
+    }
+
+    #[test]
+    fn check() {
+        assert_eq!(
+            "  x    <1\n\
+             ...    ...\n\
+             .I.    TPT\n\
+             .R.    .R.\n\
+             \n\
+             Board 1: 3x3, player at (1, 2), exit above column 2, no teleports\n\
+             Board 2: 3x3, player at (1, 2), exit left of row 1, teleports (0, 1) <-> (2, 1)\n\
+             Puzzle looks good\n",
+            super::check("  x\n...\n.I.\n.R.\n\n<1\n...\nTPT\n.R.").unwrap()
+        );
+    }
+}
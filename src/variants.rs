@@ -0,0 +1,221 @@
+//! Trying small changes to a puzzle to see what they do
+//!
+//! Each variant changes one thing on one board: two tiles swap places, the
+//! exit moves, or a floor tile becomes a pit. The table shows how the
+//! shortest solution changes, so a designer can see which changes make the
+//! puzzle harder, easier, or impossible, and then make them with `edit`.
+
+use crate::algorithm::{self, Algorithm};
+use crate::rng::Rng;
+use crate::{limits, Board, Error, Exit, Player, Result, Side, Stats, Tile};
+
+type Boards = [(Board, Player); 2];
+
+/// One change to one board
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Change {
+    Swap(Player, Player),
+    Exit(Exit),
+    Pit(Player),
+}
+
+impl Change {
+    /// Pick a change at random, if the one picked would change anything
+    fn random(rng: &mut Rng, (board, player): &(Board, Player)) -> Option<Self> {
+        let (width, height) = (board.tiles[0].len(), board.tiles.len());
+        let mut cell = || Player {
+            x: rng.below(width) as isize,
+            y: rng.below(height) as isize,
+        };
+        let (a, b) = (cell(), cell());
+
+        match rng.below(4) {
+            0 | 1 => {
+                let differ = board.get_tile(a).to_char() != board.get_tile(b).to_char();
+                (differ && a != *player && b != *player).then_some(Change::Swap(a, b))
+            }
+            2 => {
+                let exit = match rng.below(4) {
+                    0 => Exit {
+                        side: Side::Top,
+                        pos: a.x as usize,
+                    },
+                    1 => Exit {
+                        side: Side::Bottom,
+                        pos: a.x as usize,
+                    },
+                    2 => Exit {
+                        side: Side::Left,
+                        pos: a.y as usize,
+                    },
+                    _ => Exit {
+                        side: Side::Right,
+                        pos: a.y as usize,
+                    },
+                };
+                (exit != board.exit).then_some(Change::Exit(exit))
+            }
+            _ => {
+                let floor = matches!(board.get_tile(a), Tile::None);
+                (floor && a != *player).then_some(Change::Pit(a))
+            }
+        }
+    }
+
+    /// Make the change to a copy of the board
+    fn apply(self, (board, player): &(Board, Player)) -> (Board, Player) {
+        let mut board = board.clone();
+        let cell = |p: Player| (p.y as usize, p.x as usize);
+
+        match self {
+            Change::Swap(a, b) => {
+                let ((ay, ax), (by, bx)) = (cell(a), cell(b));
+                let tile = board.tiles[ay][ax];
+                board.tiles[ay][ax] = board.tiles[by][bx];
+                board.tiles[by][bx] = tile;
+            }
+            Change::Pit(p) => {
+                let (y, x) = cell(p);
+                board.tiles[y][x] = Tile::Pit;
+            }
+            Change::Exit(exit) => board.exit = exit,
+        }
+
+        (board, *player)
+    }
+
+    /// Say what the change is, given the board it's made to
+    fn describe(self, board: &Board) -> String {
+        let at = |p: Player| format!("({}, {})", p.x, p.y);
+
+        match self {
+            Change::Swap(a, b) => format!(
+                "swap {} {} with {} {}",
+                board.get_tile(a).to_char(),
+                at(a),
+                board.get_tile(b).to_char(),
+                at(b)
+            ),
+            Change::Pit(p) => format!("pit at {}", at(p)),
+            Change::Exit(exit) => match exit.side {
+                Side::Top => format!("exit above column {}", exit.pos),
+                Side::Bottom => format!("exit below column {}", exit.pos),
+                Side::Left => format!("exit left of row {}", exit.pos),
+                Side::Right => format!("exit right of row {}", exit.pos),
+            },
+        }
+    }
+}
+
+/// The length of the shortest solution, if there is one
+fn shortest([(b1, p1), (b2, p2)]: &Boards) -> Option<usize> {
+    algorithm::search(
+        Algorithm::Astar,
+        (b1, *p1),
+        (b2, *p2),
+        &mut Stats::default(),
+        limits::deadline(),
+        &mut |_| {},
+    )
+    .ok()
+    .map(|moves| moves.len())
+}
+
+/// How a variant's solution compares to the original's
+fn effect(before: Option<usize>, after: Option<usize>) -> String {
+    match (before, after) {
+        (Some(before), Some(after)) if before == after => "same".to_string(),
+        (Some(before), Some(after)) => format!("{:+}", after as isize - before as isize),
+        (Some(_), None) => "unsolvable".to_string(),
+        (None, Some(_)) => "now solvable".to_string(),
+        (None, None) => "still unsolvable".to_string(),
+    }
+}
+
+/// Try `count` different changes to the puzzle and tabulate what each does
+pub fn variants(input: &str, count: usize, rng: &mut Rng) -> Result<String> {
+    let (mut b1, mut b2) = crate::parse_puzzle(input)?;
+    for (board, _) in [&mut b1, &mut b2] {
+        limits::check(board)?;
+        // Short rows end in walls, as far as moving goes
+        let width = board.tiles.iter().map(Vec::len).max().unwrap_or(0);
+        for row in &mut board.tiles {
+            row.resize(width, Tile::Wall);
+        }
+    }
+    let boards = [b1, b2];
+    let original = shortest(&boards);
+
+    let mut tried = Vec::new();
+    // Small boards may not have `count` different changes to make
+    for _ in 0..count * 50 {
+        if tried.len() == count {
+            break;
+        }
+        let i = rng.below(2);
+        match Change::random(rng, &boards[i]) {
+            Some(change) if !tried.contains(&(i, change)) => tried.push((i, change)),
+            _ => {}
+        }
+    }
+    if tried.is_empty() {
+        return Err(Error::Usage(
+            "there's nothing on the boards to change".to_string(),
+        ));
+    }
+
+    let mut rows = vec![["board", "change", "moves", "effect"].map(String::from)];
+    for (i, change) in tried {
+        let mut variant = boards.clone();
+        variant[i] = change.apply(&boards[i]);
+        let moves = shortest(&variant);
+        rows.push([
+            (i + 1).to_string(),
+            change.describe(&boards[i].0),
+            moves.map_or("-".to_string(), |m| m.to_string()),
+            effect(original, moves),
+        ]);
+    }
+
+    let original = match original {
+        Some(moves) => format!("{} moves", moves),
+        None => "no solution".to_string(),
+    };
+    Ok(format!(
+        "Original: {}\n{}",
+        original,
+        crate::output::table(&rows)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Change;
+    use crate::rng::Rng;
+    use crate::{Exit, Player, Side};
+
+    #[test]
+    fn changes() {
+        let puzzle = "  x\n...\n.W.\n.R.\n\n x\n..\n.R";
+        let (b1, b2) = crate::parse_puzzle(puzzle).unwrap();
+        let at = |x, y| Player { x, y };
+
+        let exit = Change::Exit(Exit {
+            side: Side::Top,
+            pos: 0,
+        });
+        assert_eq!("exit above column 0", exit.describe(&b2.0));
+        assert_eq!(Some(6), super::shortest(&[b1.clone(), b2.clone()]));
+        assert_eq!(Some(7), super::shortest(&[b1.clone(), exit.apply(&b2)]));
+        let pit = Change::Pit(at(1, 0));
+        assert_eq!(None, super::shortest(&[b1.clone(), pit.apply(&b2)]));
+        assert_eq!(
+            "swap W (1, 1) with . (2, 1)",
+            Change::Swap(at(1, 1), at(2, 1)).describe(&b1.0)
+        );
+
+        let table = super::variants(puzzle, 5, &mut Rng::from_seed(3)).unwrap();
+        assert!(table.starts_with("Original: 6 moves\nboard  change"));
+        assert_eq!(2 + 5, table.lines().count());
+    }
+}
@@ -0,0 +1,193 @@
+//! Replay a proposed solution through the simulator
+
+use std::fmt;
+
+use crate::{animate, apply, Board, Dir, Player, PlayerState, Result, Tile};
+
+/// The outcome of replaying a move sequence
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    /// Both players reached the exit on the given (1-based) move
+    Solved { moves: usize },
+    /// A player fell into a pit
+    Died {
+        step: usize,
+        dir: Dir,
+        player: usize,
+    },
+    /// Only one of the players reached the exit
+    ExitedAlone {
+        step: usize,
+        dir: Dir,
+        player: usize,
+    },
+    /// The moves ran out before both players reached the exit
+    Incomplete { p1: Player, p2: Player },
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Solved { moves } => write!(f, "Both players exit after {} moves", moves),
+            Verdict::Died { step, dir, player } => write!(
+                f,
+                "Move {} ({:?}): player {} falls into a pit",
+                step, dir, player
+            ),
+            Verdict::ExitedAlone { step, dir, player } => write!(
+                f,
+                "Move {} ({:?}): only player {} reaches the exit",
+                step, dir, player
+            ),
+            Verdict::Incomplete { p1, p2 } => write!(
+                f,
+                "Out of moves with player 1 at ({}, {}) and player 2 at ({}, {})",
+                p1.x, p1.y, p2.x, p2.y
+            ),
+        }
+    }
+}
+
+/// What happens to both players after a single move
+#[derive(Debug, PartialEq)]
+pub enum Step {
+    /// Both players reached the exit
+    Solved,
+    /// The given player fell into a pit
+    Died(usize),
+    /// Only the given player reached the exit
+    ExitedAlone(usize),
+    /// Both players are still on their boards
+    Moved(Player, Player),
+}
+
+/// Move both players in the given direction
+pub fn step(dir: Dir, (b1, p1): (&Board, Player), (b2, p2): (&Board, Player)) -> Step {
+    match (apply(dir, b1, p1), apply(dir, b2, p2)) {
+        (PlayerState::Success, PlayerState::Success) => Step::Solved,
+        (PlayerState::Dead, _) => Step::Died(1),
+        (_, PlayerState::Dead) => Step::Died(2),
+        (PlayerState::Success, _) => Step::ExitedAlone(1),
+        (_, PlayerState::Success) => Step::ExitedAlone(2),
+        (PlayerState::Just(p1), PlayerState::Just(p2)) => Step::Moved(p1, p2),
+    }
+}
+
+/// How a single move ends for one player
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum End {
+    /// On a plain tile
+    Walked,
+    /// Against a wall, whether or not the player got anywhere first
+    Blocked,
+    /// Out the other teleport
+    Teleported,
+    Exited,
+    Fell,
+}
+
+/// Everything a single move does to one player
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Walk {
+    /// How many ice tiles the player slid across
+    pub slid: usize,
+    pub end: End,
+    /// Where the player ends up, just outside the board for the exit
+    pub to: Player,
+}
+
+impl Walk {
+    /// Follow the player through a move
+    pub fn new(dir: Dir, board: &Board, from: Player) -> Self {
+        let cells = animate::path(dir, board, from);
+        let to = *cells.last().unwrap();
+        let slid = cells[1..]
+            .iter()
+            .filter(|&&c| matches!(board.get_tile(c), Tile::Ice))
+            .count();
+
+        let end = match board.get_tile(to) {
+            _ if cells.len() == 1 => End::Blocked,
+            Tile::Exit => End::Exited,
+            Tile::Pit => End::Fell,
+            Tile::Teleport => End::Teleported,
+            Tile::Ice | Tile::Wall => End::Blocked,
+            Tile::None => End::Walked,
+        };
+
+        Walk { slid, end, to }
+    }
+}
+
+/// Replay the moves on the puzzle and report how far they get
+pub fn verify(input: &str, moves: &[Dir]) -> Result<Verdict> {
+    let ((b1, mut p1), (b2, mut p2)) = crate::parse_puzzle(input)?;
+
+    for (i, &dir) in moves.iter().enumerate() {
+        let step = i + 1;
+
+        match self::step(dir, (&b1, p1), (&b2, p2)) {
+            Step::Solved => return Ok(Verdict::Solved { moves: step }),
+            Step::Died(player) => return Ok(Verdict::Died { step, dir, player }),
+            Step::ExitedAlone(player) => return Ok(Verdict::ExitedAlone { step, dir, player }),
+            Step::Moved(n1, n2) => {
+                p1 = n1;
+                p2 = n2;
+            }
+        }
+    }
+
+    Ok(Verdict::Incomplete { p1, p2 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{End, Verdict, Walk};
+    use crate::Dir::{self, *};
+    use crate::Player;
+
+    const PUZZLE: &str = "  x\n...\n.I.\n.R.\n\n  x\n...\nTPT\n.R.";
+
+    #[test]
+    fn replay() {
+        let verify = |moves| super::verify(PUZZLE, &Dir::parse_moves(moves).unwrap());
+
+        assert_eq!(Ok(Verdict::Solved { moves: 5 }), verify("LURUU"));
+        assert_eq!(
+            Ok(Verdict::Solved { moves: 5 }),
+            verify(r#"["Left", "Up", "Right", "Up", "Up"]"#)
+        );
+        assert_eq!(
+            Ok(Verdict::Died {
+                step: 1,
+                dir: Up,
+                player: 2
+            }),
+            verify("U")
+        );
+    }
+
+    #[test]
+    fn walk() {
+        let ((b1, p1), (b2, p2)) = crate::parse_puzzle(PUZZLE).unwrap();
+
+        assert_eq!(
+            Walk {
+                slid: 1,
+                end: End::Walked,
+                to: Player { x: 1, y: 0 }
+            },
+            Walk::new(Up, &b1, p1)
+        );
+        assert_eq!(End::Fell, Walk::new(Up, &b2, p2).end);
+        assert_eq!(End::Blocked, Walk::new(Down, &b2, p2).end);
+        assert_eq!(
+            Walk {
+                slid: 0,
+                end: End::Teleported,
+                to: Player { x: 2, y: 1 }
+            },
+            Walk::new(Up, &b2, Player { x: 0, y: 2 })
+        );
+    }
+}
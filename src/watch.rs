@@ -0,0 +1,41 @@
+//! Re-running a command whenever the puzzle file is saved
+
+use std::time::{Duration, SystemTime};
+
+use crate::{Error, Result};
+
+/// How often to check the file for changes
+const POLL: Duration = Duration::from_millis(250);
+
+/// When the file was last changed, if it can be read at all
+fn modified(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Run `f` on the file's contents now and after every change, until
+/// interrupted
+pub fn watch(path: &str, mut f: impl FnMut(&str)) -> Result<()> {
+    let mut last = None;
+
+    loop {
+        let now = modified(path);
+        if now != last {
+            last = now;
+
+            // Editors can save in several steps, so let the file settle first
+            std::thread::sleep(POLL);
+            last = modified(path).or(last);
+
+            match std::fs::read_to_string(path) {
+                Ok(input) => {
+                    println!("--- {} ---", path);
+                    f(&input);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(Error::Io(e.kind())),
+            }
+        }
+
+        std::thread::sleep(POLL);
+    }
+}
@@ -0,0 +1,161 @@
+//! Solving every puzzle under `tests/puzzles/` and holding the results to the
+//! lengths committed in `tests/puzzles/expected`
+//!
+//! The solver is run as a program, the way it's used, and every solution is
+//! replayed with `verify` as well, so a refactor that changes what gets
+//! found, or finds something that doesn't work, shows up here. Run with
+//! `BLESS=1` to write the lengths found back to the expectations instead.
+//!
+//! Every puzzle is also solved twice over with each algorithm, in separate
+//! processes, and the two runs have to print exactly the same thing, so
+//! nothing comes to depend on hash map order or the like.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// What a puzzle came to: the shortest solution's length, or `None` when it
+/// has no solution
+type Length = Option<usize>;
+
+/// Run a command without picking up a user's config
+fn run(command: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hive-mind-solver"))
+        .arg(command)
+        .args(["--config", "/dev/null", "--algorithm", "bfs", "--quiet"])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// The committed lengths, by file name
+fn expected(text: &str) -> BTreeMap<String, Length> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (name, length) = line.split_once(' ').unwrap();
+            let length = match length.trim() {
+                "none" => None,
+                n => Some(n.parse().unwrap()),
+            };
+            (name.to_string(), length)
+        })
+        .collect()
+}
+
+/// Solve the puzzle, checking that the solution it finds works
+fn solve(path: &Path) -> std::result::Result<Length, String> {
+    let path = path.to_str().unwrap();
+    let solved = run("solve", &["--output", "compact", path]);
+    match solved.status.code() {
+        Some(0) => {}
+        Some(4) => return Ok(None),
+        _ => return Err(String::from_utf8_lossy(&solved.stdout).into_owned()),
+    }
+
+    let moves = String::from_utf8_lossy(&solved.stdout).trim().to_string();
+    let verified = run("verify", &[&moves, path]);
+    let verdict = String::from_utf8_lossy(&verified.stdout).trim().to_string();
+    match verdict == format!("Both players exit after {} moves", moves.len()) {
+        true => Ok(Some(moves.len())),
+        false => Err(format!("{} doesn't solve it: {}", moves, verdict)),
+    }
+}
+
+/// The puzzles under `tests/puzzles/`, by file name
+fn puzzles(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".txt"))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn corpus() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/puzzles");
+    let expectations = dir.join("expected");
+    let mut expected = expected(&std::fs::read_to_string(&expectations).unwrap());
+
+    let names = puzzles(&dir);
+
+    let mut found = BTreeMap::new();
+    let mut failures = Vec::new();
+    for name in &names {
+        match solve(&dir.join(name)) {
+            Ok(length) => {
+                found.insert(name.clone(), length);
+            }
+            Err(err) => failures.push(format!("{}: {}", name, err)),
+        }
+    }
+
+    if std::env::var_os("BLESS").is_some() {
+        let header: String = std::fs::read_to_string(&expectations)
+            .unwrap()
+            .lines()
+            .take_while(|line| line.starts_with('#'))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let lines: String = found
+            .iter()
+            .map(|(name, length)| match length {
+                Some(n) => format!("{} {}\n", name, n),
+                None => format!("{} none\n", name),
+            })
+            .collect();
+        std::fs::write(&expectations, header + &lines).unwrap();
+        expected = found.clone();
+    }
+
+    for name in &names {
+        match (expected.get(name), found.get(name)) {
+            (None, _) => failures.push(format!("{}: no expectation committed", name)),
+            (Some(want), Some(got)) if want != got => {
+                failures.push(format!("{}: expected {:?}, found {:?}", name, want, got))
+            }
+            _ => {}
+        }
+    }
+    for name in expected.keys().filter(|name| !names.contains(name)) {
+        failures.push(format!("{}: expected, but there's no such puzzle", name));
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn deterministic() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/puzzles");
+
+    let mut failures = Vec::new();
+    for name in puzzles(&dir) {
+        let path = dir.join(&name);
+        for algorithm in ["bfs", "dfs", "astar", "ida", "beam"] {
+            let solve = || {
+                // The heatmap shows what the search looked at, not just what it found
+                Command::new(env!("CARGO_BIN_EXE_hive-mind-solver"))
+                    .arg("solve")
+                    .args(["--config", "/dev/null", "--quiet", "--seed", "7"])
+                    .args(["--algorithm", algorithm, "--heatmap"])
+                    .arg(&path)
+                    .output()
+                    .unwrap()
+            };
+            let (first, second) = (solve(), solve());
+            if (first.status, &first.stdout) != (second.status, &second.stdout) {
+                failures.push(format!(
+                    "{} with {}:\n{}\nthen:\n{}",
+                    name,
+                    algorithm,
+                    String::from_utf8_lossy(&first.stdout),
+                    String::from_utf8_lossy(&second.stdout)
+                ));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
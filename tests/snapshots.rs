@@ -0,0 +1,84 @@
+//! Holding what the renderers draw to the snapshots under `tests/snapshots/`
+//!
+//! Each puzzle here is drawn every way there is to draw it, and each drawing
+//! has to match its snapshot byte for byte, so a new kind of tile that one
+//! renderer forgets, or a change that shifts a board over, shows up as a
+//! diff. Run with `BLESS=1` to write what's drawn now back to the snapshots
+//! instead, then look over the changes before committing them.
+//!
+//! The drawings have to come out the same whichever features are on, so only
+//! stdout is compared, and anything a feature keeps on the side, like the
+//! `sqlite` results database, goes in a scratch directory instead of the
+//! user's.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Puzzles between them using every kind of tile, with exits on two sides
+const PUZZLES: &[&str] = &["roomy", "side-exits"];
+
+/// Each drawing, by the end of its snapshot's file name, and how to make it
+const DRAWINGS: &[(&str, &[&str])] = &[
+    ("text.txt", &["render"]),
+    ("color.txt", &["render", "--color", "always"]),
+    ("box.txt", &["render", "--format", "box"]),
+    ("path.txt", &["render", "--path"]),
+    ("reach.txt", &["render", "--reach"]),
+    ("reach-plain.txt", &["render", "--reach", "--legend", "off"]),
+    ("svg", &["render", "--format", "svg"]),
+    ("explain.txt", &["solve", "--explain"]),
+];
+
+/// Draw the puzzle without picking up a user's config or touching their data
+fn draw(args: &[&str], path: &Path, data: &Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_hive-mind-solver"))
+        .args(args)
+        .args(["--config", "/dev/null"])
+        .arg(path)
+        .env("XDG_DATA_HOME", data)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{} {}: {}",
+        args.join(" "),
+        path.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn snapshots() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let bless = std::env::var_os("BLESS").is_some();
+    let data =
+        std::env::temp_dir().join(format!("hive-mind-solver-snapshots-{}", std::process::id()));
+
+    let mut failures = Vec::new();
+    for puzzle in PUZZLES {
+        let path = root.join("puzzles").join(format!("{}.txt", puzzle));
+        for (name, args) in DRAWINGS {
+            let drawn = draw(args, &path, &data);
+            let snapshot = root.join("snapshots").join(format!("{}.{}", puzzle, name));
+
+            if bless {
+                std::fs::write(&snapshot, &drawn).unwrap();
+                continue;
+            }
+            match std::fs::read_to_string(&snapshot) {
+                Ok(expected) if expected == drawn => {}
+                Ok(expected) => failures.push(format!(
+                    "{} doesn't match:\n{}\nnow:\n{}",
+                    snapshot.display(),
+                    expected,
+                    drawn
+                )),
+                Err(_) => failures.push(format!("{}: no snapshot committed", snapshot.display())),
+            }
+        }
+    }
+
+    let _ = std::fs::remove_dir_all(&data);
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}